@@ -0,0 +1,68 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+use crypto::cipher::{Aes128,Aes256,BlockCipher};
+use crypto::ctr::{CounterLayout,CTR_encrypt};
+
+const MEGABYTES: usize = 4;
+
+fn backend() -> &'static str {
+    #[cfg(target_arch = "x86_64")]
+    if !cfg!(feature = "force-soft") && std::arch::is_x86_feature_detected!("aes") {
+        return "AES-NI";
+    }
+
+    if cfg!(feature = "ttable") {
+        "T-table"
+    } else if cfg!(feature = "constant-time") {
+        "constant-time"
+    } else {
+        "reference"
+    }
+}
+
+fn bench_blocks<C: BlockCipher>(name: &str, cipher: &C) {
+    let blocks = MEGABYTES * 1024 * 1024 / 16;
+
+    let start = Instant::now();
+    let mut block: [u8; 16] = [0; 16];
+    for _ in 0..blocks {
+        block = cipher.encrypt_block(black_box(block));
+    }
+    black_box(block);
+    report(&format!("{name} encrypt_block"), start.elapsed().as_secs_f64());
+
+    let start = Instant::now();
+    for _ in 0..blocks {
+        block = cipher.decrypt_block(black_box(block));
+    }
+    black_box(block);
+    report(&format!("{name} decrypt_block"), start.elapsed().as_secs_f64());
+}
+
+fn bench_ctr<C: BlockCipher>(name: &str, cipher: &C) {
+    let data = vec![0; MEGABYTES * 1024 * 1024];
+
+    let start = Instant::now();
+    let res = CTR_encrypt(cipher, black_box(data), [0; 16], CounterLayout::Counter128).unwrap();
+    black_box(res);
+    report(&format!("{name} CTR"), start.elapsed().as_secs_f64());
+}
+
+fn report(name: &str, seconds: f64) {
+    println!("{name:<24} {:>10.2} MB/s", MEGABYTES as f64 / seconds);
+}
+
+// Compare backends with `cargo bench` (AES-NI where available), `cargo bench --features force-soft`
+// (reference) and `cargo bench --features ttable,force-soft` (T-table)
+fn main() {
+    println!("backend: {}", backend());
+
+    let aes128 = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+    let aes256 = Aes256::new([0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4]);
+
+    bench_blocks("AES-128", &aes128);
+    bench_blocks("AES-256", &aes256);
+    bench_ctr("AES-128", &aes128);
+    bench_ctr("AES-256", &aes256);
+}
\ No newline at end of file
@@ -0,0 +1,392 @@
+use std::arch::x86_64::*;
+
+use crate::cipher::expand_key_words;
+use crate::util::zeroize;
+
+/// Description:
+/// Whether the CPU has the AES-NI instructions.
+pub fn has_aes() -> bool {
+    is_x86_feature_detected!("aes") && is_x86_feature_detected!("sse2")
+}
+
+/// Description:
+/// Whether the CPU can run the AES rounds on two blocks per 256-bit register.
+pub fn has_vaes() -> bool {
+    has_aes() && is_x86_feature_detected!("vaes") && is_x86_feature_detected!("avx2")
+}
+
+/// Description:
+/// Whether the CPU has carry-less multiplication for GHASH.
+pub fn has_pclmulqdq() -> bool {
+    is_x86_feature_detected!("pclmulqdq") && is_x86_feature_detected!("sse2")
+}
+
+/// Description:
+/// Round keys laid out for the AES instructions. The decryption keys are for the
+/// equivalent inverse cipher, in the order they are applied, with AESIMC already applied.
+/// Both are zeroed on drop.
+#[derive(Clone)]
+pub struct RoundKeys {
+    encrypt: Vec<[u8; 16]>,
+    decrypt: Vec<[u8; 16]>,
+    vaes: bool,
+}
+
+impl RoundKeys {
+    /// Description:
+    /// Expands the key with AESKEYGENASSIST standing in for SubWord.
+    ///
+    /// Arguments:
+    /// key - (I,REQ) - The key as 4, 6 or 8 big-endian words
+    ///
+    /// Returns:
+    /// The round keys, or None if the CPU lacks AES-NI
+    pub fn new<const NK: usize>(key: &[u32; NK]) -> Option<Self> {
+        if !has_aes() {
+            return None;
+        }
+
+        // SAFETY: has_aes() confirmed the CPU supports the aes and sse2 target features.
+        let (encrypt, decrypt) = unsafe { expand(key) };
+
+        Some(RoundKeys { encrypt, decrypt, vaes: has_vaes() })
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        // SAFETY: a RoundKeys only exists if the CPU supports AES-NI.
+        unsafe { encrypt_block(&self.encrypt, block) }
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        // SAFETY: a RoundKeys only exists if the CPU supports AES-NI.
+        unsafe { decrypt_block(&self.decrypt, block) }
+    }
+
+    pub fn encrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        if self.vaes {
+            // SAFETY: vaes is only set when has_vaes() found VAES and AVX2.
+            unsafe { vaes_encrypt_blocks(&self.encrypt, blocks) }
+        } else {
+            // SAFETY: a RoundKeys only exists if the CPU supports AES-NI.
+            unsafe { encrypt_blocks(&self.encrypt, blocks) }
+        }
+    }
+
+    pub fn decrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        if self.vaes {
+            // SAFETY: vaes is only set when has_vaes() found VAES and AVX2.
+            unsafe { vaes_decrypt_blocks(&self.decrypt, blocks) }
+        } else {
+            // SAFETY: a RoundKeys only exists if the CPU supports AES-NI.
+            unsafe { decrypt_blocks(&self.decrypt, blocks) }
+        }
+    }
+}
+
+impl Drop for RoundKeys {
+    fn drop(&mut self) {
+        zeroize(&mut self.encrypt);
+        zeroize(&mut self.decrypt);
+    }
+}
+
+#[target_feature(enable = "sse2")]
+fn load(bytes: [u8; 16]) -> __m128i {
+    let x = u128::from_le_bytes(bytes);
+    _mm_set_epi64x((x >> 64) as i64, x as i64)
+}
+
+#[target_feature(enable = "sse2")]
+fn to_u128(x: __m128i) -> u128 {
+    let low = _mm_cvtsi128_si64(x) as u64;
+    let high = _mm_cvtsi128_si64(_mm_unpackhi_epi64(x, x)) as u64;
+    (high as u128) << 64 | low as u128
+}
+
+#[target_feature(enable = "sse2")]
+fn store(x: __m128i) -> [u8; 16] {
+    to_u128(x).to_le_bytes()
+}
+
+#[target_feature(enable = "aes,sse2")]
+fn sub_word(word: u32) -> u32 {
+    // AESKEYGENASSIST puts SubWord of the second dword in the first. SubWord works
+    // on each byte alone, so the byte order of the word does not matter.
+    let x = _mm_set_epi32(0, 0, word as i32, 0);
+    _mm_cvtsi128_si32(_mm_aeskeygenassist_si128::<0>(x)) as u32
+}
+
+#[target_feature(enable = "aes,sse2")]
+fn expand<const NK: usize>(key: &[u32; NK]) -> (Vec<[u8; 16]>, Vec<[u8; 16]>) {
+    let mut words = expand_key_words(key, |word| sub_word(word));
+
+    let encrypt: Vec<[u8; 16]> = words.chunks_exact(4).map(|round| {
+        let mut bytes: [u8; 16] = [0; 16];
+        for (j, word) in round.iter().enumerate() {
+            bytes[4*j..4*j+4].copy_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }).collect();
+
+    let nr = encrypt.len() - 1;
+    let decrypt = (0..=nr).map(|round| {
+        let key = encrypt[nr-round];
+        if round == 0 || round == nr {
+            key
+        } else {
+            store(_mm_aesimc_si128(load(key)))
+        }
+    }).collect();
+    zeroize(&mut words);
+
+    (encrypt, decrypt)
+}
+
+#[target_feature(enable = "aes,sse2")]
+fn encrypt_block(keys: &[[u8; 16]], block: [u8; 16]) -> [u8; 16] {
+    let nr = keys.len() - 1;
+    let mut state = _mm_xor_si128(load(block), load(keys[0]));
+    for key in &keys[1..nr] {
+        state = _mm_aesenc_si128(state, load(*key));
+    }
+
+    store(_mm_aesenclast_si128(state, load(keys[nr])))
+}
+
+#[target_feature(enable = "aes,sse2")]
+fn decrypt_block(keys: &[[u8; 16]], block: [u8; 16]) -> [u8; 16] {
+    let nr = keys.len() - 1;
+    let mut state = _mm_xor_si128(load(block), load(keys[0]));
+    for key in &keys[1..nr] {
+        state = _mm_aesdec_si128(state, load(*key));
+    }
+
+    store(_mm_aesdeclast_si128(state, load(keys[nr])))
+}
+
+// Four independent blocks keep the AES unit busy while each round's result is pending.
+#[target_feature(enable = "aes,sse2")]
+fn encrypt_blocks(keys: &[[u8; 16]], blocks: &mut [[u8; 16]]) {
+    let nr = keys.len() - 1;
+    let (chunks, rest) = blocks.as_chunks_mut::<4>();
+    for chunk in chunks {
+        let key = load(keys[0]);
+        let mut state = chunk.map(|block| _mm_xor_si128(load(block), key));
+        for key in &keys[1..nr] {
+            let key = load(*key);
+            state = state.map(|s| _mm_aesenc_si128(s, key));
+        }
+        let key = load(keys[nr]);
+        *chunk = state.map(|s| store(_mm_aesenclast_si128(s, key)));
+    }
+    for block in rest {
+        *block = encrypt_block(keys, *block);
+    }
+}
+
+#[target_feature(enable = "aes,sse2")]
+fn decrypt_blocks(keys: &[[u8; 16]], blocks: &mut [[u8; 16]]) {
+    let nr = keys.len() - 1;
+    let (chunks, rest) = blocks.as_chunks_mut::<4>();
+    for chunk in chunks {
+        let key = load(keys[0]);
+        let mut state = chunk.map(|block| _mm_xor_si128(load(block), key));
+        for key in &keys[1..nr] {
+            let key = load(*key);
+            state = state.map(|s| _mm_aesdec_si128(s, key));
+        }
+        let key = load(keys[nr]);
+        *chunk = state.map(|s| store(_mm_aesdeclast_si128(s, key)));
+    }
+    for block in rest {
+        *block = decrypt_block(keys, *block);
+    }
+}
+
+#[target_feature(enable = "aes,vaes,avx2")]
+fn load_pair(pair: &[[u8; 16]; 2]) -> __m256i {
+    _mm256_set_m128i(load(pair[1]), load(pair[0]))
+}
+
+#[target_feature(enable = "aes,vaes,avx2")]
+fn store_pair(x: __m256i) -> [[u8; 16]; 2] {
+    [store(_mm256_castsi256_si128(x)), store(_mm256_extracti128_si256::<1>(x))]
+}
+
+#[target_feature(enable = "aes,vaes,avx2")]
+fn broadcast(key: [u8; 16]) -> __m256i {
+    _mm256_broadcastsi128_si256(load(key))
+}
+
+// Eight blocks per iteration, two in each 256-bit register.
+#[target_feature(enable = "aes,vaes,avx2")]
+fn vaes_encrypt_blocks(keys: &[[u8; 16]], blocks: &mut [[u8; 16]]) {
+    let nr = keys.len() - 1;
+    let (chunks, rest) = blocks.as_chunks_mut::<8>();
+    for chunk in chunks {
+        let (pairs, _) = chunk.as_chunks::<2>();
+        let key = broadcast(keys[0]);
+        let mut state: [__m256i; 4] = std::array::from_fn(|i| _mm256_xor_si256(load_pair(&pairs[i]), key));
+        for key in &keys[1..nr] {
+            let key = broadcast(*key);
+            state = state.map(|s| _mm256_aesenc_epi128(s, key));
+        }
+        let key = broadcast(keys[nr]);
+        let state = state.map(|s| store_pair(_mm256_aesenclast_epi128(s, key)));
+        *chunk = std::array::from_fn(|i| state[i/2][i%2]);
+    }
+    encrypt_blocks(keys, rest);
+}
+
+#[target_feature(enable = "aes,vaes,avx2")]
+fn vaes_decrypt_blocks(keys: &[[u8; 16]], blocks: &mut [[u8; 16]]) {
+    let nr = keys.len() - 1;
+    let (chunks, rest) = blocks.as_chunks_mut::<8>();
+    for chunk in chunks {
+        let (pairs, _) = chunk.as_chunks::<2>();
+        let key = broadcast(keys[0]);
+        let mut state: [__m256i; 4] = std::array::from_fn(|i| _mm256_xor_si256(load_pair(&pairs[i]), key));
+        for key in &keys[1..nr] {
+            let key = broadcast(*key);
+            state = state.map(|s| _mm256_aesdec_epi128(s, key));
+        }
+        let key = broadcast(keys[nr]);
+        let state = state.map(|s| store_pair(_mm256_aesdeclast_epi128(s, key)));
+        *chunk = std::array::from_fn(|i| state[i/2][i%2]);
+    }
+    decrypt_blocks(keys, rest);
+}
+
+/// Description:
+/// Multiplication in GF(2^128) with the GCM bit order, matching math::gf128_multiplication.
+/// The operands are bit-reflected polynomials, so the carry-less product comes out reflected
+/// and one bit short; it is shifted left by one and then reduced by x^128 = x^7 + x^2 + x + 1.
+///
+/// Arguments:
+/// x - (I,REQ) - A field element as a big-endian u128
+/// y - (I,REQ) - A field element as a big-endian u128
+///
+/// Returns:
+/// x * y
+#[target_feature(enable = "pclmulqdq,sse2")]
+pub fn gf128_multiplication(x: u128, y: u128) -> u128 {
+    let a = _mm_set_epi64x((x >> 64) as i64, x as i64);
+    let b = _mm_set_epi64x((y >> 64) as i64, y as i64);
+
+    let low = to_u128(_mm_clmulepi64_si128::<0x00>(a, b));
+    let high = to_u128(_mm_clmulepi64_si128::<0x11>(a, b));
+    let middle = to_u128(_mm_clmulepi64_si128::<0x01>(a, b)) ^ to_u128(_mm_clmulepi64_si128::<0x10>(a, b));
+
+    let product_high = high ^ (middle >> 64);
+    let product_low = low ^ (middle << 64);
+    let h = product_high << 1 | product_low >> 127;
+    let l = product_low << 1;
+
+    // l holds the coefficients of x^128 and up; the bits that fall off the end of
+    // its shifts wrap around through x^128 once more.
+    let overflow = l << 127 ^ l << 126 ^ l << 121;
+
+    h ^ l ^ l >> 1 ^ l >> 2 ^ l >> 7 ^ overflow ^ overflow >> 1 ^ overflow >> 2 ^ overflow >> 7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes, Aes128};
+    use crate::math;
+    use crate::util;
+    use rand::Rng;
+
+    fn reference_encrypt<const NK: usize>(cipher: &Aes<NK>, block: [u8; 16]) -> [u8; 16] {
+        let mut state = block;
+        cipher.encrypt_state(&mut state);
+        state
+    }
+
+    fn reference_decrypt<const NK: usize>(cipher: &Aes<NK>, block: [u8; 16]) -> [u8; 16] {
+        let mut state = block;
+        cipher.decrypt_state(&mut state);
+        state
+    }
+
+    fn cross_check<const NK: usize>() {
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let key: [u32; NK] = rng.random();
+            let cipher = Aes::<NK>::new(key);
+            let round_keys = RoundKeys::new(&key).unwrap();
+
+            let mut blocks: Vec<[u8; 16]> = (0..19).map(|_| rng.random()).collect();
+            let expected: Vec<[u8; 16]> = blocks.iter().map(|&block| reference_encrypt(&cipher, block)).collect();
+            for (&block, &ct) in blocks.iter().zip(&expected) {
+                assert_eq!(round_keys.encrypt_block(block), ct);
+                assert_eq!(round_keys.decrypt_block(ct), block);
+                assert_eq!(reference_decrypt(&cipher, ct), block);
+            }
+
+            let plain_text = blocks.clone();
+            round_keys.encrypt_blocks(&mut blocks);
+            assert_eq!(blocks, expected);
+            round_keys.decrypt_blocks(&mut blocks);
+            assert_eq!(blocks, plain_text);
+        }
+    }
+
+    #[test]
+    fn sub_word_test() {
+        if !has_aes() {
+            return;
+        }
+        let mut rng = rand::rng();
+        for _ in 0..1000 {
+            let word: u32 = rng.random();
+            // SAFETY: has_aes() was checked above.
+            assert_eq!(unsafe { sub_word(word) }, util::sub_word(word));
+        }
+    }
+
+    #[test]
+    fn cross_check_test() {
+        if !has_aes() {
+            return;
+        }
+        cross_check::<4>();
+        cross_check::<6>();
+        cross_check::<8>();
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn FIPS_197_test() {
+        if !has_aes() {
+            return;
+        }
+        let key: [u32; 4] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f];
+        let plain_text: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let cipher_text: [u8; 16] = [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a];
+
+        let round_keys = RoundKeys::new(&key).unwrap();
+        assert_eq!(round_keys.encrypt_block(plain_text), cipher_text);
+        assert_eq!(round_keys.decrypt_block(cipher_text), plain_text);
+
+        let cipher = Aes128::new(key);
+        assert_eq!(reference_encrypt(&cipher, plain_text), cipher_text);
+    }
+
+    #[test]
+    fn gf128_multiplication_test() {
+        if !has_pclmulqdq() {
+            return;
+        }
+        let mut rng = rand::rng();
+        for _ in 0..1000 {
+            let x: u128 = rng.random();
+            let y: u128 = rng.random();
+            // SAFETY: has_pclmulqdq() was checked above.
+            assert_eq!(unsafe { gf128_multiplication(x, y) }, math::gf128_multiplication(x, y));
+        }
+        // The multiplicative identity is x^0, the top bit in GCM order.
+        // SAFETY: has_pclmulqdq() was checked above.
+        assert_eq!(unsafe { gf128_multiplication(1 << 127, 0x0123456789abcdef) }, 0x0123456789abcdef);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,410 @@
+use crate::cipher::BlockCipher;
+use crate::error::CryptoError;
+use crate::padding::Padding;
+use crate::salt::get_iv_128;
+
+/// Description:
+/// Pads and encrypts with CBC.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// iv - (I,REQ) - The initialization vector, which must be unpredictable
+/// padding - (I,REQ) - The padding scheme
+///
+/// Returns:
+/// The cipher text without the IV, or InvalidLength if the padding scheme cannot pad the message
+#[allow(non_snake_case)]
+pub fn CBC_encrypt<C: BlockCipher, P: Padding>(cipher: &C, plain_text: Vec<u8>, iv: [u8; 16], padding: &P) -> Result<Vec<u8>, CryptoError> {
+    let mut cipher_text = padding.pad(plain_text, 16)?;
+    CBC_encrypt_blocks(cipher, &mut cipher_text, iv)?;
+
+    Ok(cipher_text)
+}
+
+/// Description:
+/// Decrypts CBC cipher text and removes the padding.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The cipher text produced by CBC_encrypt
+/// iv - (I,REQ) - The initialization vector used for encryption
+/// padding - (I,REQ) - The padding scheme used for encryption
+///
+/// Returns:
+/// The plain text, InvalidLength if the cipher text is not a multiple of 16
+/// or InvalidPadding if the padding is malformed
+#[allow(non_snake_case)]
+pub fn CBC_decrypt<C: BlockCipher, P: Padding>(cipher: &C, cipher_text: Vec<u8>, iv: [u8; 16], padding: &P) -> Result<Vec<u8>, CryptoError> {
+    let mut plain_text = cipher_text;
+    CBC_decrypt_blocks(cipher, &mut plain_text, iv)?;
+
+    padding.unpad(plain_text, 16)
+}
+
+/// Description:
+/// Encrypts with CBC under a fresh random IV and prepends the IV to the cipher text.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// padding - (I,REQ) - The padding scheme
+///
+/// Returns:
+/// IV || cipher text, or an error as for CBC_encrypt
+#[allow(non_snake_case)]
+pub fn CBC_encrypt_prefixed<C: BlockCipher, P: Padding>(cipher: &C, plain_text: Vec<u8>, padding: &P) -> Result<Vec<u8>, CryptoError> {
+    let iv = get_iv_128();
+    let cipher_text = CBC_encrypt(cipher, plain_text, iv, padding)?;
+
+    let mut message = Vec::with_capacity(16 + cipher_text.len());
+    message.extend(iv);
+    message.extend(cipher_text);
+
+    Ok(message)
+}
+
+/// Description:
+/// Decrypts a message produced by CBC_encrypt_prefixed.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - IV || cipher text
+/// padding - (I,REQ) - The padding scheme used for encryption
+///
+/// Returns:
+/// The plain text, or an error as for CBC_decrypt
+#[allow(non_snake_case)]
+pub fn CBC_decrypt_prefixed<C: BlockCipher, P: Padding>(cipher: &C, message: Vec<u8>, padding: &P) -> Result<Vec<u8>, CryptoError> {
+    if message.len() < 16 {
+        return Err(CryptoError::InvalidLength);
+    }
+    let iv: [u8; 16] = message[..16].try_into().unwrap();
+
+    CBC_decrypt(cipher, message[16..].to_vec(), iv, padding)
+}
+
+/// Description:
+/// Encrypts whole blocks with CBC in place, without any padding.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// text - (I/O,REQ) - Plain text on input, cipher text on output
+/// iv - (I,REQ) - The initialization vector
+///
+/// Returns:
+/// InvalidLength if text is not a multiple of 16
+#[allow(non_snake_case)]
+pub fn CBC_encrypt_blocks<C: BlockCipher>(cipher: &C, text: &mut [u8], iv: [u8; 16]) -> Result<(), CryptoError> {
+    if !text.len().is_multiple_of(16) {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let mut previous = iv;
+    for chunk in text.chunks_exact_mut(16) {
+        let mut block: [u8; 16] = chunk.try_into().unwrap();
+        for i in 0..16 {
+            block[i] ^= previous[i];
+        }
+        previous = cipher.encrypt_block(block);
+        chunk.copy_from_slice(&previous);
+    }
+
+    Ok(())
+}
+
+/// Description:
+/// Decrypts whole blocks with CBC in place, without any padding.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// text - (I/O,REQ) - Cipher text on input, plain text on output
+/// iv - (I,REQ) - The initialization vector
+///
+/// Returns:
+/// InvalidLength if text is not a multiple of 16
+#[allow(non_snake_case)]
+pub fn CBC_decrypt_blocks<C: BlockCipher>(cipher: &C, text: &mut [u8], iv: [u8; 16]) -> Result<(), CryptoError> {
+    if !text.len().is_multiple_of(16) {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let mut previous = iv;
+    for chunk in text.chunks_exact_mut(16) {
+        let block: [u8; 16] = chunk.try_into().unwrap();
+        let mut decrypted = cipher.decrypt_block(block);
+        for i in 0..16 {
+            decrypted[i] ^= previous[i];
+        }
+        previous = block;
+        chunk.copy_from_slice(&decrypted);
+    }
+
+    Ok(())
+}
+
+/// Description:
+/// Where the partial final block goes in CBC with ciphertext stealing (SP 800-38A Addendum).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CiphertextStealing {
+    /// The truncated penultimate cipher text block stays in place, before the last block.
+    Cs1,
+    /// The last two blocks are swapped only when the final block is partial.
+    Cs2,
+    /// The last two blocks are always swapped, as in Kerberos (RFC 3962).
+    Cs3,
+}
+
+/// Description:
+/// Encrypts with CBC and ciphertext stealing, so the cipher text is as long as the plain text.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt, at least 16 bytes long
+/// iv - (I,REQ) - The initialization vector, which must be unpredictable
+/// variant - (I,REQ) - The ordering of the last two blocks
+///
+/// Returns:
+/// The cipher text, or InvalidLength if the plain text is shorter than a block
+#[allow(non_snake_case)]
+pub fn CBC_CS_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, iv: [u8; 16], variant: CiphertextStealing) -> Result<Vec<u8>, CryptoError> {
+    if plain_text.len() < 16 {
+        return Err(CryptoError::InvalidLength);
+    }
+    let partial = plain_text.len() % 16;
+    let full = plain_text.len() - partial;
+
+    let mut cipher_text = plain_text;
+    CBC_encrypt_blocks(cipher, &mut cipher_text[..full], iv)?;
+    if partial == 0 {
+        if variant == CiphertextStealing::Cs3 && full > 16 {
+            swap_last_blocks(&mut cipher_text);
+        }
+        return Ok(cipher_text);
+    }
+
+    // The partial block is zero padded, so XORing it into the previous cipher text block
+    // only touches the first `partial` bytes
+    let previous: [u8; 16] = cipher_text[full-16..full].try_into().unwrap();
+    let mut block = previous;
+    for i in 0..partial {
+        block[i] ^= cipher_text[full+i];
+    }
+    let last = cipher.encrypt_block(block);
+
+    if variant == CiphertextStealing::Cs1 {
+        cipher_text[full-16+partial..].copy_from_slice(&last);
+    } else {
+        cipher_text[full-16..full].copy_from_slice(&last);
+        cipher_text[full..].copy_from_slice(&previous[..partial]);
+    }
+
+    Ok(cipher_text)
+}
+
+/// Description:
+/// Decrypts CBC cipher text with ciphertext stealing.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The cipher text produced by CBC_CS_encrypt
+/// iv - (I,REQ) - The initialization vector used for encryption
+/// variant - (I,REQ) - The ordering used for encryption
+///
+/// Returns:
+/// The plain text, or InvalidLength if the cipher text is shorter than a block
+#[allow(non_snake_case)]
+pub fn CBC_CS_decrypt<C: BlockCipher>(cipher: &C, cipher_text: Vec<u8>, iv: [u8; 16], variant: CiphertextStealing) -> Result<Vec<u8>, CryptoError> {
+    if cipher_text.len() < 16 {
+        return Err(CryptoError::InvalidLength);
+    }
+    let partial = cipher_text.len() % 16;
+    let full = cipher_text.len() - partial;
+
+    let mut plain_text = cipher_text;
+    if partial == 0 {
+        if variant == CiphertextStealing::Cs3 && full > 16 {
+            swap_last_blocks(&mut plain_text);
+        }
+        CBC_decrypt_blocks(cipher, &mut plain_text, iv)?;
+        return Ok(plain_text);
+    }
+
+    let (last, stolen) = if variant == CiphertextStealing::Cs1 {
+        (full-16+partial, full-16)
+    } else {
+        (full-16, full)
+    };
+    let last: [u8; 16] = plain_text[last..last+16].try_into().unwrap();
+    let mut stolen_block = [0; 16];
+    stolen_block[..partial].copy_from_slice(&plain_text[stolen..stolen+partial]);
+
+    // Decrypting the last block gives the previous cipher text block XOR the zero padded
+    // partial block, so its tail is the part of the previous block that was left out
+    let decrypted = cipher.decrypt_block(last);
+    let mut previous = decrypted;
+    previous[..partial].copy_from_slice(&stolen_block[..partial]);
+
+    plain_text[full-16..full].copy_from_slice(&previous);
+    for i in 0..partial {
+        plain_text[full+i] = decrypted[i] ^ stolen_block[i];
+    }
+    CBC_decrypt_blocks(cipher, &mut plain_text[..full], iv)?;
+
+    Ok(plain_text)
+}
+
+// Swaps the last two 16-byte blocks of a whole number of blocks
+fn swap_last_blocks(text: &mut [u8]) {
+    let n = text.len();
+    let (head, tail) = text[n-32..].split_at_mut(16);
+    head.swap_with_slice(tail);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes128,Aes256};
+    use crate::padding::{AnsiX923,NoPadding,Pkcs7};
+
+    const KEY: [u32; 8] = [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4];
+    const IV: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+    const PLAIN_TEXT: [u8; 64] = [0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
+    const CIPHER_TEXT: [u8; 64] = [0xf5, 0x8c, 0x4c, 0x04, 0xd6, 0xe5, 0xf1, 0xba, 0x77, 0x9e, 0xab, 0xfb, 0x5f, 0x7b, 0xfb, 0xd6, 0x9c, 0xfc, 0x4e, 0x96, 0x7e, 0xdb, 0x80, 0x8d, 0x67, 0x9f, 0x77, 0x7b, 0xc6, 0x70, 0x2c, 0x7d, 0x39, 0xf2, 0x33, 0x69, 0xa9, 0xd9, 0xba, 0xcf, 0xa5, 0x30, 0xe2, 0x63, 0x04, 0x23, 0x14, 0x61, 0xb2, 0xeb, 0x05, 0xe2, 0xc3, 0x9b, 0xe9, 0xfc, 0xda, 0x6c, 0x19, 0x07, 0x8c, 0x6a, 0x9d, 0x1b];
+    // "chicken teriyaki"
+    const KERBEROS_KEY: [u32; 4] = [0x63686963, 0x6b656e20, 0x74657269, 0x79616b69];
+    const KERBEROS_PLAIN_TEXT: [u8; 64] = [0x49, 0x20, 0x77, 0x6f, 0x75, 0x6c, 0x64, 0x20, 0x6c, 0x69, 0x6b, 0x65, 0x20, 0x74, 0x68, 0x65, 0x20, 0x47, 0x65, 0x6e, 0x65, 0x72, 0x61, 0x6c, 0x20, 0x47, 0x61, 0x75, 0x27, 0x73, 0x20, 0x43, 0x68, 0x69, 0x63, 0x6b, 0x65, 0x6e, 0x2c, 0x20, 0x70, 0x6c, 0x65, 0x61, 0x73, 0x65, 0x2c, 0x20, 0x61, 0x6e, 0x64, 0x20, 0x77, 0x6f, 0x6e, 0x74, 0x6f, 0x6e, 0x20, 0x73, 0x6f, 0x75, 0x70, 0x2e];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_encrypt_blocks_test() {
+        let cipher = Aes256::new(KEY);
+        let mut res = PLAIN_TEXT;
+        CBC_encrypt_blocks(&cipher, &mut res, IV).unwrap();
+        assert_eq!(res, CIPHER_TEXT);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_decrypt_blocks_test() {
+        let cipher = Aes256::new(KEY);
+        let mut res = CIPHER_TEXT;
+        CBC_decrypt_blocks(&cipher, &mut res, IV).unwrap();
+        assert_eq!(res, PLAIN_TEXT);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_encrypt_decrypt_test() {
+        let cipher = Aes256::new(KEY);
+        let res = CBC_encrypt(&cipher, PLAIN_TEXT.to_vec(), IV, &Pkcs7).unwrap();
+        assert_eq!(res.len(), 80);
+        assert_eq!(res[..64], CIPHER_TEXT);
+        let res = CBC_decrypt(&cipher, res, IV, &Pkcs7).unwrap();
+        assert_eq!(res, PLAIN_TEXT);
+
+        let res = CBC_encrypt(&cipher, PLAIN_TEXT.to_vec(), IV, &NoPadding).unwrap();
+        assert_eq!(res, CIPHER_TEXT);
+        let res = CBC_decrypt(&cipher, res, IV, &NoPadding).unwrap();
+        assert_eq!(res, PLAIN_TEXT);
+
+        let res = CBC_encrypt(&cipher, PLAIN_TEXT[..50].to_vec(), IV, &AnsiX923).unwrap();
+        assert_eq!(res[..48], CIPHER_TEXT[..48]);
+        let res = CBC_decrypt(&cipher, res, IV, &AnsiX923).unwrap();
+        assert_eq!(res, PLAIN_TEXT[..50]);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_decrypt_error_test() {
+        let cipher = Aes256::new(KEY);
+        let res = CBC_decrypt(&cipher, CIPHER_TEXT[..63].to_vec(), IV, &Pkcs7);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+
+        let res = CBC_decrypt(&cipher, CIPHER_TEXT.to_vec(), IV, &Pkcs7);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+
+        let res = CBC_decrypt_prefixed(&cipher, IV[..15].to_vec(), &Pkcs7);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+
+        let mut res = [0; 20];
+        assert_eq!(CBC_encrypt_blocks(&cipher, &mut res, IV), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_prefixed_test() {
+        let cipher = Aes256::new(KEY);
+        let plain_text = PLAIN_TEXT[..37].to_vec();
+        let message = CBC_encrypt_prefixed(&cipher, plain_text.clone(), &Pkcs7).unwrap();
+        assert_eq!(message.len(), 16 + 48);
+        let iv: [u8; 16] = message[..16].try_into().unwrap();
+        assert_eq!(message[16..], CBC_encrypt(&cipher, plain_text.clone(), iv, &Pkcs7).unwrap());
+        let res = CBC_decrypt_prefixed(&cipher, message, &Pkcs7).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_CS3_test() {
+        // RFC 3962 appendix B: AES-128 with IV 0 over "I would like the General Gau's Chicken, please, and wonton soup."
+        let cipher = Aes128::new(KERBEROS_KEY);
+        let vectors: [&[u8]; 6] = [
+            &[0xc6, 0x35, 0x35, 0x68, 0xf2, 0xbf, 0x8c, 0xb4, 0xd8, 0xa5, 0x80, 0x36, 0x2d, 0xa7, 0xff, 0x7f, 0x97],
+            &[0xfc, 0x00, 0x78, 0x3e, 0x0e, 0xfd, 0xb2, 0xc1, 0xd4, 0x45, 0xd4, 0xc8, 0xef, 0xf7, 0xed, 0x22, 0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5],
+            &[0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8, 0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84],
+            &[0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84, 0xb3, 0xff, 0xfd, 0x94, 0x0c, 0x16, 0xa1, 0x8c, 0x1b, 0x55, 0x49, 0xd2, 0xf8, 0x38, 0x02, 0x9e, 0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5],
+            &[0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84, 0x9d, 0xad, 0x8b, 0xbb, 0x96, 0xc4, 0xcd, 0xc0, 0x3b, 0xc1, 0x03, 0xe1, 0xa1, 0x94, 0xbb, 0xd8, 0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8],
+            &[0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84, 0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8, 0x48, 0x07, 0xef, 0xe8, 0x36, 0xee, 0x89, 0xa5, 0x26, 0x73, 0x0d, 0xbc, 0x2f, 0x7b, 0xc8, 0x40, 0x9d, 0xad, 0x8b, 0xbb, 0x96, 0xc4, 0xcd, 0xc0, 0x3b, 0xc1, 0x03, 0xe1, 0xa1, 0x94, 0xbb, 0xd8],
+        ];
+
+        for actual in vectors {
+            let plain_text = KERBEROS_PLAIN_TEXT[..actual.len()].to_vec();
+            let res = CBC_CS_encrypt(&cipher, plain_text.clone(), [0; 16], CiphertextStealing::Cs3).unwrap();
+            assert_eq!(res, actual);
+            let res = CBC_CS_decrypt(&cipher, res, [0; 16], CiphertextStealing::Cs3).unwrap();
+            assert_eq!(res, plain_text);
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_CS1_CS2_test() {
+        let cipher = Aes128::new(KERBEROS_KEY);
+        let vectors: [(CiphertextStealing, &[u8]); 5] = [
+            (CiphertextStealing::Cs1, &[0x97, 0xc6, 0x35, 0x35, 0x68, 0xf2, 0xbf, 0x8c, 0xb4, 0xd8, 0xa5, 0x80, 0x36, 0x2d, 0xa7, 0xff, 0x7f]),
+            (CiphertextStealing::Cs2, &[0xc6, 0x35, 0x35, 0x68, 0xf2, 0xbf, 0x8c, 0xb4, 0xd8, 0xa5, 0x80, 0x36, 0x2d, 0xa7, 0xff, 0x7f, 0x97]),
+            (CiphertextStealing::Cs1, &[0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84, 0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xb3, 0xff, 0xfd, 0x94, 0x0c, 0x16, 0xa1, 0x8c, 0x1b, 0x55, 0x49, 0xd2, 0xf8, 0x38, 0x02, 0x9e]),
+            (CiphertextStealing::Cs2, &[0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84, 0xb3, 0xff, 0xfd, 0x94, 0x0c, 0x16, 0xa1, 0x8c, 0x1b, 0x55, 0x49, 0xd2, 0xf8, 0x38, 0x02, 0x9e, 0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5]),
+            // Without a partial block CS1 and CS2 are plain CBC
+            (CiphertextStealing::Cs2, &[0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84, 0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8]),
+        ];
+
+        for (variant, actual) in vectors {
+            let plain_text = KERBEROS_PLAIN_TEXT[..actual.len()].to_vec();
+            let res = CBC_CS_encrypt(&cipher, plain_text.clone(), [0; 16], variant).unwrap();
+            assert_eq!(res, actual);
+            let res = CBC_CS_decrypt(&cipher, res, [0; 16], variant).unwrap();
+            assert_eq!(res, plain_text);
+        }
+
+        for variant in [CiphertextStealing::Cs1, CiphertextStealing::Cs2, CiphertextStealing::Cs3] {
+            for length in 16..=KERBEROS_PLAIN_TEXT.len() {
+                let plain_text = KERBEROS_PLAIN_TEXT[..length].to_vec();
+                let res = CBC_CS_encrypt(&cipher, plain_text.clone(), IV, variant).unwrap();
+                assert_eq!(res.len(), length);
+                assert_eq!(CBC_CS_decrypt(&cipher, res, IV, variant).unwrap(), plain_text);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CBC_CS_error_test() {
+        let cipher = Aes128::new(KERBEROS_KEY);
+        let res = CBC_CS_encrypt(&cipher, KERBEROS_PLAIN_TEXT[..15].to_vec(), IV, CiphertextStealing::Cs3);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+        let res = CBC_CS_decrypt(&cipher, Vec::new(), IV, CiphertextStealing::Cs1);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+    }
+}
\ No newline at end of file
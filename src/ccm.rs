@@ -0,0 +1,326 @@
+use crate::cipher::BlockCipher;
+use crate::ctr::{CounterLayout,Ctr};
+use crate::error::CryptoError;
+use crate::util::constant_time_eq;
+
+/// Description:
+/// AES-CCM authenticated encryption (RFC 3610, SP 800-38C): CBC-MAC over the formatted
+/// nonce, associated data and plain text, then CTR mode over the plain text and the tag.
+/// M is the tag length and L the size of the length field, which leaves a nonce of 15 - L bytes.
+pub struct Ccm<'a, C: BlockCipher> {
+    cipher: &'a C,
+    tag_length: usize,
+    length_size: usize,
+}
+
+impl<'a, C: BlockCipher> Ccm<'a, C> {
+    /// Description:
+    /// Creates a CCM instance for a keyed cipher.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// tag_length - (I,REQ) - M, the tag length in bytes: 4, 6, 8, 10, 12, 14 or 16
+    /// length_size - (I,REQ) - L, the size of the message length field in bytes, from 2 to 8
+    ///
+    /// Returns:
+    /// The CCM instance, or InvalidLength for unsupported parameters
+    pub fn new(cipher: &'a C, tag_length: usize, length_size: usize) -> Result<Self, CryptoError> {
+        if !(4..=16).contains(&tag_length) || !tag_length.is_multiple_of(2) || !(2..=8).contains(&length_size) {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        Ok(Ccm { cipher, tag_length, length_size })
+    }
+
+    /// Description:
+    /// Encrypts and authenticates plain text and authenticates the associated data.
+    ///
+    /// Arguments:
+    /// plain_text - (I,REQ) - The message to encrypt, shorter than 2^(8L) bytes
+    /// nonce - (I,REQ) - A 15 - L byte nonce that is never reused with the same key
+    /// aad - (I,REQ) - Associated data that is authenticated but not encrypted
+    ///
+    /// Returns:
+    /// cipher text || tag, or InvalidLength for a wrong nonce or message length
+    pub fn encrypt(&self, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        self.check_lengths(nonce, plain_text.len())?;
+
+        let mut tag = self.cbc_mac(nonce, aad, &plain_text);
+        let mut message = plain_text;
+        self.ctr(nonce, &mut tag, &mut message)?;
+        message.extend(&tag[..self.tag_length]);
+
+        Ok(message)
+    }
+
+    /// Description:
+    /// Decrypts and verifies the tag. The plain text is only returned if the tag matches.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - cipher text || tag as produced by encrypt
+    /// nonce - (I,REQ) - The nonce used for encryption
+    /// aad - (I,REQ) - The associated data used for encryption
+    ///
+    /// Returns:
+    /// The plain text, or AuthenticationFailed if the tag does not match
+    pub fn decrypt(&self, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if message.len() < self.tag_length {
+            return Err(CryptoError::InvalidLength);
+        }
+        self.check_lengths(nonce, message.len() - self.tag_length)?;
+
+        let mut plain_text = message;
+        let received_tag = plain_text.split_off(plain_text.len() - self.tag_length);
+        let mut mask: [u8; 16] = [0; 16];
+        self.ctr(nonce, &mut mask, &mut plain_text)?;
+
+        let tag = self.cbc_mac(nonce, aad, &plain_text);
+        let tag: Vec<u8> = tag.iter().zip(mask).map(|(t, s)| t ^ s).collect();
+        if !constant_time_eq(&tag[..self.tag_length], &received_tag) {
+            plain_text.fill(0);
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        Ok(plain_text)
+    }
+
+    fn check_lengths(&self, nonce: &[u8], message_length: usize) -> Result<(), CryptoError> {
+        if nonce.len() != 15 - self.length_size {
+            return Err(CryptoError::InvalidLength);
+        }
+        if self.length_size < 8 && message_length as u64 >> (8*self.length_size) != 0 {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        Ok(())
+    }
+
+    // A_i: flags holding L - 1, the nonce, then the counter i in the last L bytes
+    fn counter_block(&self, nonce: &[u8]) -> [u8; 16] {
+        let mut block: [u8; 16] = [0; 16];
+        block[0] = (self.length_size - 1) as u8;
+        block[1..16-self.length_size].copy_from_slice(nonce);
+        block
+    }
+
+    // XORs S_0 into tag and S_1, S_2, ... into data. The counter cannot carry into the nonce
+    // because data is shorter than 2^(8L) bytes.
+    fn ctr(&self, nonce: &[u8], tag: &mut [u8; 16], data: &mut [u8]) -> Result<(), CryptoError> {
+        let mut ctr = Ctr::new(self.cipher, self.counter_block(nonce), CounterLayout::Counter128);
+        ctr.apply_keystream(tag)?;
+        ctr.apply_keystream(data)
+    }
+
+    fn cbc_mac(&self, nonce: &[u8], aad: &[u8], plain_text: &[u8]) -> [u8; 16] {
+        let mut b0 = self.counter_block(nonce);
+        b0[0] |= (((self.tag_length - 2) / 2) << 3) as u8;
+        if !aad.is_empty() {
+            b0[0] |= 0x40;
+        }
+        let length = (plain_text.len() as u64).to_be_bytes();
+        b0[16-self.length_size..].copy_from_slice(&length[8-self.length_size..]);
+
+        let mut x = self.cipher.encrypt_block(b0);
+        let mut absorb = |data: &[u8]| {
+            for chunk in data.chunks(16) {
+                for (x, byte) in x.iter_mut().zip(chunk) {
+                    *x ^= byte;
+                }
+                x = self.cipher.encrypt_block(x);
+            }
+        };
+
+        if !aad.is_empty() {
+            let mut encoded = encode_aad_length(aad.len());
+            encoded.extend(aad);
+            absorb(&encoded);
+        }
+        absorb(plain_text);
+
+        x
+    }
+}
+
+// The associated data length prefix of RFC 3610 section 2.2
+fn encode_aad_length(length: usize) -> Vec<u8> {
+    let length = length as u64;
+    if length < 0xff00 {
+        (length as u16).to_be_bytes().to_vec()
+    } else if length <= u32::MAX as u64 {
+        let mut encoded = vec![0xff, 0xfe];
+        encoded.extend((length as u32).to_be_bytes());
+        encoded
+    } else {
+        let mut encoded = vec![0xff, 0xff];
+        encoded.extend(length.to_be_bytes());
+        encoded
+    }
+}
+
+/// Description:
+/// Encrypts with AES-CCM, taking L from the nonce length.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// nonce - (I,REQ) - A nonce of 7 to 13 bytes that is never reused with the same key
+/// aad - (I,REQ) - Associated data
+/// tag_length - (I,REQ) - The tag length in bytes: 4, 6, 8, 10, 12, 14 or 16
+///
+/// Returns:
+/// cipher text || tag
+#[allow(non_snake_case)]
+pub fn CCM_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8], tag_length: usize) -> Result<Vec<u8>, CryptoError> {
+    Ccm::new(cipher, tag_length, 15 - nonce.len().clamp(7, 13))?.encrypt(plain_text, nonce, aad)
+}
+
+/// Description:
+/// Verifies and decrypts AES-CCM, taking L from the nonce length.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - cipher text || tag
+/// nonce - (I,REQ) - The nonce used for encryption
+/// aad - (I,REQ) - Associated data
+/// tag_length - (I,REQ) - The tag length in bytes
+///
+/// Returns:
+/// The plain text, or AuthenticationFailed if the tag does not match
+#[allow(non_snake_case)]
+pub fn CCM_decrypt<C: BlockCipher>(cipher: &C, message: Vec<u8>, nonce: &[u8], aad: &[u8], tag_length: usize) -> Result<Vec<u8>, CryptoError> {
+    Ccm::new(cipher, tag_length, 15 - nonce.len().clamp(7, 13))?.decrypt(message, nonce, aad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::Aes128;
+
+    // SP 800-38C appendix C key
+    const KEY: [u32; 4] = [0x40414243, 0x44454647, 0x48494a4b, 0x4c4d4e4f];
+    // RFC 3610 packet vectors key
+    const RFC_KEY: [u32; 4] = [0xc0c1c2c3, 0xc4c5c6c7, 0xc8c9cacb, 0xcccdcecf];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn RFC_3610_test() {
+        let cipher = Aes128::new(RFC_KEY);
+        let aad: [u8; 8] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+
+        // Packet vector #1
+        let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let plain_text: Vec<u8> = (0x08..0x1f).collect();
+        let res = CCM_encrypt(&cipher, plain_text.clone(), &nonce, &aad, 8).unwrap();
+        let actual: [u8; 31] = [0x58, 0x8c, 0x97, 0x9a, 0x61, 0xc6, 0x63, 0xd2, 0xf0, 0x66, 0xd0, 0xc2, 0xc0, 0xf9, 0x89, 0x80, 0x6d, 0x5f, 0x6b, 0x61, 0xda, 0xc3, 0x84, 0x17, 0xe8, 0xd1, 0x2c, 0xfd, 0xf9, 0x26, 0xe0];
+        assert_eq!(res, actual);
+
+        let res = CCM_decrypt(&cipher, res, &nonce, &aad, 8).unwrap();
+        assert_eq!(res, plain_text);
+
+        // Packet vector #2
+        let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let plain_text: Vec<u8> = (0x08..0x20).collect();
+        let res = CCM_encrypt(&cipher, plain_text.clone(), &nonce, &aad, 8).unwrap();
+        let actual: [u8; 32] = [0x72, 0xc9, 0x1a, 0x36, 0xe1, 0x35, 0xf8, 0xcf, 0x29, 0x1c, 0xa8, 0x94, 0x08, 0x5c, 0x87, 0xe3, 0xcc, 0x15, 0xc4, 0x39, 0xc9, 0xe4, 0x3a, 0x3b, 0xa0, 0x91, 0xd5, 0x6e, 0x10, 0x40, 0x09, 0x16];
+        assert_eq!(res, actual);
+
+        let res = CCM_decrypt(&cipher, res, &nonce, &aad, 8).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn SP_800_38C_test() {
+        let cipher = Aes128::new(KEY);
+
+        // Example 1: L = 8, M = 4
+        let nonce: [u8; 7] = [0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16];
+        let aad: Vec<u8> = (0x00..0x08).collect();
+        let plain_text: Vec<u8> = (0x20..0x24).collect();
+        let res = CCM_encrypt(&cipher, plain_text.clone(), &nonce, &aad, 4).unwrap();
+        let actual: [u8; 8] = [0x71, 0x62, 0x01, 0x5b, 0x4d, 0xac, 0x25, 0x5d];
+        assert_eq!(res, actual);
+
+        let res = CCM_decrypt(&cipher, res, &nonce, &aad, 4).unwrap();
+        assert_eq!(res, plain_text);
+
+        // Example 2: L = 7, M = 6
+        let nonce: Vec<u8> = (0x10..0x18).collect();
+        let aad: Vec<u8> = (0x00..0x10).collect();
+        let plain_text: Vec<u8> = (0x20..0x30).collect();
+        let res = CCM_encrypt(&cipher, plain_text.clone(), &nonce, &aad, 6).unwrap();
+        let actual: [u8; 22] = [0xd2, 0xa1, 0xf0, 0xe0, 0x51, 0xea, 0x5f, 0x62, 0x08, 0x1a, 0x77, 0x92, 0x07, 0x3d, 0x59, 0x3d, 0x1f, 0xc6, 0x4f, 0xbf, 0xac, 0xcd];
+        assert_eq!(res, actual);
+
+        let res = CCM_decrypt(&cipher, res, &nonce, &aad, 6).unwrap();
+        assert_eq!(res, plain_text);
+
+        // Example 3: L = 3, M = 8
+        let nonce: Vec<u8> = (0x10..0x1c).collect();
+        let aad: Vec<u8> = (0x00..0x14).collect();
+        let plain_text: Vec<u8> = (0x20..0x38).collect();
+        let res = CCM_encrypt(&cipher, plain_text.clone(), &nonce, &aad, 8).unwrap();
+        let actual: [u8; 32] = [0xe3, 0xb2, 0x01, 0xa9, 0xf5, 0xb7, 0x1a, 0x7a, 0x9b, 0x1c, 0xea, 0xec, 0xcd, 0x97, 0xe7, 0x0b, 0x61, 0x76, 0xaa, 0xd9, 0xa4, 0x42, 0x8a, 0xa5, 0x48, 0x43, 0x92, 0xfb, 0xc1, 0xb0, 0x99, 0x51];
+        assert_eq!(res, actual);
+
+        let res = CCM_decrypt(&cipher, res, &nonce, &aad, 8).unwrap();
+        assert_eq!(res, plain_text);
+
+        // Example 4: L = 2, M = 14 and 2^16 bytes of associated data, which needs the 0xfffe length prefix
+        let aad: Vec<u8> = (0..65536).map(|i| i as u8).collect();
+        let nonce: Vec<u8> = (0x10..0x1d).collect();
+        let plain_text: Vec<u8> = (0x20..0x40).collect();
+        let res = CCM_encrypt(&cipher, plain_text.clone(), &nonce, &aad, 14).unwrap();
+        let actual: [u8; 46] = [0x69, 0x91, 0x5d, 0xad, 0x1e, 0x84, 0xc6, 0x37, 0x6a, 0x68, 0xc2, 0x96, 0x7e, 0x4d, 0xab, 0x61, 0x5a, 0xe0, 0xfd, 0x1f, 0xae, 0xc4, 0x4c, 0xc4, 0x84, 0x82, 0x85, 0x29, 0x46, 0x3c, 0xcf, 0x72, 0xb4, 0xac, 0x6b, 0xec, 0x93, 0xe8, 0x59, 0x8e, 0x7f, 0x0d, 0xad, 0xbc, 0xea, 0x5b];
+        assert_eq!(res, actual);
+
+        let res = CCM_decrypt(&cipher, res, &nonce, &aad, 14).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    fn authentication_failure_test() {
+        let cipher = Aes128::new(RFC_KEY);
+        let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let aad: [u8; 8] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+        let message = CCM_encrypt(&cipher, (0x08..0x1f).collect(), &nonce, &aad, 8).unwrap();
+
+        let mut tampered = message.clone();
+        tampered[0] ^= 1;
+        let res = CCM_decrypt(&cipher, tampered, &nonce, &aad, 8);
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+
+        // A flipped tag bit
+        let mut tampered = message.clone();
+        tampered[30] ^= 1;
+        let res = CCM_decrypt(&cipher, tampered, &nonce, &aad, 8);
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(CCM_decrypt(&cipher, message.clone(), &nonce, &aad[1..], 8), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(CCM_decrypt(&cipher, message[..message.len()-1].to_vec(), &nonce, &aad, 8), Err(CryptoError::AuthenticationFailed));
+    }
+
+    #[test]
+    fn parameters_test() {
+        let cipher = Aes128::new(KEY);
+        for tag_length in [0, 2, 5, 18] {
+            assert!(Ccm::new(&cipher, tag_length, 2).is_err());
+        }
+        for length_size in [0, 1, 9] {
+            assert!(Ccm::new(&cipher, 8, length_size).is_err());
+        }
+
+        // The nonce must be 15 - L bytes
+        let ccm = Ccm::new(&cipher, 8, 2).unwrap();
+        assert_eq!(ccm.encrypt(vec![0; 16], &[0; 12], &[]), Err(CryptoError::InvalidLength));
+        assert_eq!(CCM_encrypt(&cipher, vec![0; 16], &[0; 6], &[], 8), Err(CryptoError::InvalidLength));
+        assert_eq!(CCM_encrypt(&cipher, vec![0; 16], &[0; 14], &[], 8), Err(CryptoError::InvalidLength));
+
+        // The message must fit in L bytes
+        assert_eq!(ccm.encrypt(vec![0; 65536], &[0; 13], &[]), Err(CryptoError::InvalidLength));
+        assert_eq!(ccm.decrypt(vec![0; 4], &[0; 13], &[]), Err(CryptoError::InvalidLength));
+
+        let message = ccm.encrypt(vec![0; 65535], &[0; 13], &[]).unwrap();
+        assert_eq!(ccm.decrypt(message, &[0; 13], &[]).unwrap(), vec![0; 65535]);
+    }
+}
\ No newline at end of file
@@ -1,178 +1,819 @@
-use crate::padding::{PKCS7_padding,PKCS7_unpadding};
+use crate::error::CryptoError;
+use crate::key::SecretKey;
+use crate::padding::Padding;
 use crate::math::{byte_matrix_multiplication};
-use crate::util::{block_to_message,message_to_block,rot_word,sub_word};
-use crate::util::{RCON,SBOX,INV_SBOX};
+#[cfg(feature = "ttable")]
+use crate::ttable;
+#[cfg(target_arch = "x86_64")]
+use crate::aesni;
+use crate::util::{block_to_message,zeroize};
+use crate::util::{inv_sub_byte,rot_word,sub_byte,sub_word};
+use crate::util::RCON;
+use crate::salt::get_iv_128;
 
-#[allow(non_snake_case)]
-pub fn AES_encrypt(plain_text: Vec<u8>, key: [u32; 8]) -> Vec<u8> {
-    let plain_text = PKCS7_padding(plain_text);
-    let n = plain_text.len();
+/// Description:
+/// Number of rounds for a key of nk 32-bit words (Nr = Nk + 6).
+///
+/// Arguments:
+/// nk - (I,REQ) - Key length in words, one of 4, 6 or 8
+///
+/// Returns:
+/// The number of rounds: 10, 12 or 14
+pub const fn rounds(nk: usize) -> usize {
+    nk + 6
+}
+
+/// Description:
+/// A cipher that encrypts and decrypts 16-byte blocks with an already expanded key.
+pub trait BlockCipher {
+    fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16];
+    fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16];
 
-    let mut cipher_text = Vec::with_capacity(n);
-    for i in 0..n/16 {
-        let message = &plain_text[16*i..(16*(i+1))];
-        let message_block = message_to_block(message);
-        let encrypted_block = AES_encrypt_block(message_block, key);
-        let encrypted_text = block_to_message(encrypted_block);
-        cipher_text.extend(encrypted_text);
+    /// Description:
+    /// Encrypts independent blocks in place, which lets a backend process several at once.
+    fn encrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        for block in blocks {
+            *block = self.encrypt_block(*block);
+        }
     }
 
-    cipher_text
+    /// Description:
+    /// Decrypts independent blocks in place, which lets a backend process several at once.
+    fn decrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        for block in blocks {
+            *block = self.decrypt_block(*block);
+        }
+    }
+
+    /// Description:
+    /// Encrypts a byte slice of whole blocks in place, each block independently, without allocating.
+    ///
+    /// Arguments:
+    /// data - (I/O,REQ) - Plain text on input, cipher text on output
+    ///
+    /// Returns:
+    /// InvalidLength if data is not a multiple of 16
+    fn encrypt_in_place(&self, data: &mut [u8]) -> Result<(), CryptoError> {
+        let (blocks, rest) = data.as_chunks_mut::<16>();
+        if !rest.is_empty() {
+            return Err(CryptoError::InvalidLength);
+        }
+        self.encrypt_blocks(blocks);
+
+        Ok(())
+    }
+
+    /// Description:
+    /// Decrypts a byte slice of whole blocks in place, each block independently, without allocating.
+    ///
+    /// Arguments:
+    /// data - (I/O,REQ) - Cipher text on input, plain text on output
+    ///
+    /// Returns:
+    /// InvalidLength if data is not a multiple of 16
+    fn decrypt_in_place(&self, data: &mut [u8]) -> Result<(), CryptoError> {
+        let (blocks, rest) = data.as_chunks_mut::<16>();
+        if !rest.is_empty() {
+            return Err(CryptoError::InvalidLength);
+        }
+        self.decrypt_blocks(blocks);
+
+        Ok(())
+    }
+
+    /// Description:
+    /// Encrypts whole blocks from one slice into a caller-provided slice of the same length.
+    ///
+    /// Arguments:
+    /// input - (I,REQ) - The plain text
+    /// output - (O,REQ) - Receives the cipher text
+    ///
+    /// Returns:
+    /// InvalidLength if the lengths differ or are not a multiple of 16
+    fn encrypt_into(&self, input: &[u8], output: &mut [u8]) -> Result<(), CryptoError> {
+        if input.len() != output.len() {
+            return Err(CryptoError::InvalidLength);
+        }
+        output.copy_from_slice(input);
+        self.encrypt_in_place(output)
+    }
+
+    /// Description:
+    /// Decrypts whole blocks from one slice into a caller-provided slice of the same length.
+    ///
+    /// Arguments:
+    /// input - (I,REQ) - The cipher text
+    /// output - (O,REQ) - Receives the plain text
+    ///
+    /// Returns:
+    /// InvalidLength if the lengths differ or are not a multiple of 16
+    fn decrypt_into(&self, input: &[u8], output: &mut [u8]) -> Result<(), CryptoError> {
+        if input.len() != output.len() {
+            return Err(CryptoError::InvalidLength);
+        }
+        output.copy_from_slice(input);
+        self.decrypt_in_place(output)
+    }
 }
 
-#[allow(non_snake_case)]
-pub fn AES_decrypt(cipher_text: Vec<u8>, key: [u32; 8]) -> Vec<u8> {
-    let n = cipher_text.len();
+/// Description:
+/// AES with a key of NK words, expanded once at construction.
+/// Decryption uses the equivalent inverse cipher (FIPS-197 section 5.3.5), so its
+/// round keys are kept in the order they are applied with InvMixColumns already folded in.
+/// On x86_64 CPUs with AES-NI the key schedule and every block run on the AES instructions,
+/// unless the force-soft feature is enabled; otherwise the software backend is used.
+/// All round keys are zeroed when the cipher is dropped.
+#[derive(Clone)]
+pub struct Aes<const NK: usize> {
+    key_schedule: Vec<[u8; 16]>,
+    inv_key_schedule: Vec<[u8; 16]>,
+    #[cfg(feature = "ttable")]
+    round_key_words: Vec<[u32; 4]>,
+    #[cfg(feature = "ttable")]
+    inv_round_key_words: Vec<[u32; 4]>,
+    #[cfg(target_arch = "x86_64")]
+    hardware: Option<aesni::RoundKeys>,
+}
+
+pub type Aes128 = Aes<4>;
+pub type Aes192 = Aes<6>;
+pub type Aes256 = Aes<8>;
+
+impl<const NK: usize> Aes<NK> {
+    /// Description:
+    /// Expands the key for encryption and decryption.
+    ///
+    /// Arguments:
+    /// key - (I,REQ) - The key as 4, 6 or 8 big-endian words
+    ///
+    /// Returns:
+    /// The keyed cipher
+    pub fn new(key: [u32; NK]) -> Self {
+        Aes::with_key(&SecretKey::new(key))
+    }
+
+    /// Description:
+    /// Expands a secret key for encryption and decryption.
+    ///
+    /// Arguments:
+    /// key - (I,REQ) - The key
+    ///
+    /// Returns:
+    /// The keyed cipher
+    pub fn with_key(key: &SecretKey<NK>) -> Self {
+        let mut expanded = key_expansion(key.expose());
+        let key_schedule: Vec<[u8; 16]> = expanded.iter().map(|round_key| block_to_message(*round_key)).collect();
+        zeroize(&mut expanded);
+        let nr = rounds(NK);
 
-    let mut plain_text = Vec::with_capacity(n);
-    for i in 0..n/16 {
-        let message = &cipher_text[16*i..(16*(i+1))];
-        let message_block = message_to_block(message);
-        let encrypted_block = AES_decrypt_block(message_block, key);
-        let encrypted_text = block_to_message(encrypted_block);
-        plain_text.extend(encrypted_text);
+        let mut inv_key_schedule = Vec::with_capacity(nr+1);
+        for round in (0..=nr).rev() {
+            let mut round_key = key_schedule[round];
+            if round != 0 && round != nr {
+                inv_mix_columns(&mut round_key);
+            }
+            inv_key_schedule.push(round_key);
+        }
+
+        Aes {
+            #[cfg(feature = "ttable")]
+            round_key_words: ttable::round_key_words(&key_schedule),
+            #[cfg(feature = "ttable")]
+            inv_round_key_words: ttable::round_key_words(&inv_key_schedule),
+            #[cfg(target_arch = "x86_64")]
+            hardware: if cfg!(feature = "force-soft") { None } else { aesni::RoundKeys::new(key.expose()) },
+            key_schedule,
+            inv_key_schedule,
+        }
     }
 
-    PKCS7_unpadding(plain_text)
+    /// Description:
+    /// Expands a key given as 4 * NK bytes.
+    ///
+    /// Arguments:
+    /// key - (I,REQ) - The key bytes
+    ///
+    /// Returns:
+    /// The keyed cipher, or InvalidKey if the length does not match the key size
+    pub fn from_bytes(key: &[u8]) -> Result<Self, CryptoError> {
+        Ok(Aes::with_key(&SecretKey::from_bytes(key)?))
+    }
+
+    /// Description:
+    /// Expands a key given as 8 * NK hex digits.
+    ///
+    /// Arguments:
+    /// key - (I,REQ) - The key in hexadecimal
+    ///
+    /// Returns:
+    /// The keyed cipher, or InvalidKey for a wrong length or a non-hex character
+    pub fn from_hex(key: &str) -> Result<Self, CryptoError> {
+        Ok(Aes::with_key(&SecretKey::from_hex(key)?))
+    }
+
+    /// Description:
+    /// Expands a key given in standard base64, with or without padding.
+    ///
+    /// Arguments:
+    /// key - (I,REQ) - The key in base64
+    ///
+    /// Returns:
+    /// The keyed cipher, or InvalidKey for a wrong length or malformed base64
+    pub fn from_base64(key: &str) -> Result<Self, CryptoError> {
+        Ok(Aes::with_key(&SecretKey::from_base64(key)?))
+    }
+
+    /// Description:
+    /// Encryption round keys, each laid out like a block (column by column).
+    #[cfg(test)]
+    pub(crate) fn key_schedule(&self) -> &[[u8; 16]] {
+        &self.key_schedule
+    }
+
+    /// Description:
+    /// Decryption round keys in the order the equivalent inverse cipher applies them.
+    #[cfg(test)]
+    pub(crate) fn inv_key_schedule(&self) -> &[[u8; 16]] {
+        &self.inv_key_schedule
+    }
+
+    /// Description:
+    /// Encrypts with the reference round functions, whichever backend encrypt_block uses.
+    ///
+    /// Arguments:
+    /// state - (I/O,REQ) - Plain text block on input, cipher text block on output
+    #[cfg_attr(feature = "ttable", allow(dead_code))]
+    pub(crate) fn encrypt_state(&self, state: &mut [u8; 16]) {
+        let nr = rounds(NK);
+
+        add_round_key(state, &self.key_schedule[0]);
+        for round_key in &self.key_schedule[1..nr] {
+            sub_bytes(state);
+            shift_rows(state);
+            mix_columns(state);
+            add_round_key(state, round_key);
+        }
+        sub_bytes(state);
+        shift_rows(state);
+        add_round_key(state, &self.key_schedule[nr]);
+    }
+
+    /// Description:
+    /// Decrypts with the reference round functions, whichever backend decrypt_block uses.
+    ///
+    /// Arguments:
+    /// state - (I/O,REQ) - Cipher text block on input, plain text block on output
+    #[cfg_attr(feature = "ttable", allow(dead_code))]
+    pub(crate) fn decrypt_state(&self, state: &mut [u8; 16]) {
+        let nr = rounds(NK);
+
+        add_round_key(state, &self.inv_key_schedule[0]);
+        for round_key in &self.inv_key_schedule[1..nr] {
+            inv_sub_bytes(state);
+            inv_shift_rows(state);
+            inv_mix_columns(state);
+            add_round_key(state, round_key);
+        }
+        inv_sub_bytes(state);
+        inv_shift_rows(state);
+        add_round_key(state, &self.inv_key_schedule[nr]);
+    }
 }
 
-#[allow(non_snake_case)]
-fn AES_encrypt_block(plain_text: [[u8; 4]; 4], key: [u32; 8]) -> [[u8; 4]; 4] {
-    let mut state = plain_text;
-    let key_schedule = key_expansion(key);
+impl<const NK: usize> Drop for Aes<NK> {
+    fn drop(&mut self) {
+        zeroize(&mut self.key_schedule);
+        zeroize(&mut self.inv_key_schedule);
+        #[cfg(feature = "ttable")]
+        zeroize(&mut self.round_key_words);
+        #[cfg(feature = "ttable")]
+        zeroize(&mut self.inv_round_key_words);
+    }
+}
+
+impl<const NK: usize> Aes<NK> {
+    #[cfg(not(feature = "ttable"))]
+    fn soft_encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut state = block;
+        self.encrypt_state(&mut state);
+        state
+    }
 
-    add_round_key(&mut state, key_schedule[0]);
-    for i in 1..14 {
-        sub_bytes(&mut state);
-        shift_rows(&mut state);
-        mix_columns(&mut state);
-        add_round_key(&mut state, key_schedule[i]);
+    #[cfg(not(feature = "ttable"))]
+    fn soft_decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut state = block;
+        self.decrypt_state(&mut state);
+        state
     }
-    sub_bytes(&mut state);
-    shift_rows(&mut state);
-    add_round_key(&mut state, key_schedule[14]);
 
-    state
+    #[cfg(feature = "ttable")]
+    fn soft_encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        ttable::encrypt_block(&self.round_key_words, block)
+    }
+
+    #[cfg(feature = "ttable")]
+    fn soft_decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        ttable::decrypt_block(&self.inv_round_key_words, block)
+    }
 }
 
+impl<const NK: usize> BlockCipher for Aes<NK> {
+    fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        #[cfg(target_arch = "x86_64")]
+        if let Some(hardware) = &self.hardware {
+            return hardware.encrypt_block(block);
+        }
+
+        self.soft_encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        #[cfg(target_arch = "x86_64")]
+        if let Some(hardware) = &self.hardware {
+            return hardware.decrypt_block(block);
+        }
+
+        self.soft_decrypt_block(block)
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        #[cfg(target_arch = "x86_64")]
+        if let Some(hardware) = &self.hardware {
+            return hardware.encrypt_blocks(blocks);
+        }
+
+        for block in blocks {
+            *block = self.soft_encrypt_block(*block);
+        }
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        #[cfg(target_arch = "x86_64")]
+        if let Some(hardware) = &self.hardware {
+            return hardware.decrypt_blocks(blocks);
+        }
+
+        for block in blocks {
+            *block = self.soft_decrypt_block(*block);
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn AES_encrypt<const NK: usize, P: Padding>(plain_text: Vec<u8>, key: [u32; NK], padding: &P) -> Result<Vec<u8>, CryptoError> {
+    ECB_encrypt(&Aes::new(key), plain_text, padding)
+}
+
+#[allow(non_snake_case)]
+pub fn AES_decrypt<const NK: usize, P: Padding>(cipher_text: Vec<u8>, key: [u32; NK], padding: &P) -> Result<Vec<u8>, CryptoError> {
+    ECB_decrypt(&Aes::new(key), cipher_text, padding)
+}
+
+/// Description:
+/// Pads and encrypts each block independently.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// padding - (I,REQ) - The padding scheme
+///
+/// Returns:
+/// The cipher text, or InvalidLength if the padding scheme cannot pad the message
+#[allow(non_snake_case)]
+pub fn ECB_encrypt<C: BlockCipher, P: Padding>(cipher: &C, plain_text: Vec<u8>, padding: &P) -> Result<Vec<u8>, CryptoError> {
+    let mut cipher_text = plain_text;
+    ECB_encrypt_in_place(cipher, &mut cipher_text, padding)?;
+
+    Ok(cipher_text)
+}
+
+/// Description:
+/// Decrypts each block independently and removes the padding.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The cipher text produced by ECB_encrypt
+/// padding - (I,REQ) - The padding scheme used for encryption
+///
+/// Returns:
+/// The plain text, InvalidLength if the cipher text is not a multiple of 16
+/// or InvalidPadding if the padding is malformed
+#[allow(non_snake_case)]
+pub fn ECB_decrypt<C: BlockCipher, P: Padding>(cipher: &C, cipher_text: Vec<u8>, padding: &P) -> Result<Vec<u8>, CryptoError> {
+    let mut plain_text = cipher_text;
+    ECB_decrypt_in_place(cipher, &mut plain_text, padding)?;
+
+    Ok(plain_text)
+}
+
+/// Description:
+/// Pads and encrypts a buffer in place. Only the padding can grow the buffer, so a buffer
+/// with at least 16 bytes of spare capacity is never reallocated and can be reused across calls.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// text - (I/O,REQ) - Plain text on input, cipher text on output
+/// padding - (I,REQ) - The padding scheme
+///
+/// Returns:
+/// InvalidLength if the padding scheme cannot pad the message
 #[allow(non_snake_case)]
-fn AES_decrypt_block(cipher_text: [[u8; 4]; 4], key: [u32; 8]) -> [[u8; 4]; 4] {
-    let mut state = cipher_text;
-    let key_schedule = key_expansion(key);
+pub fn ECB_encrypt_in_place<C: BlockCipher, P: Padding>(cipher: &C, text: &mut Vec<u8>, padding: &P) -> Result<(), CryptoError> {
+    padding.pad_in_place(text, 16)?;
+    cipher.encrypt_in_place(text)
+}
+
+/// Description:
+/// Decrypts a buffer in place and removes the padding, without allocating.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// text - (I/O,REQ) - Cipher text on input, plain text on output
+/// padding - (I,REQ) - The padding scheme used for encryption
+///
+/// Returns:
+/// InvalidLength if the cipher text is not a multiple of 16 or InvalidPadding if the padding is malformed
+#[allow(non_snake_case)]
+pub fn ECB_decrypt_in_place<C: BlockCipher, P: Padding>(cipher: &C, text: &mut Vec<u8>, padding: &P) -> Result<(), CryptoError> {
+    cipher.decrypt_in_place(text)?;
+    padding.unpad_in_place(text, 16)
+}
+
+/// Description:
+/// A mode that turns the block cipher into a stream cipher seeded by a 16-byte IV (SP 800-38A).
+/// Data can be passed in pieces of any length, including partial blocks; the mode carries its
+/// position within the current block from one call to the next.
+pub trait FeedbackMode<'a, C: BlockCipher>: Sized {
+    /// Description:
+    /// Starts the mode at the beginning of a message.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// iv - (I,REQ) - The initialization vector, unpredictable for CFB and unique for OFB
+    ///
+    /// Returns:
+    /// The mode
+    fn new(cipher: &'a C, iv: [u8; 16]) -> Self;
+
+    /// Description:
+    /// Encrypts the next part of the message in place.
+    ///
+    /// Arguments:
+    /// data - (I/O,REQ) - Plain text on input, cipher text on output
+    fn encrypt(&mut self, data: &mut [u8]);
+
+    /// Description:
+    /// Decrypts the next part of the message in place.
+    ///
+    /// Arguments:
+    /// data - (I/O,REQ) - Cipher text on input, plain text on output
+    fn decrypt(&mut self, data: &mut [u8]);
+}
+
+/// Description:
+/// CFB with a 128-bit segment: each cipher text block is encrypted to give the keystream for the next.
+pub struct Cfb<'a, C: BlockCipher> {
+    cipher: &'a C,
+    register: [u8; 16],
+    keystream: [u8; 16],
+    position: usize,
+}
+
+impl<'a, C: BlockCipher> Cfb<'a, C> {
+    // Cipher text bytes are written back into the register as they are produced, so once
+    // the block is used up the register holds the cipher text block to encrypt next
+    fn process(&mut self, data: &mut [u8], encrypt: bool) {
+        for byte in data {
+            if self.position == 16 {
+                self.keystream = self.cipher.encrypt_block(self.register);
+                self.position = 0;
+            }
+            let output = *byte ^ self.keystream[self.position];
+            self.register[self.position] = if encrypt { output } else { *byte };
+            *byte = output;
+            self.position += 1;
+        }
+    }
+}
+
+impl<'a, C: BlockCipher> FeedbackMode<'a, C> for Cfb<'a, C> {
+    fn new(cipher: &'a C, iv: [u8; 16]) -> Self {
+        Cfb { cipher, register: iv, keystream: [0; 16], position: 16 }
+    }
 
-    add_round_key(&mut state, key_schedule[14]);
-    inv_shift_rows(&mut state);
-    inv_sub_bytes(&mut state);
-    for i in (1..14).rev() {
-        add_round_key(&mut state, key_schedule[i]);
-        inv_mix_columns(&mut state);
-        inv_shift_rows(&mut state);
-        inv_sub_bytes(&mut state);
+    fn encrypt(&mut self, data: &mut [u8]) {
+        self.process(data, true);
     }
-    add_round_key(&mut state, key_schedule[0]);
 
-    state
+    fn decrypt(&mut self, data: &mut [u8]) {
+        self.process(data, false);
+    }
 }
 
-pub fn key_expansion(key: [u32; 8]) -> [[[u8; 4]; 4]; 15] {
-    let mut words: [u32; 60] = [0; 60];
-    let n = 8;
+/// Description:
+/// CFB with an 8-bit segment: one block encryption per byte, with the register shifted
+/// left by a byte and the cipher text byte appended each time.
+pub struct Cfb8<'a, C: BlockCipher> {
+    cipher: &'a C,
+    register: [u8; 16],
+}
 
-    for i in 0..60 {
-        if i < n {
-            words[i] = key[i]
-        } else if i % n == 0 {
-            words[i] = words[i-n] ^ sub_word(rot_word(words[i-1])) ^ RCON[i/n - 1];
-        } else if n > 6 && i % n == 4 {
-            words[i] = words[i-n] ^ sub_word(words[i-1]);
-        } else {
-            words[i] = words[i-n] ^ words[i-1];
+impl<'a, C: BlockCipher> Cfb8<'a, C> {
+    fn process(&mut self, data: &mut [u8], encrypt: bool) {
+        for byte in data {
+            let output = *byte ^ self.cipher.encrypt_block(self.register)[0];
+            self.register.copy_within(1.., 0);
+            self.register[15] = if encrypt { output } else { *byte };
+            *byte = output;
         }
     }
+}
+
+impl<'a, C: BlockCipher> FeedbackMode<'a, C> for Cfb8<'a, C> {
+    fn new(cipher: &'a C, iv: [u8; 16]) -> Self {
+        Cfb8 { cipher, register: iv }
+    }
+
+    fn encrypt(&mut self, data: &mut [u8]) {
+        self.process(data, true);
+    }
+
+    fn decrypt(&mut self, data: &mut [u8]) {
+        self.process(data, false);
+    }
+}
+
+/// Description:
+/// OFB: the IV is encrypted repeatedly and the outputs form the keystream, independent of the data.
+pub struct Ofb<'a, C: BlockCipher> {
+    cipher: &'a C,
+    keystream: [u8; 16],
+    position: usize,
+}
+
+impl<'a, C: BlockCipher> Ofb<'a, C> {
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for byte in data {
+            if self.position == 16 {
+                self.keystream = self.cipher.encrypt_block(self.keystream);
+                self.position = 0;
+            }
+            *byte ^= self.keystream[self.position];
+            self.position += 1;
+        }
+    }
+}
+
+impl<'a, C: BlockCipher> FeedbackMode<'a, C> for Ofb<'a, C> {
+    fn new(cipher: &'a C, iv: [u8; 16]) -> Self {
+        Ofb { cipher, keystream: iv, position: 16 }
+    }
+
+    fn encrypt(&mut self, data: &mut [u8]) {
+        self.apply_keystream(data);
+    }
+
+    fn decrypt(&mut self, data: &mut [u8]) {
+        self.apply_keystream(data);
+    }
+}
+
+/// Description:
+/// Encrypts a whole message under a fresh random IV and prepends the IV, for any feedback mode.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+///
+/// Returns:
+/// IV || cipher text
+pub fn feedback_encrypt_prefixed<'a, C: BlockCipher, M: FeedbackMode<'a, C>>(cipher: &'a C, plain_text: Vec<u8>) -> Vec<u8> {
+    let iv = get_iv_128();
+    let mut message = Vec::with_capacity(16 + plain_text.len());
+    message.extend(iv);
+    message.extend(plain_text);
+    M::new(cipher, iv).encrypt(&mut message[16..]);
+
+    message
+}
+
+/// Description:
+/// Decrypts a message produced by feedback_encrypt_prefixed with the same mode.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - IV || cipher text
+///
+/// Returns:
+/// The plain text, or InvalidLength if the message is shorter than an IV
+pub fn feedback_decrypt_prefixed<'a, C: BlockCipher, M: FeedbackMode<'a, C>>(cipher: &'a C, message: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    if message.len() < 16 {
+        return Err(CryptoError::InvalidLength);
+    }
+    let iv: [u8; 16] = message[..16].try_into().unwrap();
+
+    let mut plain_text = message;
+    plain_text.drain(..16);
+    M::new(cipher, iv).decrypt(&mut plain_text);
+
+    Ok(plain_text)
+}
 
-    let mut key_schedule: [[[u8; 4]; 4]; 15]  = [[[0; 4]; 4]; 15];
-    for round in 0..15 {
+/// Description:
+/// Encrypts a whole message with CFB128.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt, of any length
+/// iv - (I,REQ) - The initialization vector
+///
+/// Returns:
+/// The cipher text, the same length as the plain text
+#[allow(non_snake_case)]
+pub fn CFB_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, iv: [u8; 16]) -> Vec<u8> {
+    let mut cipher_text = plain_text;
+    Cfb::new(cipher, iv).encrypt(&mut cipher_text);
+    cipher_text
+}
+
+/// Description:
+/// Decrypts a whole message with CFB128.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The message to decrypt
+/// iv - (I,REQ) - The initialization vector used for encryption
+///
+/// Returns:
+/// The plain text
+#[allow(non_snake_case)]
+pub fn CFB_decrypt<C: BlockCipher>(cipher: &C, cipher_text: Vec<u8>, iv: [u8; 16]) -> Vec<u8> {
+    let mut plain_text = cipher_text;
+    Cfb::new(cipher, iv).decrypt(&mut plain_text);
+    plain_text
+}
+
+/// Description:
+/// Encrypts a whole message with CFB-8.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt, of any length
+/// iv - (I,REQ) - The initialization vector
+///
+/// Returns:
+/// The cipher text, the same length as the plain text
+#[allow(non_snake_case)]
+pub fn CFB8_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, iv: [u8; 16]) -> Vec<u8> {
+    let mut cipher_text = plain_text;
+    Cfb8::new(cipher, iv).encrypt(&mut cipher_text);
+    cipher_text
+}
+
+/// Description:
+/// Decrypts a whole message with CFB-8.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The message to decrypt
+/// iv - (I,REQ) - The initialization vector used for encryption
+///
+/// Returns:
+/// The plain text
+#[allow(non_snake_case)]
+pub fn CFB8_decrypt<C: BlockCipher>(cipher: &C, cipher_text: Vec<u8>, iv: [u8; 16]) -> Vec<u8> {
+    let mut plain_text = cipher_text;
+    Cfb8::new(cipher, iv).decrypt(&mut plain_text);
+    plain_text
+}
+
+/// Description:
+/// Encrypts a whole message with OFB.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt, of any length
+/// iv - (I,REQ) - An IV that is never reused with the same key
+///
+/// Returns:
+/// The cipher text, the same length as the plain text
+#[allow(non_snake_case)]
+pub fn OFB_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, iv: [u8; 16]) -> Vec<u8> {
+    let mut cipher_text = plain_text;
+    Ofb::new(cipher, iv).encrypt(&mut cipher_text);
+    cipher_text
+}
+
+/// Description:
+/// Decrypts a whole message with OFB.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The message to decrypt
+/// iv - (I,REQ) - The initialization vector used for encryption
+///
+/// Returns:
+/// The plain text
+#[allow(non_snake_case)]
+pub fn OFB_decrypt<C: BlockCipher>(cipher: &C, cipher_text: Vec<u8>, iv: [u8; 16]) -> Vec<u8> {
+    let mut plain_text = cipher_text;
+    Ofb::new(cipher, iv).decrypt(&mut plain_text);
+    plain_text
+}
+
+/// Description:
+/// Expands an AES-128, AES-192 or AES-256 key into its round keys (FIPS-197 section 5.2).
+///
+/// Arguments:
+/// key - (I,REQ) - The key as 4, 6 or 8 big-endian words
+///
+/// Returns:
+/// Nr + 1 round keys, each in the same column-major layout as the state
+#[allow(clippy::needless_range_loop)]
+pub fn key_expansion<const NK: usize>(key: &[u32; NK]) -> Vec<[[u8; 4]; 4]> {
+    let mut words = expand_key_words(key, sub_word);
+    let nr = rounds(NK);
+
+    let mut key_schedule: Vec<[[u8; 4]; 4]> = vec![[[0; 4]; 4]; nr+1];
+    for round in 0..=nr {
         for j in 0..4 {
             for i in 0..4 {
                 key_schedule[round][i][j] = ((words[4*round+j] >> (8*(3-i))) & 0b_1111_1111) as u8;
             }
         }
     }
+    zeroize(&mut words);
 
     key_schedule
 }
 
-fn sub_bytes(state: &mut [[u8; 4]; 4]) {
-   for row in state {
-        for byte in row {
-            *byte = SBOX[*byte as usize];
+/// Description:
+/// The word-oriented key schedule of FIPS-197 section 5.2, with SubWord supplied by the caller
+/// so that a hardware backend can run it on its own instructions.
+///
+/// Arguments:
+/// key - (I,REQ) - The key as 4, 6 or 8 big-endian words
+/// sub_word - (I,REQ) - Applies the S-box to each byte of a word
+///
+/// Returns:
+/// The 4 * (Nr + 1) words of the expanded key
+pub(crate) fn expand_key_words<const NK: usize>(key: &[u32; NK], sub_word: impl Fn(u32) -> u32) -> Vec<u32> {
+    const { assert!(NK == 4 || NK == 6 || NK == 8, "AES keys are 4, 6 or 8 words long") };
+    let n = NK;
+    let nr = rounds(NK);
+    let total = 4*(nr+1);
+
+    let mut words: Vec<u32> = Vec::with_capacity(total);
+    for i in 0..total {
+        let word = if i < n {
+            key[i]
+        } else if i % n == 0 {
+            words[i-n] ^ sub_word(rot_word(words[i-1])) ^ RCON[i/n - 1]
+        } else if n > 6 && i % n == 4 {
+            words[i-n] ^ sub_word(words[i-1])
+        } else {
+            words[i-n] ^ words[i-1]
+        };
+        words.push(word);
+    }
+
+    words
+}
+
+// The state is kept as a block: byte 4c + r is row r of column c (FIPS-197 section 3.4)
+
+fn sub_bytes(state: &mut [u8; 16]) {
+    for byte in state {
+        *byte = sub_byte(*byte);
+    }
+}
+
+fn inv_sub_bytes(state: &mut [u8; 16]) {
+    for byte in state {
+        *byte = inv_sub_byte(*byte);
+    }
+}
+
+// Row r rotates left by r columns
+fn shift_rows(state: &mut [u8; 16]) {
+    let original = *state;
+    for c in 0..4 {
+        for r in 1..4 {
+            state[4*c+r] = original[4*((c+r) % 4)+r];
         }
-   }
+    }
 }
 
-fn inv_sub_bytes(state: &mut [[u8; 4]; 4]) {
-   for row in state {
-        for byte in row {
-            *byte = INV_SBOX[*byte as usize];
+fn inv_shift_rows(state: &mut [u8; 16]) {
+    let original = *state;
+    for c in 0..4 {
+        for r in 1..4 {
+            state[4*((c+r) % 4)+r] = original[4*c+r];
         }
-   }
-}
-
-fn shift_rows(state: &mut [[u8; 4]; 4]) {
-    // Row 1
-    // Nothing
-
-    // Row 2
-    let temp = state[1][0];
-    state[1][0] = state[1][1];
-    state[1][1] = state[1][2];
-    state[1][2] = state[1][3];
-    state[1][3] = temp;
-
-    // Row 3
-    let temp = state[2][0];
-    state[2][0] = state[2][2];
-    state[2][2] = temp;
-    let temp = state[2][1];
-    state[2][1] = state[2][3];
-    state[2][3] = temp;
-
-    // Row 4
-    let temp = state[3][0];
-    state[3][0] = state[3][3];
-    state[3][3] = state[3][2];
-    state[3][2] = state[3][1];
-    state[3][1] = temp;
-}
-
-fn inv_shift_rows(state: &mut [[u8; 4]; 4]) {
-    // Row 1
-    // Nothing
-
-    // Row 2
-    let temp = state[1][0];
-    state[1][0] = state[1][3];
-    state[1][3] = state[1][2];
-    state[1][2] = state[1][1];
-    state[1][1] = temp;
-
-    // Row 3
-    let temp = state[2][0];
-    state[2][0] = state[2][2];
-    state[2][2] = temp;
-    let temp = state[2][1];
-    state[2][1] = state[2][3];
-    state[2][3] = temp;
-
-    // Row 4
-    let temp = state[3][0];
-    state[3][0] = state[3][1];
-    state[3][1] = state[3][2];
-    state[3][2] = state[3][3];
-    state[3][3] = temp;
-}
-
-fn mix_columns(state: &mut [[u8; 4]; 4]) {
+    }
+}
+
+fn mix_columns(state: &mut [u8; 16]) {
     let transform:[[u8; 4]; 4] = [
         [2, 3, 1, 1],
         [1, 2, 3, 1],
@@ -180,19 +821,13 @@ fn mix_columns(state: &mut [[u8; 4]; 4]) {
         [3, 1, 1, 2],
     ];
 
-    for j in 0..4 {
-        let mut col: [u8; 4] = [0; 4];
-        for i in 0..4 {
-            col[i] = state[i][j];
-        }
-        let res = byte_matrix_multiplication(&transform, &col);
-        for i in 0..4 {
-            state[i][j] = res[i];
-        }
+    let (columns, _) = state.as_chunks_mut::<4>();
+    for column in columns {
+        *column = byte_matrix_multiplication(&transform, column);
     }
 }
 
-fn inv_mix_columns(state: &mut [[u8; 4]; 4]) {
+fn inv_mix_columns(state: &mut [u8; 16]) {
     let transform:[[u8; 4]; 4] = [
         [14, 11, 13, 9],
         [9, 14, 11, 13],
@@ -200,23 +835,15 @@ fn inv_mix_columns(state: &mut [[u8; 4]; 4]) {
         [11, 13, 9, 14],
     ];
 
-    for j in 0..4 {
-        let mut col: [u8; 4] = [0; 4];
-        for i in 0..4 {
-            col[i] = state[i][j];
-        }
-        let res = byte_matrix_multiplication(&transform, &col);
-        for i in 0..4 {
-            state[i][j] = res[i];
-        }
+    let (columns, _) = state.as_chunks_mut::<4>();
+    for column in columns {
+        *column = byte_matrix_multiplication(&transform, column);
     }
 }
 
-fn add_round_key(state: &mut [[u8; 4]; 4], key: [[u8; 4]; 4]) {
-    for i in 0..4 {
-        for j in 0..4 {
-            state[i][j] ^= key[i][j];
-        }
+fn add_round_key(state: &mut [u8; 16], key: &[u8; 16]) {
+    for (byte, key_byte) in state.iter_mut().zip(key) {
+        *byte ^= key_byte;
     }
 }
 
@@ -224,6 +851,7 @@ fn add_round_key(state: &mut [[u8; 4]; 4], key: [[u8; 4]; 4]) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::padding::{Iso7816,NoPadding,Pkcs7};
 
     #[test]
     #[allow(non_snake_case)]
@@ -235,14 +863,15 @@ mod tests {
             [0xe2, 0x96, 0x11, 0x2a],
         ];
         let key: [u32; 8] = [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4];
-        let res = AES_encrypt_block(state, key);
+        let mut res = block_to_message(state);
+        Aes256::new(key).encrypt_state(&mut res);
         let actual = [
             [0xf3, 0xb5, 0x06, 0x3d],
             [0xee, 0xd2, 0x4b, 0xb1],
             [0xd1, 0xa0, 0x5a, 0x81],
             [0xbd, 0x3c, 0x7e, 0xf8],
         ];
-        assert_eq!(res, actual)
+        assert_eq!(res, block_to_message(actual))
     }
 
     #[test]
@@ -255,20 +884,150 @@ mod tests {
             [0xbd, 0x3c, 0x7e, 0xf8],
         ];
         let key: [u32; 8] = [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4];
-        let res = AES_decrypt_block(state, key);
+        let mut res = block_to_message(state);
+        Aes256::new(key).decrypt_state(&mut res);
         let actual = [
             [0x6b, 0x2e, 0xe9, 0x73],
             [0xc1, 0x40, 0x3d, 0x93],
             [0xbe, 0x9f, 0x7e, 0x17],
             [0xe2, 0x96, 0x11, 0x2a],
         ];
-        assert_eq!(res, actual)
+        assert_eq!(res, block_to_message(actual))
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn AES_128_block_test() {
+        let plain_text: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let key: [u32; 4] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f];
+        let cipher = Aes128::new(key);
+        let res = cipher.encrypt_block(plain_text);
+        let actual: [u8; 16] = [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a];
+        assert_eq!(res, actual);
+        assert_eq!(cipher.decrypt_block(res), plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn AES_192_block_test() {
+        let plain_text: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let key: [u32; 6] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f, 0x10111213, 0x14151617];
+        let cipher = Aes192::new(key);
+        let res = cipher.encrypt_block(plain_text);
+        let actual: [u8; 16] = [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91];
+        assert_eq!(res, actual);
+        assert_eq!(cipher.decrypt_block(res), plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn AES_256_block_test() {
+        let plain_text: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let key: [u32; 8] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f, 0x10111213, 0x14151617, 0x18191a1b, 0x1c1d1e1f];
+        let cipher = Aes256::new(key);
+        let res = cipher.encrypt_block(plain_text);
+        let actual: [u8; 16] = [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89];
+        assert_eq!(res, actual);
+        assert_eq!(cipher.decrypt_block(res), plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn AES_encrypt_decrypt_test() {
+        let plain_text = vec![0x00, 0x3F, 0x24, 0xF6, 0xD2, 0x57, 0x05, 0x92, 0xA4, 0x5E, 0xEA, 0x05, 0x05, 0x05, 0x05, 0x05, 0x10, 0x10, 0x10];
+        let key: [u32; 4] = [0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c];
+        let cipher_text = AES_encrypt(plain_text.clone(), key, &Pkcs7).unwrap();
+        assert_eq!(cipher_text.len(), 32);
+        assert_eq!(AES_decrypt(cipher_text.clone(), key, &Pkcs7).unwrap(), plain_text);
+        assert_eq!(AES_decrypt(cipher_text[..31].to_vec(), key, &Pkcs7), Err(CryptoError::InvalidLength));
+        assert_eq!(AES_decrypt(vec![], key, &Pkcs7), Err(CryptoError::InvalidLength));
+
+        let key: [u32; 6] = [0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b];
+        let cipher_text = AES_encrypt(plain_text.clone(), key, &Iso7816).unwrap();
+        assert_eq!(AES_decrypt(cipher_text, key, &Iso7816).unwrap(), plain_text);
+
+        assert_eq!(AES_encrypt(plain_text.clone(), key, &NoPadding), Err(CryptoError::InvalidLength));
+        let cipher_text = AES_encrypt(plain_text[..16].to_vec(), key, &NoPadding).unwrap();
+        assert_eq!(cipher_text.len(), 16);
+        assert_eq!(AES_decrypt(cipher_text, key, &NoPadding).unwrap(), plain_text[..16]);
+    }
+
+    #[test]
+    fn in_place_test() {
+        let cipher = Aes128::new([0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f]);
+        let plain_text: Vec<u8> = (0..48).collect();
+        let mut expected = plain_text.clone();
+        for chunk in expected.chunks_mut(16) {
+            let block = cipher.encrypt_block(chunk.try_into().unwrap());
+            chunk.copy_from_slice(&block);
+        }
+
+        let mut res = plain_text.clone();
+        cipher.encrypt_in_place(&mut res).unwrap();
+        assert_eq!(res, expected);
+        cipher.decrypt_in_place(&mut res).unwrap();
+        assert_eq!(res, plain_text);
+
+        let mut output = [0; 48];
+        cipher.encrypt_into(&plain_text, &mut output).unwrap();
+        assert_eq!(output, expected[..]);
+        let mut res = [0; 48];
+        cipher.decrypt_into(&output, &mut res).unwrap();
+        assert_eq!(res, plain_text[..]);
+
+        assert_eq!(cipher.encrypt_in_place(&mut [0; 20]), Err(CryptoError::InvalidLength));
+        assert_eq!(cipher.encrypt_into(&[0; 32], &mut [0; 16]), Err(CryptoError::InvalidLength));
+        assert_eq!(cipher.decrypt_into(&[0; 15], &mut [0; 15]), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn ECB_in_place_test() {
+        let cipher = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+        let mut buffer: Vec<u8> = Vec::with_capacity(64);
+        let address = buffer.as_ptr();
+
+        for length in [0, 5, 16, 40] {
+            let plain_text: Vec<u8> = (0..length).collect();
+            buffer.clear();
+            buffer.extend_from_slice(&plain_text);
+            ECB_encrypt_in_place(&cipher, &mut buffer, &Pkcs7).unwrap();
+            assert_eq!(buffer, ECB_encrypt(&cipher, plain_text.clone(), &Pkcs7).unwrap());
+            ECB_decrypt_in_place(&cipher, &mut buffer, &Pkcs7).unwrap();
+            assert_eq!(buffer, plain_text);
+        }
+        // The buffer was reused throughout
+        assert_eq!(buffer.as_ptr(), address);
+
+        let mut buffer = vec![0; 17];
+        assert_eq!(ECB_decrypt_in_place(&cipher, &mut buffer, &Pkcs7), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    fn key_from_bytes_test() {
+        let bytes: Vec<u8> = (0x00..0x20).collect();
+        let words: [u32; 8] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f, 0x10111213, 0x14151617, 0x18191a1b, 0x1c1d1e1f];
+
+        let expected: Vec<[u8; 16]> = key_expansion::<4>(words[..4].try_into().unwrap()).into_iter().map(block_to_message).collect();
+        assert_eq!(Aes128::from_bytes(&bytes[..16]).unwrap().key_schedule(), expected);
+        let expected: Vec<[u8; 16]> = key_expansion::<6>(words[..6].try_into().unwrap()).into_iter().map(block_to_message).collect();
+        assert_eq!(Aes192::from_bytes(&bytes[..24]).unwrap().key_schedule(), expected);
+        let expected: Vec<[u8; 16]> = key_expansion(&words).into_iter().map(block_to_message).collect();
+        assert_eq!(Aes256::from_bytes(&bytes).unwrap().key_schedule(), expected);
+
+        let hex = Aes256::from_hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap();
+        let base64 = Aes256::from_base64("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").unwrap();
+        assert_eq!(hex.key_schedule(), Aes256::new(words).key_schedule());
+        assert_eq!(base64.key_schedule(), Aes256::new(words).key_schedule());
+
+        assert_eq!(Aes128::from_bytes(&bytes[..24]).err(), Some(CryptoError::InvalidKey));
+        assert_eq!(Aes256::from_bytes(&bytes[..16]).err(), Some(CryptoError::InvalidKey));
     }
 
     #[test]
     fn key_expansion_test() {
         let key: [u32; 8] = [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4];
-        let res = key_expansion(key)[14];
+        let res = key_expansion(&key)[14];
         let actual: [[u8; 4]; 4] = [
             [0xfe, 0xe6, 0x04, 0x70],
             [0x48, 0x18, 0x6d, 0x6c],
@@ -278,14 +1037,42 @@ mod tests {
         assert_eq!(res, actual);
     }
 
+    #[test]
+    fn key_expansion_128_test() {
+        let key: [u32; 4] = [0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c];
+        let key_schedule = key_expansion(&key);
+        assert_eq!(key_schedule.len(), 11);
+        let actual: [[u8; 4]; 4] = [
+            [0xd0, 0xc9, 0xe1, 0xb6],
+            [0x14, 0xee, 0x3f, 0x63],
+            [0xf9, 0x25, 0x0c, 0x0c],
+            [0xa8, 0x89, 0xc8, 0xa6]
+        ];
+        assert_eq!(key_schedule[10], actual);
+    }
+
+    #[test]
+    fn key_expansion_192_test() {
+        let key: [u32; 6] = [0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b];
+        let key_schedule = key_expansion(&key);
+        assert_eq!(key_schedule.len(), 13);
+        let actual: [[u8; 4]; 4] = [
+            [0xe9, 0x44, 0x8e, 0x01],
+            [0x8b, 0x8c, 0xcc, 0x00],
+            [0xa0, 0x77, 0x72, 0x22],
+            [0x6f, 0x3c, 0x04, 0x02]
+        ];
+        assert_eq!(key_schedule[12], actual);
+    }
+
     #[test]
     fn sub_bytes_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [0x00,0x01,0x02,0x03],
             [0x04,0x05,0x06,0x07],
             [0x08,0x09,0x0A,0x0B],
             [0x0C,0x0D,0x0E,0x0F]
-        ];
+        ]);
         sub_bytes(&mut res);
         let actual: [[u8; 4]; 4] = [
             [0x63,0x7C,0x77,0x7B],
@@ -293,17 +1080,17 @@ mod tests {
             [0x30,0x01,0x67,0x2B],
             [0xFE,0xD7,0xAB,0x76]
         ];
-        assert_eq!(res, actual);     
+        assert_eq!(res, block_to_message(actual));     
     }
 
     #[test]
     fn inv_sub_bytes_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [0x63,0x7C,0x77,0x7B],
             [0xF2,0x6B,0x6F,0xC5],
             [0x30,0x01,0x67,0x2B],
             [0xFE,0xD7,0xAB,0x76]
-        ];
+        ]);
         inv_sub_bytes(&mut res);
         let actual: [[u8; 4]; 4] = [
             [0x00,0x01,0x02,0x03],
@@ -311,17 +1098,17 @@ mod tests {
             [0x08,0x09,0x0A,0x0B],
             [0x0C,0x0D,0x0E,0x0F]
         ];
-        assert_eq!(res, actual);     
+        assert_eq!(res, block_to_message(actual));     
     }
 
     #[test]
     fn shift_rows_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [1,2,3,4],
             [5,6,7,8],
             [9,10,11,12],
             [13,14,15,16]
-        ];
+        ]);
         shift_rows(&mut res);
         let actual: [[u8; 4]; 4] = [
             [1,2,3,4],
@@ -329,17 +1116,17 @@ mod tests {
             [11,12,9,10],
             [16,13,14,15]
         ];
-        assert_eq!(res, actual);
+        assert_eq!(res, block_to_message(actual));
     }
 
     #[test]
     fn inv_shift_rows_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [1,2,3,4],
             [6,7,8,5],
             [11,12,9,10],
             [16,13,14,15]
-        ];
+        ]);
         inv_shift_rows(&mut res);
         let actual: [[u8; 4]; 4] = [
             [1,2,3,4],
@@ -347,17 +1134,17 @@ mod tests {
             [9,10,11,12],
             [13,14,15,16]
         ];
-        assert_eq!(res, actual);
+        assert_eq!(res, block_to_message(actual));
     }
 
     #[test]
     fn mix_columns_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [0x63,0xF2,0x01,0xC6],
             [0x47,0x0A,0x01,0xC6],
             [0xA2,0x22,0x01,0xC6],
             [0xF0,0x5C,0x01,0xC6]
-        ];
+        ]);
         mix_columns(&mut res);
         let actual: [[u8; 4]; 4] = [
             [0x5D,0x9F,0x01,0xC6],
@@ -365,17 +1152,17 @@ mod tests {
             [0x70,0x58,0x01,0xC6],
             [0xBB,0x9D,0x01,0xC6]
         ];
-        assert_eq!(res, actual);
+        assert_eq!(res, block_to_message(actual));
     }
 
     #[test]
     fn inv_mix_columns_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [0x5D,0x9F,0x01,0xC6],
             [0xE0,0xDC,0x01,0xC6],
             [0x70,0x58,0x01,0xC6],
             [0xBB,0x9D,0x01,0xC6]
-        ];
+        ]);
         inv_mix_columns(&mut res);
         let actual: [[u8; 4]; 4] = [
             [0x63,0xF2,0x01,0xC6],
@@ -383,25 +1170,120 @@ mod tests {
             [0xA2,0x22,0x01,0xC6],
             [0xF0,0x5C,0x01,0xC6]
         ];
-        assert_eq!(res, actual);
+        assert_eq!(res, block_to_message(actual));
     }
 
     #[test]
     fn add_round_key_test() {
-        let mut res: [[u8; 4]; 4] = [
+        let mut res = block_to_message([
             [1,2,3,4],
             [5,6,7,8],
             [9,10,11,12],
             [13,14,15,16]
-        ];
+        ]);
         let key: [[u8; 4]; 4] = [
             [1,2,3,4],
             [5,6,7,8],
             [9,10,11,12],
             [13,14,15,16]
         ];
-        add_round_key(&mut res, key);
-        let actual: [[u8; 4]; 4] = [[0; 4]; 4];
-        assert_eq!(res, actual);
+        add_round_key(&mut res, &block_to_message(key));
+        assert_eq!(res, [0; 16]);
+    }
+
+    const SP800_38A_KEY_128: [u32; 4] = [0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c];
+    const SP800_38A_KEY_192: [u32; 6] = [0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b];
+    const SP800_38A_KEY_256: [u32; 8] = [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4];
+    const SP800_38A_IV: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+    const SP800_38A_PLAIN_TEXT: [u8; 64] = [0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
+
+    // Checks the whole-message functions and then streams the same message in uneven pieces
+    fn check_feedback<'a, C: BlockCipher, M: FeedbackMode<'a, C>>(cipher: &'a C, plain_text: &[u8], expected: &[u8]) {
+        for pieces in [vec![plain_text.len()], vec![1, 15, 17, 3], vec![5; 13]] {
+            let mut data = plain_text.to_vec();
+            let mut mode = M::new(cipher, SP800_38A_IV);
+            let mut start = 0;
+            for piece in pieces.into_iter().chain([plain_text.len()]) {
+                let end = (start + piece).min(data.len());
+                mode.encrypt(&mut data[start..end]);
+                start = end;
+            }
+            assert_eq!(data, expected);
+
+            let mut mode = M::new(cipher, SP800_38A_IV);
+            mode.decrypt(&mut data[..7]);
+            mode.decrypt(&mut data[7..]);
+            assert_eq!(data, plain_text);
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CFB_test() {
+        // SP 800-38A F.3.13, F.3.15 and F.3.17
+        let actual: [u8; 64] = [0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a, 0xc8, 0xa6, 0x45, 0x37, 0xa0, 0xb3, 0xa9, 0x3f, 0xcd, 0xe3, 0xcd, 0xad, 0x9f, 0x1c, 0xe5, 0x8b, 0x26, 0x75, 0x1f, 0x67, 0xa3, 0xcb, 0xb1, 0x40, 0xb1, 0x80, 0x8c, 0xf1, 0x87, 0xa4, 0xf4, 0xdf, 0xc0, 0x4b, 0x05, 0x35, 0x7c, 0x5d, 0x1c, 0x0e, 0xea, 0xc4, 0xc6, 0x6f, 0x9f, 0xf7, 0xf2, 0xe6];
+        let cipher = Aes128::new(SP800_38A_KEY_128);
+        assert_eq!(CFB_encrypt(&cipher, SP800_38A_PLAIN_TEXT.to_vec(), SP800_38A_IV), actual);
+        assert_eq!(CFB_decrypt(&cipher, actual.to_vec(), SP800_38A_IV), SP800_38A_PLAIN_TEXT);
+        check_feedback::<_, Cfb<_>>(&cipher, &SP800_38A_PLAIN_TEXT, &actual);
+
+        let actual: [u8; 64] = [0xcd, 0xc8, 0x0d, 0x6f, 0xdd, 0xf1, 0x8c, 0xab, 0x34, 0xc2, 0x59, 0x09, 0xc9, 0x9a, 0x41, 0x74, 0x67, 0xce, 0x7f, 0x7f, 0x81, 0x17, 0x36, 0x21, 0x96, 0x1a, 0x2b, 0x70, 0x17, 0x1d, 0x3d, 0x7a, 0x2e, 0x1e, 0x8a, 0x1d, 0xd5, 0x9b, 0x88, 0xb1, 0xc8, 0xe6, 0x0f, 0xed, 0x1e, 0xfa, 0xc4, 0xc9, 0xc0, 0x5f, 0x9f, 0x9c, 0xa9, 0x83, 0x4f, 0xa0, 0x42, 0xae, 0x8f, 0xba, 0x58, 0x4b, 0x09, 0xff];
+        check_feedback::<_, Cfb<_>>(&Aes192::new(SP800_38A_KEY_192), &SP800_38A_PLAIN_TEXT, &actual);
+
+        let actual: [u8; 64] = [0xdc, 0x7e, 0x84, 0xbf, 0xda, 0x79, 0x16, 0x4b, 0x7e, 0xcd, 0x84, 0x86, 0x98, 0x5d, 0x38, 0x60, 0x39, 0xff, 0xed, 0x14, 0x3b, 0x28, 0xb1, 0xc8, 0x32, 0x11, 0x3c, 0x63, 0x31, 0xe5, 0x40, 0x7b, 0xdf, 0x10, 0x13, 0x24, 0x15, 0xe5, 0x4b, 0x92, 0xa1, 0x3e, 0xd0, 0xa8, 0x26, 0x7a, 0xe2, 0xf9, 0x75, 0xa3, 0x85, 0x74, 0x1a, 0xb9, 0xce, 0xf8, 0x20, 0x31, 0x62, 0x3d, 0x55, 0xb1, 0xe4, 0x71];
+        check_feedback::<_, Cfb<_>>(&Aes256::new(SP800_38A_KEY_256), &SP800_38A_PLAIN_TEXT, &actual);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CFB8_test() {
+        // SP 800-38A F.3.7, F.3.9 and F.3.11
+        let actual: [u8; 18] = [0x3b, 0x79, 0x42, 0x4c, 0x9c, 0x0d, 0xd4, 0x36, 0xba, 0xce, 0x9e, 0x0e, 0xd4, 0x58, 0x6a, 0x4f, 0x32, 0xb9];
+        let cipher = Aes128::new(SP800_38A_KEY_128);
+        assert_eq!(CFB8_encrypt(&cipher, SP800_38A_PLAIN_TEXT[..18].to_vec(), SP800_38A_IV), actual);
+        assert_eq!(CFB8_decrypt(&cipher, actual.to_vec(), SP800_38A_IV), SP800_38A_PLAIN_TEXT[..18]);
+        check_feedback::<_, Cfb8<_>>(&cipher, &SP800_38A_PLAIN_TEXT[..18], &actual);
+
+        let actual: [u8; 18] = [0xcd, 0xa2, 0x52, 0x1e, 0xf0, 0xa9, 0x05, 0xca, 0x44, 0xcd, 0x05, 0x7c, 0xbf, 0x0d, 0x47, 0xa0, 0x67, 0x8a];
+        check_feedback::<_, Cfb8<_>>(&Aes192::new(SP800_38A_KEY_192), &SP800_38A_PLAIN_TEXT[..18], &actual);
+
+        let actual: [u8; 18] = [0xdc, 0x1f, 0x1a, 0x85, 0x20, 0xa6, 0x4d, 0xb5, 0x5f, 0xcc, 0x8a, 0xc5, 0x54, 0x84, 0x4e, 0x88, 0x97, 0x00];
+        check_feedback::<_, Cfb8<_>>(&Aes256::new(SP800_38A_KEY_256), &SP800_38A_PLAIN_TEXT[..18], &actual);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn OFB_test() {
+        // SP 800-38A F.4.1, F.4.3 and F.4.5
+        let actual: [u8; 64] = [0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a, 0x77, 0x89, 0x50, 0x8d, 0x16, 0x91, 0x8f, 0x03, 0xf5, 0x3c, 0x52, 0xda, 0xc5, 0x4e, 0xd8, 0x25, 0x97, 0x40, 0x05, 0x1e, 0x9c, 0x5f, 0xec, 0xf6, 0x43, 0x44, 0xf7, 0xa8, 0x22, 0x60, 0xed, 0xcc, 0x30, 0x4c, 0x65, 0x28, 0xf6, 0x59, 0xc7, 0x78, 0x66, 0xa5, 0x10, 0xd9, 0xc1, 0xd6, 0xae, 0x5e];
+        let cipher = Aes128::new(SP800_38A_KEY_128);
+        assert_eq!(OFB_encrypt(&cipher, SP800_38A_PLAIN_TEXT.to_vec(), SP800_38A_IV), actual);
+        assert_eq!(OFB_decrypt(&cipher, actual.to_vec(), SP800_38A_IV), SP800_38A_PLAIN_TEXT);
+        check_feedback::<_, Ofb<_>>(&cipher, &SP800_38A_PLAIN_TEXT, &actual);
+
+        let actual: [u8; 64] = [0xcd, 0xc8, 0x0d, 0x6f, 0xdd, 0xf1, 0x8c, 0xab, 0x34, 0xc2, 0x59, 0x09, 0xc9, 0x9a, 0x41, 0x74, 0xfc, 0xc2, 0x8b, 0x8d, 0x4c, 0x63, 0x83, 0x7c, 0x09, 0xe8, 0x17, 0x00, 0xc1, 0x10, 0x04, 0x01, 0x8d, 0x9a, 0x9a, 0xea, 0xc0, 0xf6, 0x59, 0x6f, 0x55, 0x9c, 0x6d, 0x4d, 0xaf, 0x59, 0xa5, 0xf2, 0x6d, 0x9f, 0x20, 0x08, 0x57, 0xca, 0x6c, 0x3e, 0x9c, 0xac, 0x52, 0x4b, 0xd9, 0xac, 0xc9, 0x2a];
+        check_feedback::<_, Ofb<_>>(&Aes192::new(SP800_38A_KEY_192), &SP800_38A_PLAIN_TEXT, &actual);
+
+        let actual: [u8; 64] = [0xdc, 0x7e, 0x84, 0xbf, 0xda, 0x79, 0x16, 0x4b, 0x7e, 0xcd, 0x84, 0x86, 0x98, 0x5d, 0x38, 0x60, 0x4f, 0xeb, 0xdc, 0x67, 0x40, 0xd2, 0x0b, 0x3a, 0xc8, 0x8f, 0x6a, 0xd8, 0x2a, 0x4f, 0xb0, 0x8d, 0x71, 0xab, 0x47, 0xa0, 0x86, 0xe8, 0x6e, 0xed, 0xf3, 0x9d, 0x1c, 0x5b, 0xba, 0x97, 0xc4, 0x08, 0x01, 0x26, 0x14, 0x1d, 0x67, 0xf3, 0x7b, 0xe8, 0x53, 0x8f, 0x5a, 0x8b, 0xe7, 0x40, 0xe4, 0x84];
+        check_feedback::<_, Ofb<_>>(&Aes256::new(SP800_38A_KEY_256), &SP800_38A_PLAIN_TEXT, &actual);
+    }
+
+    #[test]
+    fn feedback_prefixed_test() {
+        let cipher = Aes128::new(SP800_38A_KEY_128);
+
+        let message = feedback_encrypt_prefixed::<_, Cfb<_>>(&cipher, SP800_38A_PLAIN_TEXT[..20].to_vec());
+        assert_eq!(message.len(), 36);
+        assert_eq!(feedback_decrypt_prefixed::<_, Cfb<_>>(&cipher, message).unwrap(), SP800_38A_PLAIN_TEXT[..20]);
+
+        let message = feedback_encrypt_prefixed::<_, Cfb8<_>>(&cipher, SP800_38A_PLAIN_TEXT[..20].to_vec());
+        assert_eq!(feedback_decrypt_prefixed::<_, Cfb8<_>>(&cipher, message).unwrap(), SP800_38A_PLAIN_TEXT[..20]);
+
+        let message = feedback_encrypt_prefixed::<_, Ofb<_>>(&cipher, SP800_38A_PLAIN_TEXT[..20].to_vec());
+        let iv: [u8; 16] = message[..16].try_into().unwrap();
+        assert_eq!(message[16..], OFB_encrypt(&cipher, SP800_38A_PLAIN_TEXT[..20].to_vec(), iv));
+        assert_eq!(feedback_decrypt_prefixed::<_, Ofb<_>>(&cipher, message).unwrap(), SP800_38A_PLAIN_TEXT[..20]);
+
+        assert_eq!(feedback_decrypt_prefixed::<_, Ofb<_>>(&cipher, vec![0; 15]), Err(CryptoError::InvalidLength));
     }
 }
\ No newline at end of file
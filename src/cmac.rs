@@ -0,0 +1,257 @@
+use crate::cipher::{Aes128,BlockCipher};
+use crate::error::CryptoError;
+use crate::key::SecretKey128;
+use crate::math::gf128_double;
+use crate::util::{constant_time_eq,zeroize};
+
+/// Description:
+/// Streaming CMAC (SP 800-38B, RFC 4493). The last block is held back until finalize,
+/// since whether it is complete decides which subkey it is masked with. The subkeys and the
+/// chaining state are zeroed when the instance is dropped.
+pub struct Cmac<'a, C: BlockCipher> {
+    cipher: &'a C,
+    k1: u128,
+    k2: u128,
+    state: [u8; 16],
+    buffer: [u8; 16],
+    buffer_length: usize,
+}
+
+impl<'a, C: BlockCipher> Cmac<'a, C> {
+    /// Description:
+    /// Derives the subkeys K1 = 2L and K2 = 4L from L = E(0^128).
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    ///
+    /// Returns:
+    /// A CMAC with no input yet
+    pub fn new(cipher: &'a C) -> Self {
+        let k1 = gf128_double(u128::from_be_bytes(cipher.encrypt_block([0; 16])));
+        let k2 = gf128_double(k1);
+
+        Cmac { cipher, k1, k2, state: [0; 16], buffer: [0; 16], buffer_length: 0 }
+    }
+
+    /// Description:
+    /// Absorbs more of the message.
+    ///
+    /// Arguments:
+    /// data - (I,REQ) - The next bytes of the message
+    pub fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+        while !data.is_empty() {
+            if self.buffer_length == 16 {
+                for (s, b) in self.state.iter_mut().zip(self.buffer) {
+                    *s ^= b;
+                }
+                self.state = self.cipher.encrypt_block(self.state);
+                self.buffer_length = 0;
+            }
+
+            let n = (16 - self.buffer_length).min(data.len());
+            self.buffer[self.buffer_length..self.buffer_length+n].copy_from_slice(&data[..n]);
+            self.buffer_length += n;
+            data = &data[n..];
+        }
+    }
+
+    /// Description:
+    /// Masks and encrypts the last block.
+    ///
+    /// Returns:
+    /// The 16-byte tag
+    pub fn finalize(self) -> [u8; 16] {
+        let mut block = self.buffer;
+        let subkey = if self.buffer_length == 16 {
+            self.k1
+        } else {
+            block[self.buffer_length] = 0x80;
+            block[self.buffer_length+1..].fill(0);
+            self.k2
+        };
+        let block = u128::from_be_bytes(block) ^ subkey ^ u128::from_be_bytes(self.state);
+
+        self.cipher.encrypt_block(block.to_be_bytes())
+    }
+
+    /// Description:
+    /// Compares the tag of the absorbed message with an expected tag in constant time.
+    ///
+    /// Arguments:
+    /// tag - (I,REQ) - The expected tag, possibly truncated to its first 1 to 16 bytes
+    ///
+    /// Returns:
+    /// Ok if the tags match, AuthenticationFailed if not, or InvalidLength for an empty or overlong tag
+    pub fn verify(self, tag: &[u8]) -> Result<(), CryptoError> {
+        if tag.is_empty() || tag.len() > 16 {
+            return Err(CryptoError::InvalidLength);
+        }
+        if !constant_time_eq(&self.finalize()[..tag.len()], tag) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, C: BlockCipher> Drop for Cmac<'a, C> {
+    fn drop(&mut self) {
+        zeroize(std::slice::from_mut(&mut self.k1));
+        zeroize(std::slice::from_mut(&mut self.k2));
+        zeroize(&mut self.state);
+        zeroize(&mut self.buffer);
+    }
+}
+
+/// Description:
+/// Computes the CMAC of a whole message.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - The message to authenticate
+///
+/// Returns:
+/// The 16-byte tag
+#[allow(non_snake_case)]
+pub fn AES_CMAC<C: BlockCipher>(cipher: &C, message: &[u8]) -> [u8; 16] {
+    let mut mac = Cmac::new(cipher);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// Description:
+/// AES-CMAC-PRF-128 (RFC 4615), a pseudo-random function for key derivation that accepts
+/// a key of any length. Keys that are not 16 bytes are first compressed with AES-CMAC under the zero key.
+///
+/// Arguments:
+/// key - (I,REQ) - The variable-length key
+/// message - (I,REQ) - The PRF input
+///
+/// Returns:
+/// The 16-byte PRF output
+#[allow(non_snake_case)]
+pub fn AES_CMAC_PRF_128(key: &[u8], message: &[u8]) -> [u8; 16] {
+    let key = if key.len() == 16 {
+        SecretKey128::from_bytes(key)
+    } else {
+        let mut derived = AES_CMAC(&Aes128::new([0; 4]), key);
+        let key = SecretKey128::from_bytes(&derived);
+        zeroize(&mut derived);
+        key
+    };
+
+    AES_CMAC(&Aes128::with_key(&key.unwrap()), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes192,Aes256};
+
+    const MESSAGE: [u8; 64] = [0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
+    // Message lengths of the four examples for each key size
+    const LENGTHS: [usize; 4] = [0, 16, 40, 64];
+
+    fn check<C: BlockCipher>(cipher: &C, expected: [[u8; 16]; 4]) {
+        for (length, tag) in LENGTHS.into_iter().zip(expected) {
+            let message = &MESSAGE[..length];
+            assert_eq!(AES_CMAC(cipher, message), tag);
+
+            // Every split point gives the same tag when streamed
+            for split in 0..=length {
+                let mut mac = Cmac::new(cipher);
+                mac.update(&message[..split]);
+                mac.update(&[]);
+                mac.update(&message[split..]);
+                assert_eq!(mac.finalize(), tag);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CMAC_AES_128_test() {
+        // RFC 4493 section 4 and SP 800-38B appendix D.1
+        let cipher = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+        let expected: [[u8; 16]; 4] = [
+            [0xbb, 0x1d, 0x69, 0x29, 0xe9, 0x59, 0x37, 0x28, 0x7f, 0xa3, 0x7d, 0x12, 0x9b, 0x75, 0x67, 0x46],
+            [0x07, 0x0a, 0x16, 0xb4, 0x6b, 0x4d, 0x41, 0x44, 0xf7, 0x9b, 0xdd, 0x9d, 0xd0, 0x4a, 0x28, 0x7c],
+            [0xdf, 0xa6, 0x67, 0x47, 0xde, 0x9a, 0xe6, 0x30, 0x30, 0xca, 0x32, 0x61, 0x14, 0x97, 0xc8, 0x27],
+            [0x51, 0xf0, 0xbe, 0xbf, 0x7e, 0x3b, 0x9d, 0x92, 0xfc, 0x49, 0x74, 0x17, 0x79, 0x36, 0x3c, 0xfe],
+        ];
+        check(&cipher, expected);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CMAC_AES_192_test() {
+        // SP 800-38B appendix D.2
+        let cipher = Aes192::new([0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b]);
+        let expected: [[u8; 16]; 4] = [
+            [0xd1, 0x7d, 0xdf, 0x46, 0xad, 0xaa, 0xcd, 0xe5, 0x31, 0xca, 0xc4, 0x83, 0xde, 0x7a, 0x93, 0x67],
+            [0x9e, 0x99, 0xa7, 0xbf, 0x31, 0xe7, 0x10, 0x90, 0x06, 0x62, 0xf6, 0x5e, 0x61, 0x7c, 0x51, 0x84],
+            [0x8a, 0x1d, 0xe5, 0xbe, 0x2e, 0xb3, 0x1a, 0xad, 0x08, 0x9a, 0x82, 0xe6, 0xee, 0x90, 0x8b, 0x0e],
+            [0xa1, 0xd5, 0xdf, 0x0e, 0xed, 0x79, 0x0f, 0x79, 0x4d, 0x77, 0x58, 0x96, 0x59, 0xf3, 0x9a, 0x11],
+        ];
+        check(&cipher, expected);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CMAC_AES_256_test() {
+        // SP 800-38B appendix D.3
+        let cipher = Aes256::new([0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4]);
+        let expected: [[u8; 16]; 4] = [
+            [0x02, 0x89, 0x62, 0xf6, 0x1b, 0x7b, 0xf8, 0x9e, 0xfc, 0x6b, 0x55, 0x1f, 0x46, 0x67, 0xd9, 0x83],
+            [0x28, 0xa7, 0x02, 0x3f, 0x45, 0x2e, 0x8f, 0x82, 0xbd, 0x4b, 0xf2, 0x8d, 0x8c, 0x37, 0xc3, 0x5c],
+            [0xaa, 0xf3, 0xd8, 0xf1, 0xde, 0x56, 0x40, 0xc2, 0x32, 0xf5, 0xb1, 0x69, 0xb9, 0xc9, 0x11, 0xe6],
+            [0xe1, 0x99, 0x21, 0x90, 0x54, 0x9f, 0x6e, 0xd5, 0x69, 0x6a, 0x2c, 0x05, 0x6c, 0x31, 0x54, 0x10],
+        ];
+        check(&cipher, expected);
+    }
+
+    #[test]
+    fn verify_test() {
+        let cipher = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+        let tag = AES_CMAC(&cipher, &MESSAGE);
+
+        let mut mac = Cmac::new(&cipher);
+        mac.update(&MESSAGE);
+        assert_eq!(mac.verify(&tag), Ok(()));
+
+        let mut mac = Cmac::new(&cipher);
+        mac.update(&MESSAGE);
+        assert_eq!(mac.verify(&tag[..8]), Ok(()));
+
+        let mut mac = Cmac::new(&cipher);
+        mac.update(&MESSAGE[..63]);
+        assert_eq!(mac.verify(&tag), Err(CryptoError::AuthenticationFailed));
+
+        let mut wrong = tag;
+        wrong[15] ^= 1;
+        let mut mac = Cmac::new(&cipher);
+        mac.update(&MESSAGE);
+        assert_eq!(mac.verify(&wrong), Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(Cmac::new(&cipher).verify(&[]), Err(CryptoError::InvalidLength));
+        assert_eq!(Cmac::new(&cipher).verify(&[0; 17]), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn AES_CMAC_PRF_128_test() {
+        // RFC 4615 section 4
+        let key: [u8; 18] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0xed, 0xcb];
+        let message: Vec<u8> = (0x00..0x14).collect();
+
+        let actual: [u8; 16] = [0x84, 0xa3, 0x48, 0xa4, 0xa4, 0x5d, 0x23, 0x5b, 0xab, 0xff, 0xfc, 0x0d, 0x2b, 0x4d, 0xa0, 0x9a];
+        assert_eq!(AES_CMAC_PRF_128(&key, &message), actual);
+
+        let actual: [u8; 16] = [0x98, 0x0a, 0xe8, 0x7b, 0x5f, 0x4c, 0x9c, 0x52, 0x14, 0xf5, 0xb6, 0xa8, 0x45, 0x5e, 0x4c, 0x2d];
+        assert_eq!(AES_CMAC_PRF_128(&key[..16], &message), actual);
+
+        let actual: [u8; 16] = [0x29, 0x0d, 0x9e, 0x11, 0x2e, 0xdb, 0x09, 0xee, 0x14, 0x1f, 0xcf, 0x64, 0xc0, 0xb7, 0x2f, 0x3d];
+        assert_eq!(AES_CMAC_PRF_128(&key[..10], &message), actual);
+    }
+}
\ No newline at end of file
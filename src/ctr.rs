@@ -0,0 +1,291 @@
+use crate::cipher::BlockCipher;
+use crate::error::CryptoError;
+
+const KEYSTREAM_BATCH: usize = 8;
+/// Number of distinct counter blocks of the 32-bit layouts, after which the keystream would repeat
+const COUNTER32_BLOCKS: u64 = 1 << 32;
+
+/// Description:
+/// How the 16-byte counter block is split between nonce and counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CounterLayout {
+    /// A 96-bit nonce followed by a 32-bit big-endian counter that wraps without touching the nonce.
+    Nonce96Counter32,
+    /// The whole block is a single 128-bit big-endian counter.
+    Counter128,
+    /// A 32-bit little-endian counter followed by a 96-bit nonce, as in AES-GCM-SIV; the counter wraps without touching the nonce.
+    Counter32LeNonce96,
+}
+
+/// Description:
+/// AES-CTR keystream positioned at an arbitrary byte offset, so a range in the
+/// middle of a message can be encrypted or decrypted without processing what precedes it.
+pub struct Ctr<'a, C: BlockCipher> {
+    cipher: &'a C,
+    counter_block: [u8; 16],
+    layout: CounterLayout,
+    position: u64,
+}
+
+impl<'a, C: BlockCipher> Ctr<'a, C> {
+    /// Description:
+    /// Creates a keystream starting at offset 0.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// counter_block - (I,REQ) - The initial counter block (nonce and starting counter)
+    /// layout - (I,REQ) - How the counter block is incremented
+    ///
+    /// Returns:
+    /// The keystream
+    pub fn new(cipher: &'a C, counter_block: [u8; 16], layout: CounterLayout) -> Self {
+        Ctr { cipher, counter_block, layout, position: 0 }
+    }
+
+    /// Description:
+    /// Moves the keystream to a byte offset from the initial counter block. Offsets past the
+    /// end of the keystream are rejected by apply_keystream.
+    ///
+    /// Arguments:
+    /// position - (I,REQ) - The byte offset
+    pub fn seek(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Description:
+    /// The current byte offset in the keystream.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Description:
+    /// XORs the keystream into data starting at the current position and advances past it.
+    ///
+    /// Arguments:
+    /// data - (I/O,REQ) - Plain text or cipher text, transformed in place
+    ///
+    /// Returns:
+    /// InvalidLength, with data untouched, if it runs past the last byte offset or, for the
+    /// 32-bit layouts, past the 2^32 blocks after which the keystream would repeat
+    pub fn apply_keystream(&mut self, data: &mut [u8]) -> Result<(), CryptoError> {
+        let end = self.position.checked_add(data.len() as u64).ok_or(CryptoError::InvalidLength)?;
+        if self.layout != CounterLayout::Counter128 && end > 16*COUNTER32_BLOCKS {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let mut i = 0;
+        while i < data.len() {
+            // Whole blocks are encrypted in batches so a backend can work on several at once
+            if self.position.is_multiple_of(16) && data.len() - i >= 16 {
+                let n = ((data.len() - i) / 16).min(KEYSTREAM_BATCH);
+                let mut keystream = [[0; 16]; KEYSTREAM_BATCH];
+                for (k, block) in keystream[..n].iter_mut().enumerate() {
+                    *block = counter_block_at(self.counter_block, self.layout, self.position / 16 + k as u64)?;
+                }
+                self.cipher.encrypt_blocks(&mut keystream[..n]);
+                for (j, byte) in data[i..i+16*n].iter_mut().enumerate() {
+                    *byte ^= keystream[j/16][j%16];
+                }
+                i += 16*n;
+                self.position += 16*n as u64;
+                continue;
+            }
+
+            let start = (self.position % 16) as usize;
+            let keystream = self.keystream_block(self.position / 16)?;
+            let n = (16 - start).min(data.len() - i);
+            for j in 0..n {
+                data[i+j] ^= keystream[start+j];
+            }
+            i += n;
+            self.position += n as u64;
+        }
+
+        Ok(())
+    }
+
+    fn keystream_block(&self, index: u64) -> Result<[u8; 16], CryptoError> {
+        let block = counter_block_at(self.counter_block, self.layout, index)?;
+        Ok(self.cipher.encrypt_block(block))
+    }
+}
+
+/// Description:
+/// Computes the counter block a given number of increments after the initial one. A 32-bit
+/// counter wraps around, but only 2^32 blocks are available before the keystream repeats.
+///
+/// Arguments:
+/// counter_block - (I,REQ) - The initial counter block
+/// layout - (I,REQ) - Which part of the block is the counter
+/// index - (I,REQ) - The number of increments
+///
+/// Returns:
+/// The counter block for block number index, or InvalidLength if a 32-bit counter has no
+/// block left at that index
+pub fn counter_block_at(counter_block: [u8; 16], layout: CounterLayout, index: u64) -> Result<[u8; 16], CryptoError> {
+    if layout != CounterLayout::Counter128 && index >= COUNTER32_BLOCKS {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let block = match layout {
+        CounterLayout::Nonce96Counter32 => {
+            let mut block = counter_block;
+            let counter = u32::from_be_bytes(counter_block[12..].try_into().unwrap());
+            block[12..].copy_from_slice(&counter.wrapping_add(index as u32).to_be_bytes());
+            block
+        }
+        CounterLayout::Counter128 => {
+            u128::from_be_bytes(counter_block).wrapping_add(index as u128).to_be_bytes()
+        }
+        CounterLayout::Counter32LeNonce96 => {
+            let mut block = counter_block;
+            let counter = u32::from_le_bytes(counter_block[..4].try_into().unwrap());
+            block[..4].copy_from_slice(&counter.wrapping_add(index as u32).to_le_bytes());
+            block
+        }
+    };
+
+    Ok(block)
+}
+
+/// Description:
+/// Encrypts with CTR. No padding is used, so the cipher text is as long as the plain text.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// counter_block - (I,REQ) - The initial counter block, which must never be reused with the same key
+/// layout - (I,REQ) - How the counter block is incremented
+///
+/// Returns:
+/// The cipher text, or InvalidLength if a 32-bit counter layout runs out of blocks
+#[allow(non_snake_case)]
+pub fn CTR_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, counter_block: [u8; 16], layout: CounterLayout) -> Result<Vec<u8>, CryptoError> {
+    let mut cipher_text = plain_text;
+    Ctr::new(cipher, counter_block, layout).apply_keystream(&mut cipher_text)?;
+
+    Ok(cipher_text)
+}
+
+/// Description:
+/// Decrypts CTR cipher text.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// cipher_text - (I,REQ) - The cipher text produced by CTR_encrypt
+/// counter_block - (I,REQ) - The initial counter block used for encryption
+/// layout - (I,REQ) - How the counter block is incremented
+///
+/// Returns:
+/// The plain text, or InvalidLength if a 32-bit counter layout runs out of blocks
+#[allow(non_snake_case)]
+pub fn CTR_decrypt<C: BlockCipher>(cipher: &C, cipher_text: Vec<u8>, counter_block: [u8; 16], layout: CounterLayout) -> Result<Vec<u8>, CryptoError> {
+    CTR_encrypt(cipher, cipher_text, counter_block, layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes128,Aes192,Aes256};
+
+    const COUNTER_BLOCK: [u8; 16] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff];
+    const PLAIN_TEXT: [u8; 64] = [0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CTR_AES_128_test() {
+        let cipher = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+        let res = CTR_encrypt(&cipher, PLAIN_TEXT.to_vec(), COUNTER_BLOCK, CounterLayout::Counter128).unwrap();
+        let actual: [u8; 64] = [0x87, 0x4d, 0x61, 0x91, 0xb6, 0x20, 0xe3, 0x26, 0x1b, 0xef, 0x68, 0x64, 0x99, 0x0d, 0xb6, 0xce, 0x98, 0x06, 0xf6, 0x6b, 0x79, 0x70, 0xfd, 0xff, 0x86, 0x17, 0x18, 0x7b, 0xb9, 0xff, 0xfd, 0xff, 0x5a, 0xe4, 0xdf, 0x3e, 0xdb, 0xd5, 0xd3, 0x5e, 0x5b, 0x4f, 0x09, 0x02, 0x0d, 0xb0, 0x3e, 0xab, 0x1e, 0x03, 0x1d, 0xda, 0x2f, 0xbe, 0x03, 0xd1, 0x79, 0x21, 0x70, 0xa0, 0xf3, 0x00, 0x9c, 0xee];
+        assert_eq!(res, actual);
+        let res = CTR_decrypt(&cipher, res, COUNTER_BLOCK, CounterLayout::Counter128).unwrap();
+        assert_eq!(res, PLAIN_TEXT);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CTR_AES_192_test() {
+        let cipher = Aes192::new([0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b]);
+        let res = CTR_encrypt(&cipher, PLAIN_TEXT.to_vec(), COUNTER_BLOCK, CounterLayout::Nonce96Counter32).unwrap();
+        let actual: [u8; 64] = [0x1a, 0xbc, 0x93, 0x24, 0x17, 0x52, 0x1c, 0xa2, 0x4f, 0x2b, 0x04, 0x59, 0xfe, 0x7e, 0x6e, 0x0b, 0x09, 0x03, 0x39, 0xec, 0x0a, 0xa6, 0xfa, 0xef, 0xd5, 0xcc, 0xc2, 0xc6, 0xf4, 0xce, 0x8e, 0x94, 0x1e, 0x36, 0xb2, 0x6b, 0xd1, 0xeb, 0xc6, 0x70, 0xd1, 0xbd, 0x1d, 0x66, 0x56, 0x20, 0xab, 0xf7, 0x4f, 0x78, 0xa7, 0xf6, 0xd2, 0x98, 0x09, 0x58, 0x5a, 0x97, 0xda, 0xec, 0x58, 0xc6, 0xb0, 0x50];
+        assert_eq!(res, actual);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn CTR_AES_256_test() {
+        let cipher = Aes256::new([0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4]);
+        let res = CTR_encrypt(&cipher, PLAIN_TEXT.to_vec(), COUNTER_BLOCK, CounterLayout::Counter128).unwrap();
+        let actual: [u8; 64] = [0x60, 0x1e, 0xc3, 0x13, 0x77, 0x57, 0x89, 0xa5, 0xb7, 0xa7, 0xf5, 0x04, 0xbb, 0xf3, 0xd2, 0x28, 0xf4, 0x43, 0xe3, 0xca, 0x4d, 0x62, 0xb5, 0x9a, 0xca, 0x84, 0xe9, 0x90, 0xca, 0xca, 0xf5, 0xc5, 0x2b, 0x09, 0x30, 0xda, 0xa2, 0x3d, 0xe9, 0x4c, 0xe8, 0x70, 0x17, 0xba, 0x2d, 0x84, 0x98, 0x8d, 0xdf, 0xc9, 0xc5, 0x8d, 0xb6, 0x7a, 0xad, 0xa6, 0x13, 0xc2, 0xdd, 0x08, 0x45, 0x79, 0x41, 0xa6];
+        assert_eq!(res, actual);
+    }
+
+    #[test]
+    fn seek_test() {
+        let cipher = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+        let cipher_text = CTR_encrypt(&cipher, PLAIN_TEXT.to_vec(), COUNTER_BLOCK, CounterLayout::Counter128).unwrap();
+
+        let mut ctr = Ctr::new(&cipher, COUNTER_BLOCK, CounterLayout::Counter128);
+        ctr.seek(21);
+        let mut res = cipher_text[21..50].to_vec();
+        ctr.apply_keystream(&mut res).unwrap();
+        assert_eq!(res, PLAIN_TEXT[21..50]);
+        assert_eq!(ctr.position(), 50);
+
+        let mut res = cipher_text[50..].to_vec();
+        ctr.apply_keystream(&mut res).unwrap();
+        assert_eq!(res, PLAIN_TEXT[50..]);
+    }
+
+    #[test]
+    fn counter_block_at_test() {
+        let block: [u8; 16] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0xff, 0xff, 0xff, 0xff, 0xfe];
+        let res = counter_block_at(block, CounterLayout::Nonce96Counter32, 3).unwrap();
+        let actual: [u8; 16] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0xff, 0x00, 0x00, 0x00, 0x01];
+        assert_eq!(res, actual);
+
+        let res = counter_block_at(block, CounterLayout::Counter128, 3).unwrap();
+        let actual: [u8; 16] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x01];
+        assert_eq!(res, actual);
+
+        let block: [u8; 16] = [0xfe, 0xff, 0xff, 0xff, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10];
+        let res = counter_block_at(block, CounterLayout::Counter32LeNonce96, 3).unwrap();
+        let actual: [u8; 16] = [0x01, 0x00, 0x00, 0x00, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10];
+        assert_eq!(res, actual);
+
+        assert_eq!(counter_block_at(block, CounterLayout::Counter32LeNonce96, u32::MAX as u64).unwrap()[..4], [0xfd, 0xff, 0xff, 0xff]);
+        assert_eq!(counter_block_at(block, CounterLayout::Counter32LeNonce96, 1 << 32), Err(CryptoError::InvalidLength));
+        assert_eq!(counter_block_at(block, CounterLayout::Nonce96Counter32, 1 << 32), Err(CryptoError::InvalidLength));
+        assert!(counter_block_at(block, CounterLayout::Counter128, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn keystream_end_test() {
+        let cipher = Aes128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+
+        // The last block of a 32-bit counter can be used, the one after it cannot
+        let mut ctr = Ctr::new(&cipher, COUNTER_BLOCK, CounterLayout::Nonce96Counter32);
+        ctr.seek((1 << 36) - 16);
+        let mut data = PLAIN_TEXT[..16].to_vec();
+        ctr.apply_keystream(&mut data).unwrap();
+        assert_eq!(ctr.position(), 1 << 36);
+        let mut data = PLAIN_TEXT[..1].to_vec();
+        assert_eq!(ctr.apply_keystream(&mut data), Err(CryptoError::InvalidLength));
+        assert_eq!(data, PLAIN_TEXT[..1]);
+
+        ctr.seek((1 << 36) - 8);
+        let mut data = PLAIN_TEXT[..9].to_vec();
+        assert_eq!(ctr.apply_keystream(&mut data), Err(CryptoError::InvalidLength));
+        assert_eq!(data, PLAIN_TEXT[..9]);
+        assert_eq!(ctr.position(), (1 << 36) - 8);
+
+        // A 128-bit counter only stops at the last byte offset
+        let mut ctr = Ctr::new(&cipher, COUNTER_BLOCK, CounterLayout::Counter128);
+        ctr.seek(u64::MAX - 4);
+        let mut data = PLAIN_TEXT[..4].to_vec();
+        ctr.apply_keystream(&mut data).unwrap();
+        let mut data = PLAIN_TEXT[..2].to_vec();
+        assert_eq!(ctr.apply_keystream(&mut data), Err(CryptoError::InvalidLength));
+        assert_eq!(data, PLAIN_TEXT[..2]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,194 @@
+use crate::cipher::BlockCipher;
+use crate::cmac::Cmac;
+use crate::ctr::{CounterLayout,Ctr};
+use crate::error::CryptoError;
+use crate::util::constant_time_eq;
+
+/// Description:
+/// EAX authenticated encryption (Bellare, Rogaway and Wagner). The nonce, the associated data
+/// and the cipher text are each authenticated with CMAC under a distinct one-block prefix
+/// (OMAC^t), the nonce MAC is the initial CTR counter block, and the tag XORs the three MACs.
+pub struct Eax<'a, C: BlockCipher> {
+    cipher: &'a C,
+    tag_length: usize,
+}
+
+impl<'a, C: BlockCipher> Eax<'a, C> {
+    /// Description:
+    /// Creates an EAX instance for a keyed cipher.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// tag_length - (I,REQ) - Tag length in bytes, from 1 to 16
+    ///
+    /// Returns:
+    /// The EAX instance, or InvalidLength for an unsupported tag length
+    pub fn new(cipher: &'a C, tag_length: usize) -> Result<Self, CryptoError> {
+        if !(1..=16).contains(&tag_length) {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        Ok(Eax { cipher, tag_length })
+    }
+
+    /// Description:
+    /// Encrypts and authenticates plain text and authenticates the associated data.
+    ///
+    /// Arguments:
+    /// plain_text - (I,REQ) - The message to encrypt
+    /// nonce - (I,REQ) - A nonce of any length that is never reused with the same key
+    /// aad - (I,REQ) - Associated data that is authenticated but not encrypted
+    ///
+    /// Returns:
+    /// cipher text || tag
+    pub fn encrypt(&self, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Vec<u8> {
+        let nonce_mac = self.omac(0, nonce);
+
+        let mut message = plain_text;
+        self.ctr(nonce_mac, &mut message);
+        let tag = self.tag(nonce_mac, aad, &message);
+        message.extend(&tag[..self.tag_length]);
+
+        message
+    }
+
+    /// Description:
+    /// Verifies the tag and decrypts. The plain text is only returned if the tag matches.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - cipher text || tag as produced by encrypt
+    /// nonce - (I,REQ) - The nonce used for encryption
+    /// aad - (I,REQ) - The associated data used for encryption
+    ///
+    /// Returns:
+    /// The plain text, InvalidLength for a message shorter than the tag
+    /// or AuthenticationFailed if the tag does not match
+    pub fn decrypt(&self, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if message.len() < self.tag_length {
+            return Err(CryptoError::InvalidLength);
+        }
+        let nonce_mac = self.omac(0, nonce);
+
+        let mut plain_text = message;
+        let received_tag = plain_text.split_off(plain_text.len() - self.tag_length);
+        let tag = self.tag(nonce_mac, aad, &plain_text);
+        if !constant_time_eq(&tag[..self.tag_length], &received_tag) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        self.ctr(nonce_mac, &mut plain_text);
+
+        Ok(plain_text)
+    }
+
+    // A 128-bit counter started at offset 0 cannot run out within one slice
+    fn ctr(&self, nonce_mac: [u8; 16], data: &mut [u8]) {
+        Ctr::new(self.cipher, nonce_mac, CounterLayout::Counter128).apply_keystream(data).expect("128-bit counter exhausted");
+    }
+
+    // OMAC^t(M) = CMAC([t]_128 || M)
+    fn omac(&self, t: u8, data: &[u8]) -> [u8; 16] {
+        let mut mac = Cmac::new(self.cipher);
+        let mut prefix: [u8; 16] = [0; 16];
+        prefix[15] = t;
+        mac.update(&prefix);
+        mac.update(data);
+        mac.finalize()
+    }
+
+    fn tag(&self, nonce_mac: [u8; 16], aad: &[u8], cipher_text: &[u8]) -> [u8; 16] {
+        let aad_mac = u128::from_be_bytes(self.omac(1, aad));
+        let cipher_text_mac = u128::from_be_bytes(self.omac(2, cipher_text));
+        (u128::from_be_bytes(nonce_mac) ^ aad_mac ^ cipher_text_mac).to_be_bytes()
+    }
+}
+
+/// Description:
+/// Encrypts with EAX and a 16-byte tag.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// nonce - (I,REQ) - A nonce of any length that is never reused with the same key
+/// aad - (I,REQ) - Associated data that is authenticated but not encrypted
+///
+/// Returns:
+/// cipher text || tag
+#[allow(non_snake_case)]
+pub fn EAX_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ok(Eax::new(cipher, 16)?.encrypt(plain_text, nonce, aad))
+}
+
+/// Description:
+/// Verifies and decrypts a message produced by EAX_encrypt.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - cipher text || tag
+/// nonce - (I,REQ) - The nonce used for encryption
+/// aad - (I,REQ) - The associated data used for encryption
+///
+/// Returns:
+/// The plain text, or an error as for Eax::decrypt
+#[allow(non_snake_case)]
+pub fn EAX_decrypt<C: BlockCipher>(cipher: &C, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Eax::new(cipher, 16)?.decrypt(message, nonce, aad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::Aes128;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn EAX_test() {
+        // Test vectors from the EAX paper: (key, message, nonce, header, cipher text || tag)
+        type Vector = ([u32; 4], &'static [u8], [u8; 16], [u8; 8], &'static [u8]);
+        let vectors: [Vector; 5] = [
+            ([0x233952de, 0xe4d5ed5f, 0x9b9c6d6f, 0xf80ff478], &[], [0x62, 0xec, 0x67, 0xf9, 0xc3, 0xa4, 0xa4, 0x07, 0xfc, 0xb2, 0xa8, 0xc4, 0x90, 0x31, 0xa8, 0xb3], [0x6b, 0xfb, 0x91, 0x4f, 0xd0, 0x7e, 0xae, 0x6b], &[0xe0, 0x37, 0x83, 0x0e, 0x83, 0x89, 0xf2, 0x7b, 0x02, 0x5a, 0x2d, 0x65, 0x27, 0xe7, 0x9d, 0x01]),
+            ([0x91945d3f, 0x4dcbee0b, 0xf45ef522, 0x55f095a4], &[0xf7, 0xfb], [0xbe, 0xca, 0xf0, 0x43, 0xb0, 0xa2, 0x3d, 0x84, 0x31, 0x94, 0xba, 0x97, 0x2c, 0x66, 0xde, 0xbd], [0xfa, 0x3b, 0xfd, 0x48, 0x06, 0xeb, 0x53, 0xfa], &[0x19, 0xdd, 0x5c, 0x4c, 0x93, 0x31, 0x04, 0x9d, 0x0b, 0xda, 0xb0, 0x27, 0x74, 0x08, 0xf6, 0x79, 0x67, 0xe5]),
+            ([0x01f74ad6, 0x4077f2e7, 0x04c0f60a, 0xda3dd523], &[0x1a, 0x47, 0xcb, 0x49, 0x33], [0x70, 0xc3, 0xdb, 0x4f, 0x0d, 0x26, 0x36, 0x84, 0x00, 0xa1, 0x0e, 0xd0, 0x5d, 0x2b, 0xff, 0x5e], [0x23, 0x4a, 0x34, 0x63, 0xc1, 0x26, 0x4a, 0xc6], &[0xd8, 0x51, 0xd5, 0xba, 0xe0, 0x3a, 0x59, 0xf2, 0x38, 0xa2, 0x3e, 0x39, 0x19, 0x9d, 0xc9, 0x26, 0x66, 0x26, 0xc4, 0x0f, 0x80]),
+            ([0xd07cf6cb, 0xb7f313bd, 0xde66b727, 0xafd3c5e8], &[0x48, 0x1c, 0x9e, 0x39, 0xb1], [0x84, 0x08, 0xdf, 0xff, 0x3c, 0x1a, 0x2b, 0x12, 0x92, 0xdc, 0x19, 0x9e, 0x46, 0xb7, 0xd6, 0x17], [0x33, 0xcc, 0xe2, 0xea, 0xbf, 0xf5, 0xa7, 0x9d], &[0x63, 0x2a, 0x9d, 0x13, 0x1a, 0xd4, 0xc1, 0x68, 0xa4, 0x22, 0x5d, 0x8e, 0x1f, 0xf7, 0x55, 0x93, 0x99, 0x74, 0xa7, 0xbe, 0xde]),
+            ([0x35b6d058, 0x0005bbc1, 0x2b058712, 0x4557d2c2], &[0x40, 0xd0, 0xc0, 0x7d, 0xa5, 0xe4], [0xfd, 0xb6, 0xb0, 0x66, 0x76, 0xee, 0xdc, 0x5c, 0x61, 0xd7, 0x42, 0x76, 0xe1, 0xf8, 0xe8, 0x16], [0xae, 0xb9, 0x6e, 0xae, 0xbe, 0x29, 0x70, 0xe9], &[0x07, 0x1d, 0xfe, 0x16, 0xc6, 0x75, 0xcb, 0x06, 0x77, 0xe5, 0x36, 0xf7, 0x3a, 0xfe, 0x6a, 0x14, 0xb7, 0x4e, 0xe4, 0x98, 0x44, 0xdd]),
+        ];
+
+        for (key, plain_text, nonce, header, actual) in vectors {
+            let cipher = Aes128::new(key);
+            let res = EAX_encrypt(&cipher, plain_text.to_vec(), &nonce, &header).unwrap();
+            assert_eq!(res, actual);
+            let res = EAX_decrypt(&cipher, res, &nonce, &header).unwrap();
+            assert_eq!(res, plain_text);
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn EAX_tag_length_test() {
+        let cipher = Aes128::new([0x233952de, 0xe4d5ed5f, 0x9b9c6d6f, 0xf80ff478]);
+        let plain_text: Vec<u8> = (0..40).collect();
+        let full = EAX_encrypt(&cipher, plain_text.clone(), b"nonce", b"header").unwrap();
+
+        let eax = Eax::new(&cipher, 8).unwrap();
+        let res = eax.encrypt(plain_text.clone(), b"nonce", b"header");
+        assert_eq!(res, full[..48]);
+        assert_eq!(eax.decrypt(res, b"nonce", b"header").unwrap(), plain_text);
+
+        let res = Eax::new(&cipher, 1).unwrap().encrypt(Vec::new(), &[], &[]);
+        assert_eq!(res.len(), 1);
+        assert!(Eax::new(&cipher, 0).is_err());
+        assert!(Eax::new(&cipher, 17).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn EAX_error_test() {
+        let cipher = Aes128::new([0x233952de, 0xe4d5ed5f, 0x9b9c6d6f, 0xf80ff478]);
+        let mut message = EAX_encrypt(&cipher, (0..30).collect(), b"nonce", b"header").unwrap();
+
+        message[0] ^= 1;
+        assert_eq!(EAX_decrypt(&cipher, message.clone(), b"nonce", b"header"), Err(CryptoError::AuthenticationFailed));
+        message[0] ^= 1;
+        assert_eq!(EAX_decrypt(&cipher, message.clone(), b"nonce!", b"header"), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(EAX_decrypt(&cipher, message[..15].to_vec(), b"nonce", b"header"), Err(CryptoError::InvalidLength));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Description:
+/// Errors returned across the crate in place of panics, so malformed input
+/// (for example cipher text received from the network) can be handled by the caller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CryptoError {
+    /// The padding at the end of decrypted text is malformed.
+    InvalidPadding,
+    /// An input or parameter has a length the operation does not accept.
+    InvalidLength,
+    /// The key has an unsupported size or encoding.
+    InvalidKey,
+    /// The authentication tag does not match, so no plain text is released.
+    AuthenticationFailed,
+    /// Division by zero, or inversion of zero, in GF(2^8).
+    DivisionByZero,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::InvalidPadding => write!(f, "invalid padding"),
+            CryptoError::InvalidLength => write!(f, "invalid length"),
+            CryptoError::InvalidKey => write!(f, "invalid key"),
+            CryptoError::AuthenticationFailed => write!(f, "authentication failed"),
+            CryptoError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
\ No newline at end of file
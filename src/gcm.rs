@@ -0,0 +1,337 @@
+#[cfg(target_arch = "x86_64")]
+use crate::aesni;
+use crate::cipher::BlockCipher;
+use crate::ctr::{counter_block_at,CounterLayout,Ctr};
+use crate::error::CryptoError;
+use crate::math::gf128_multiplication;
+use crate::util::{constant_time_eq,zeroize};
+
+/// The longest plain text GCM accepts, 2^32 - 2 blocks (SP 800-38D section 5.2.1.1). Past
+/// that the 32-bit counter would reach J0, whose encryption masks the tag.
+pub const MAX_TEXT_LENGTH: u64 = ((1 << 32) - 2) * 16;
+/// The longest associated data or IV GCM accepts, 2^64 - 1 bits in whole bytes
+pub const MAX_AAD_LENGTH: u64 = (1 << 61) - 1;
+
+/// Description:
+/// AES-GCM authenticated encryption (SP 800-38D) with a tag of 12 to 16 bytes.
+/// The hash subkey H is computed once at construction and zeroed when the instance is dropped.
+pub struct Gcm<'a, C: BlockCipher> {
+    cipher: &'a C,
+    h: u128,
+    tag_length: usize,
+}
+
+impl<'a, C: BlockCipher> Gcm<'a, C> {
+    /// Description:
+    /// Creates a GCM instance for a keyed cipher.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// tag_length - (I,REQ) - Tag length in bytes, from 12 to 16
+    ///
+    /// Returns:
+    /// The GCM instance, or InvalidLength for an unsupported tag length
+    pub fn new(cipher: &'a C, tag_length: usize) -> Result<Self, CryptoError> {
+        if !(12..=16).contains(&tag_length) {
+            return Err(CryptoError::InvalidLength);
+        }
+        let h = u128::from_be_bytes(cipher.encrypt_block([0; 16]));
+
+        Ok(Gcm { cipher, h, tag_length })
+    }
+
+    /// Description:
+    /// Encrypts and authenticates plain text and authenticates the associated data.
+    ///
+    /// Arguments:
+    /// plain_text - (I,REQ) - The message to encrypt
+    /// iv - (I,REQ) - A non-empty IV that is never reused with the same key; 12 bytes is recommended
+    /// aad - (I,REQ) - Associated data that is authenticated but not encrypted
+    ///
+    /// Returns:
+    /// cipher text || tag, or InvalidLength for an empty IV or an input over its length limit
+    pub fn encrypt(&self, plain_text: Vec<u8>, iv: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        check_lengths(iv.len() as u64, plain_text.len() as u64, aad.len() as u64)?;
+        let j0 = self.pre_counter_block(iv);
+
+        let mut message = plain_text;
+        self.gctr(j0, &mut message)?;
+        let tag = self.tag(j0, aad, &message);
+        message.extend(&tag[..self.tag_length]);
+
+        Ok(message)
+    }
+
+    /// Description:
+    /// Verifies the tag and decrypts. Nothing is decrypted unless the tag matches.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - cipher text || tag as produced by encrypt
+    /// iv - (I,REQ) - The IV used for encryption
+    /// aad - (I,REQ) - The associated data used for encryption
+    ///
+    /// Returns:
+    /// The plain text, InvalidLength for an empty IV or an input over its length limit,
+    /// or AuthenticationFailed if the tag does not match
+    pub fn decrypt(&self, message: Vec<u8>, iv: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if message.len() < self.tag_length {
+            return Err(CryptoError::InvalidLength);
+        }
+        check_lengths(iv.len() as u64, (message.len() - self.tag_length) as u64, aad.len() as u64)?;
+        let j0 = self.pre_counter_block(iv);
+
+        let mut plain_text = message;
+        let received_tag = plain_text.split_off(plain_text.len() - self.tag_length);
+        let tag = self.tag(j0, aad, &plain_text);
+        if !constant_time_eq(&tag[..self.tag_length], &received_tag) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        self.gctr(j0, &mut plain_text)?;
+
+        Ok(plain_text)
+    }
+
+    fn pre_counter_block(&self, iv: &[u8]) -> [u8; 16] {
+        if iv.len() == 12 {
+            let mut j0: [u8; 16] = [0; 16];
+            j0[..12].copy_from_slice(iv);
+            j0[15] = 1;
+            j0
+        } else {
+            ghash(self.h, &[], iv).to_be_bytes()
+        }
+    }
+
+    fn gctr(&self, j0: [u8; 16], data: &mut [u8]) -> Result<(), CryptoError> {
+        let layout = CounterLayout::Nonce96Counter32;
+        Ctr::new(self.cipher, counter_block_at(j0, layout, 1)?, layout).apply_keystream(data)
+    }
+
+    fn tag(&self, j0: [u8; 16], aad: &[u8], cipher_text: &[u8]) -> [u8; 16] {
+        let s = ghash(self.h, aad, cipher_text);
+        let mask = u128::from_be_bytes(self.cipher.encrypt_block(j0));
+
+        (s ^ mask).to_be_bytes()
+    }
+}
+
+impl<'a, C: BlockCipher> Drop for Gcm<'a, C> {
+    fn drop(&mut self) {
+        zeroize(std::slice::from_mut(&mut self.h));
+    }
+}
+
+fn check_lengths(iv_length: u64, plain_text_length: u64, aad_length: u64) -> Result<(), CryptoError> {
+    if iv_length == 0 || iv_length > MAX_AAD_LENGTH || plain_text_length > MAX_TEXT_LENGTH || aad_length > MAX_AAD_LENGTH {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    Ok(())
+}
+
+/// Description:
+/// GHASH over the associated data and cipher text, each zero padded to a whole block,
+/// followed by the block holding both of their lengths in bits.
+///
+/// Arguments:
+/// h - (I,REQ) - The hash subkey
+/// aad - (I,REQ) - Associated data
+/// cipher_text - (I,REQ) - Cipher text
+///
+/// Returns:
+/// The GHASH output as a big-endian u128
+pub fn ghash(h: u128, aad: &[u8], cipher_text: &[u8]) -> u128 {
+    #[cfg(target_arch = "x86_64")]
+    if !cfg!(feature = "force-soft") && aesni::has_pclmulqdq() {
+        // SAFETY: has_pclmulqdq() confirmed the CPU supports the pclmulqdq and sse2 target features.
+        return unsafe { hardware_ghash(h, aad, cipher_text) };
+    }
+
+    ghash_with(h, aad, cipher_text, gf128_multiplication)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "pclmulqdq,sse2")]
+fn hardware_ghash(h: u128, aad: &[u8], cipher_text: &[u8]) -> u128 {
+    ghash_with(h, aad, cipher_text, |x, y| aesni::gf128_multiplication(x, y))
+}
+
+fn ghash_with(h: u128, aad: &[u8], cipher_text: &[u8], multiply: impl Fn(u128, u128) -> u128) -> u128 {
+    let mut y: u128 = 0;
+    for data in [aad, cipher_text] {
+        for chunk in data.chunks(16) {
+            let mut block: [u8; 16] = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            y = multiply(y ^ u128::from_be_bytes(block), h);
+        }
+    }
+    let lengths = ((aad.len() as u128 * 8) << 64) | (cipher_text.len() as u128 * 8);
+
+    multiply(y ^ lengths, h)
+}
+
+/// Description:
+/// Encrypts with AES-GCM and a full 16-byte tag.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// iv - (I,REQ) - A non-empty IV that is never reused with the same key
+/// aad - (I,REQ) - Associated data
+///
+/// Returns:
+/// cipher text || tag, or InvalidLength for an empty IV or an input over its length limit
+#[allow(non_snake_case)]
+pub fn GCM_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, iv: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Gcm::new(cipher, 16)?.encrypt(plain_text, iv, aad)
+}
+
+/// Description:
+/// Verifies and decrypts AES-GCM with a full 16-byte tag.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - cipher text || tag
+/// iv - (I,REQ) - The IV used for encryption
+/// aad - (I,REQ) - Associated data
+///
+/// Returns:
+/// The plain text, InvalidLength for an empty IV or an input over its length limit,
+/// or AuthenticationFailed if the tag does not match
+#[allow(non_snake_case)]
+pub fn GCM_decrypt<C: BlockCipher>(cipher: &C, message: Vec<u8>, iv: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Gcm::new(cipher, 16)?.decrypt(message, iv, aad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes128,Aes192,Aes256};
+
+    const KEY_128: [u32; 4] = [0xfeffe992, 0x8665731c, 0x6d6a8f94, 0x67308308];
+    const IV: [u8; 12] = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+    const PLAIN_TEXT: [u8; 64] = [0xd9, 0x31, 0x32, 0x25, 0xf8, 0x84, 0x06, 0xe5, 0xa5, 0x59, 0x09, 0xc5, 0xaf, 0xf5, 0x26, 0x9a, 0x86, 0xa7, 0xa9, 0x53, 0x15, 0x34, 0xf7, 0xda, 0x2e, 0x4c, 0x30, 0x3d, 0x8a, 0x31, 0x8a, 0x72, 0x1c, 0x3c, 0x0c, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2f, 0xcf, 0x0e, 0x24, 0x49, 0xa6, 0xb5, 0x25, 0xb1, 0x6a, 0xed, 0xf5, 0xaa, 0x0d, 0xe6, 0x57, 0xba, 0x63, 0x7b, 0x39, 0x1a, 0xaf, 0xd2, 0x55];
+    const AAD: [u8; 20] = [0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xab, 0xad, 0xda, 0xd2];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_empty_test() {
+        // Test case 1
+        let cipher = Aes128::new([0; 4]);
+        let res = GCM_encrypt(&cipher, vec![], &[0; 12], &[]).unwrap();
+        let actual = vec![0x58, 0xe2, 0xfc, 0xce, 0xfa, 0x7e, 0x30, 0x61, 0x36, 0x7f, 0x1d, 0x57, 0xa4, 0xe7, 0x45, 0x5a];
+        assert_eq!(res, actual);
+
+        // Test case 2
+        let res = GCM_encrypt(&cipher, vec![0; 16], &[0; 12], &[]).unwrap();
+        let actual = vec![0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2, 0xfe, 0x78, 0xab, 0x6e, 0x47, 0xd4, 0x2c, 0xec, 0x13, 0xbd, 0xf5, 0x3a, 0x67, 0xb2, 0x12, 0x57, 0xbd, 0xdf];
+        assert_eq!(res, actual);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_AES_128_test() {
+        // Test case 3
+        let cipher = Aes128::new(KEY_128);
+        let res = GCM_encrypt(&cipher, PLAIN_TEXT.to_vec(), &IV, &[]).unwrap();
+        let actual = vec![0x42, 0x83, 0x1e, 0xc2, 0x21, 0x77, 0x74, 0x24, 0x4b, 0x72, 0x21, 0xb7, 0x84, 0xd0, 0xd4, 0x9c, 0xe3, 0xaa, 0x21, 0x2f, 0x2c, 0x02, 0xa4, 0xe0, 0x35, 0xc1, 0x7e, 0x23, 0x29, 0xac, 0xa1, 0x2e, 0x21, 0xd5, 0x14, 0xb2, 0x54, 0x66, 0x93, 0x1c, 0x7d, 0x8f, 0x6a, 0x5a, 0xac, 0x84, 0xaa, 0x05, 0x1b, 0xa3, 0x0b, 0x39, 0x6a, 0x0a, 0xac, 0x97, 0x3d, 0x58, 0xe0, 0x91, 0x47, 0x3f, 0x59, 0x85, 0x4d, 0x5c, 0x2a, 0xf3, 0x27, 0xcd, 0x64, 0xa6, 0x2c, 0xf3, 0x5a, 0xbd, 0x2b, 0xa6, 0xfa, 0xb4];
+        assert_eq!(res, actual);
+        assert_eq!(GCM_decrypt(&cipher, res, &IV, &[]).unwrap(), PLAIN_TEXT);
+
+        // Test case 4
+        let res = GCM_encrypt(&cipher, PLAIN_TEXT[..60].to_vec(), &IV, &AAD).unwrap();
+        let actual = vec![0x42, 0x83, 0x1e, 0xc2, 0x21, 0x77, 0x74, 0x24, 0x4b, 0x72, 0x21, 0xb7, 0x84, 0xd0, 0xd4, 0x9c, 0xe3, 0xaa, 0x21, 0x2f, 0x2c, 0x02, 0xa4, 0xe0, 0x35, 0xc1, 0x7e, 0x23, 0x29, 0xac, 0xa1, 0x2e, 0x21, 0xd5, 0x14, 0xb2, 0x54, 0x66, 0x93, 0x1c, 0x7d, 0x8f, 0x6a, 0x5a, 0xac, 0x84, 0xaa, 0x05, 0x1b, 0xa3, 0x0b, 0x39, 0x6a, 0x0a, 0xac, 0x97, 0x3d, 0x58, 0xe0, 0x91, 0x5b, 0xc9, 0x4f, 0xbc, 0x32, 0x21, 0xa5, 0xdb, 0x94, 0xfa, 0xe9, 0x5a, 0xe7, 0x12, 0x1a, 0x47];
+        assert_eq!(res, actual);
+        assert_eq!(GCM_decrypt(&cipher, res, &IV, &AAD).unwrap(), PLAIN_TEXT[..60]);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_IV_length_test() {
+        // Test case 5
+        let cipher = Aes128::new(KEY_128);
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad];
+        let res = GCM_encrypt(&cipher, PLAIN_TEXT[..60].to_vec(), &iv, &AAD).unwrap();
+        let actual = vec![0x61, 0x35, 0x3b, 0x4c, 0x28, 0x06, 0x93, 0x4a, 0x77, 0x7f, 0xf5, 0x1f, 0xa2, 0x2a, 0x47, 0x55, 0x69, 0x9b, 0x2a, 0x71, 0x4f, 0xcd, 0xc6, 0xf8, 0x37, 0x66, 0xe5, 0xf9, 0x7b, 0x6c, 0x74, 0x23, 0x73, 0x80, 0x69, 0x00, 0xe4, 0x9f, 0x24, 0xb2, 0x2b, 0x09, 0x75, 0x44, 0xd4, 0x89, 0x6b, 0x42, 0x49, 0x89, 0xb5, 0xe1, 0xeb, 0xac, 0x0f, 0x07, 0xc2, 0x3f, 0x45, 0x98, 0x36, 0x12, 0xd2, 0xe7, 0x9e, 0x3b, 0x07, 0x85, 0x56, 0x1b, 0xe1, 0x4a, 0xac, 0xa2, 0xfc, 0xcb];
+        assert_eq!(res, actual);
+        assert_eq!(GCM_decrypt(&cipher, res, &iv, &AAD).unwrap(), PLAIN_TEXT[..60]);
+
+        // Test case 6
+        let iv = [0x93, 0x13, 0x22, 0x5d, 0xf8, 0x84, 0x06, 0xe5, 0x55, 0x90, 0x9c, 0x5a, 0xff, 0x52, 0x69, 0xaa, 0x6a, 0x7a, 0x95, 0x38, 0x53, 0x4f, 0x7d, 0xa1, 0xe4, 0xc3, 0x03, 0xd2, 0xa3, 0x18, 0xa7, 0x28, 0xc3, 0xc0, 0xc9, 0x51, 0x56, 0x80, 0x95, 0x39, 0xfc, 0xf0, 0xe2, 0x42, 0x9a, 0x6b, 0x52, 0x54, 0x16, 0xae, 0xdb, 0xf5, 0xa0, 0xde, 0x6a, 0x57, 0xa6, 0x37, 0xb3, 0x9b];
+        let res = GCM_encrypt(&cipher, PLAIN_TEXT[..60].to_vec(), &iv, &AAD).unwrap();
+        let actual = vec![0x8c, 0xe2, 0x49, 0x98, 0x62, 0x56, 0x15, 0xb6, 0x03, 0xa0, 0x33, 0xac, 0xa1, 0x3f, 0xb8, 0x94, 0xbe, 0x91, 0x12, 0xa5, 0xc3, 0xa2, 0x11, 0xa8, 0xba, 0x26, 0x2a, 0x3c, 0xca, 0x7e, 0x2c, 0xa7, 0x01, 0xe4, 0xa9, 0xa4, 0xfb, 0xa4, 0x3c, 0x90, 0xcc, 0xdc, 0xb2, 0x81, 0xd4, 0x8c, 0x7c, 0x6f, 0xd6, 0x28, 0x75, 0xd2, 0xac, 0xa4, 0x17, 0x03, 0x4c, 0x34, 0xae, 0xe5, 0x61, 0x9c, 0xc5, 0xae, 0xff, 0xfe, 0x0b, 0xfa, 0x46, 0x2a, 0xf4, 0x3c, 0x16, 0x99, 0xd0, 0x50];
+        assert_eq!(res, actual);
+
+        assert_eq!(GCM_encrypt(&cipher, PLAIN_TEXT.to_vec(), &[], &AAD), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    fn length_limit_test() {
+        assert_eq!(check_lengths(12, MAX_TEXT_LENGTH, MAX_AAD_LENGTH), Ok(()));
+        assert_eq!(check_lengths(MAX_AAD_LENGTH, 0, 0), Ok(()));
+        assert_eq!(check_lengths(12, MAX_TEXT_LENGTH + 1, 0), Err(CryptoError::InvalidLength));
+        assert_eq!(check_lengths(12, 0, MAX_AAD_LENGTH + 1), Err(CryptoError::InvalidLength));
+        assert_eq!(check_lengths(MAX_AAD_LENGTH + 1, 0, 0), Err(CryptoError::InvalidLength));
+        assert_eq!(check_lengths(0, 0, 0), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_AES_192_test() {
+        // Test case 10
+        let cipher = Aes192::new([0xfeffe992, 0x8665731c, 0x6d6a8f94, 0x67308308, 0xfeffe992, 0x8665731c]);
+        let res = GCM_encrypt(&cipher, PLAIN_TEXT[..60].to_vec(), &IV, &AAD).unwrap();
+        let actual = vec![0x39, 0x80, 0xca, 0x0b, 0x3c, 0x00, 0xe8, 0x41, 0xeb, 0x06, 0xfa, 0xc4, 0x87, 0x2a, 0x27, 0x57, 0x85, 0x9e, 0x1c, 0xea, 0xa6, 0xef, 0xd9, 0x84, 0x62, 0x85, 0x93, 0xb4, 0x0c, 0xa1, 0xe1, 0x9c, 0x7d, 0x77, 0x3d, 0x00, 0xc1, 0x44, 0xc5, 0x25, 0xac, 0x61, 0x9d, 0x18, 0xc8, 0x4a, 0x3f, 0x47, 0x18, 0xe2, 0x44, 0x8b, 0x2f, 0xe3, 0x24, 0xd9, 0xcc, 0xda, 0x27, 0x10, 0x25, 0x19, 0x49, 0x8e, 0x80, 0xf1, 0x47, 0x8f, 0x37, 0xba, 0x55, 0xbd, 0x6d, 0x27, 0x61, 0x8c];
+        assert_eq!(res, actual);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_AES_256_test() {
+        // Test cases 13 and 14
+        let cipher = Aes256::new([0; 8]);
+        let res = GCM_encrypt(&cipher, vec![], &[0; 12], &[]).unwrap();
+        let actual = vec![0x53, 0x0f, 0x8a, 0xfb, 0xc7, 0x45, 0x36, 0xb9, 0xa9, 0x63, 0xb4, 0xf1, 0xc4, 0xcb, 0x73, 0x8b];
+        assert_eq!(res, actual);
+        let res = GCM_encrypt(&cipher, vec![0; 16], &[0; 12], &[]).unwrap();
+        let actual = vec![0xce, 0xa7, 0x40, 0x3d, 0x4d, 0x60, 0x6b, 0x6e, 0x07, 0x4e, 0xc5, 0xd3, 0xba, 0xf3, 0x9d, 0x18, 0xd0, 0xd1, 0xc8, 0xa7, 0x99, 0x99, 0x6b, 0xf0, 0x26, 0x5b, 0x98, 0xb5, 0xd4, 0x8a, 0xb9, 0x19];
+        assert_eq!(res, actual);
+
+        // Test case 16
+        let cipher = Aes256::new([0xfeffe992, 0x8665731c, 0x6d6a8f94, 0x67308308, 0xfeffe992, 0x8665731c, 0x6d6a8f94, 0x67308308]);
+        let res = GCM_encrypt(&cipher, PLAIN_TEXT[..60].to_vec(), &IV, &AAD).unwrap();
+        let actual = vec![0x52, 0x2d, 0xc1, 0xf0, 0x99, 0x56, 0x7d, 0x07, 0xf4, 0x7f, 0x37, 0xa3, 0x2a, 0x84, 0x42, 0x7d, 0x64, 0x3a, 0x8c, 0xdc, 0xbf, 0xe5, 0xc0, 0xc9, 0x75, 0x98, 0xa2, 0xbd, 0x25, 0x55, 0xd1, 0xaa, 0x8c, 0xb0, 0x8e, 0x48, 0x59, 0x0d, 0xbb, 0x3d, 0xa7, 0xb0, 0x8b, 0x10, 0x56, 0x82, 0x88, 0x38, 0xc5, 0xf6, 0x1e, 0x63, 0x93, 0xba, 0x7a, 0x0a, 0xbc, 0xc9, 0xf6, 0x62, 0x76, 0xfc, 0x6e, 0xce, 0x0f, 0x4e, 0x17, 0x68, 0xcd, 0xdf, 0x88, 0x53, 0xbb, 0x2d, 0x55, 0x1b];
+        assert_eq!(res, actual);
+        assert_eq!(GCM_decrypt(&cipher, res, &IV, &AAD).unwrap(), PLAIN_TEXT[..60]);
+    }
+
+    #[test]
+    fn truncated_tag_test() {
+        let cipher = Aes128::new(KEY_128);
+        let gcm = Gcm::new(&cipher, 12).unwrap();
+        let res = gcm.encrypt(PLAIN_TEXT[..60].to_vec(), &IV, &AAD).unwrap();
+        assert_eq!(res.len(), 72);
+        assert_eq!(res[60..], [0x5b, 0xc9, 0x4f, 0xbc, 0x32, 0x21, 0xa5, 0xdb, 0x94, 0xfa, 0xe9, 0x5a]);
+        assert_eq!(gcm.decrypt(res, &IV, &AAD).unwrap(), PLAIN_TEXT[..60]);
+
+        assert!(Gcm::new(&cipher, 11).is_err());
+        assert!(Gcm::new(&cipher, 17).is_err());
+    }
+
+    #[test]
+    fn authentication_failure_test() {
+        let cipher = Aes128::new(KEY_128);
+        let message = GCM_encrypt(&cipher, PLAIN_TEXT[..60].to_vec(), &IV, &AAD).unwrap();
+
+        let mut tampered = message.clone();
+        tampered[3] ^= 0x01;
+        assert_eq!(GCM_decrypt(&cipher, tampered, &IV, &AAD), Err(CryptoError::AuthenticationFailed));
+
+        let mut tampered = message.clone();
+        tampered[70] ^= 0x80;
+        assert_eq!(GCM_decrypt(&cipher, tampered, &IV, &AAD), Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(GCM_decrypt(&cipher, message.clone(), &IV, &AAD[1..]), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(GCM_decrypt(&cipher, message[..10].to_vec(), &IV, &AAD), Err(CryptoError::InvalidLength));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,265 @@
+use crate::cipher::{Aes,BlockCipher};
+use crate::ctr::{CounterLayout,Ctr};
+use crate::error::CryptoError;
+use crate::key::SecretKey;
+use crate::math::polyval;
+use crate::util::{constant_time_eq,zeroize};
+
+/// The longest plain text or associated data AES-GCM-SIV accepts, 2^36 bytes (RFC 8452 section 6)
+pub const MAX_LENGTH: u64 = 1 << 36;
+
+/// Description:
+/// AES-GCM-SIV nonce-misuse-resistant authenticated encryption (RFC 8452) with a 16-byte tag.
+/// Fresh authentication and encryption keys are derived from the key-generating key for
+/// every nonce, and the tag, computed with POLYVAL over the plain text, doubles as the CTR IV.
+/// Only AES-128 and AES-256 key-generating keys are defined.
+pub struct GcmSiv<'a, const NK: usize> {
+    cipher: &'a Aes<NK>,
+}
+
+impl<'a, const NK: usize> GcmSiv<'a, NK> {
+    /// Description:
+    /// Creates an AES-GCM-SIV instance.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - AES keyed with the key-generating key
+    ///
+    /// Returns:
+    /// The AES-GCM-SIV instance, or InvalidKey for an AES-192 key
+    pub fn new(cipher: &'a Aes<NK>) -> Result<Self, CryptoError> {
+        if NK != 4 && NK != 8 {
+            return Err(CryptoError::InvalidKey);
+        }
+
+        Ok(GcmSiv { cipher })
+    }
+
+    /// Description:
+    /// Encrypts and authenticates plain text and authenticates the associated data.
+    ///
+    /// Arguments:
+    /// plain_text - (I,REQ) - The message to encrypt, at most 2^36 bytes
+    /// nonce - (I,REQ) - A 12-byte nonce; reusing it only reveals repeated messages
+    /// aad - (I,REQ) - Associated data that is authenticated but not encrypted, at most 2^36 bytes
+    ///
+    /// Returns:
+    /// cipher text || tag, or InvalidLength for a wrong nonce or input length
+    pub fn encrypt(&self, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        check_lengths(nonce, plain_text.len(), aad.len())?;
+
+        let (mut auth_key, encryption_cipher) = self.derive_keys(nonce);
+        let tag = tag(&encryption_cipher, auth_key, nonce, aad, &plain_text);
+        zeroize(std::slice::from_mut(&mut auth_key));
+        let mut message = plain_text;
+        ctr(&encryption_cipher, tag, &mut message)?;
+        message.extend(tag);
+
+        Ok(message)
+    }
+
+    /// Description:
+    /// Decrypts and verifies the tag. The plain text is only returned if the tag matches.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - cipher text || tag as produced by encrypt
+    /// nonce - (I,REQ) - The nonce used for encryption
+    /// aad - (I,REQ) - The associated data used for encryption
+    ///
+    /// Returns:
+    /// The plain text, or AuthenticationFailed if the tag does not match
+    pub fn decrypt(&self, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if message.len() < 16 {
+            return Err(CryptoError::InvalidLength);
+        }
+        check_lengths(nonce, message.len() - 16, aad.len())?;
+
+        let (mut auth_key, encryption_cipher) = self.derive_keys(nonce);
+        let mut plain_text = message;
+        let received_tag: [u8; 16] = plain_text.split_off(plain_text.len() - 16).try_into().unwrap();
+        ctr(&encryption_cipher, received_tag, &mut plain_text)?;
+
+        let tag = tag(&encryption_cipher, auth_key, nonce, aad, &plain_text);
+        zeroize(std::slice::from_mut(&mut auth_key));
+        if !constant_time_eq(&tag, &received_tag) {
+            plain_text.fill(0);
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        Ok(plain_text)
+    }
+
+    // RFC 8452 section 4: the first half of each of the blocks E(le32(i) || nonce)
+    // makes up the 16-byte authentication key and then the encryption key
+    fn derive_keys(&self, nonce: &[u8]) -> (u128, Aes<NK>) {
+        let mut key_material: Vec<u8> = Vec::with_capacity(16 + 4*NK);
+        for i in 0..(2 + NK/2) as u32 {
+            let mut block: [u8; 16] = [0; 16];
+            block[..4].copy_from_slice(&i.to_le_bytes());
+            block[4..].copy_from_slice(nonce);
+            key_material.extend(&self.cipher.encrypt_block(block)[..8]);
+        }
+
+        let auth_key = u128::from_le_bytes(key_material[..16].try_into().unwrap());
+        let encryption_key: SecretKey<NK> = SecretKey::new(std::array::from_fn(|i| {
+            u32::from_be_bytes(key_material[16+4*i..20+4*i].try_into().unwrap())
+        }));
+        zeroize(&mut key_material);
+
+        (auth_key, Aes::with_key(&encryption_key))
+    }
+}
+
+fn check_lengths(nonce: &[u8], plain_text_length: usize, aad_length: usize) -> Result<(), CryptoError> {
+    if nonce.len() != 12 || plain_text_length as u64 > MAX_LENGTH || aad_length as u64 > MAX_LENGTH {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    Ok(())
+}
+
+fn tag<const NK: usize>(cipher: &Aes<NK>, auth_key: u128, nonce: &[u8], aad: &[u8], plain_text: &[u8]) -> [u8; 16] {
+    let mut lengths: [u8; 16] = [0; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+    lengths[8..].copy_from_slice(&(plain_text.len() as u64 * 8).to_le_bytes());
+
+    let mut s = polyval(auth_key, &[aad, plain_text, &lengths]).to_le_bytes();
+    for (s, n) in s.iter_mut().zip(nonce) {
+        *s ^= n;
+    }
+    s[15] &= 0x7f;
+
+    cipher.encrypt_block(s)
+}
+
+// The initial counter block is the tag with its top bit set
+fn ctr<const NK: usize>(cipher: &Aes<NK>, tag: [u8; 16], data: &mut [u8]) -> Result<(), CryptoError> {
+    let mut counter_block = tag;
+    counter_block[15] |= 0x80;
+    Ctr::new(cipher, counter_block, CounterLayout::Counter32LeNonce96).apply_keystream(data)
+}
+
+/// Description:
+/// Encrypts with AES-GCM-SIV.
+///
+/// Arguments:
+/// cipher - (I,REQ) - AES-128 or AES-256 keyed with the key-generating key
+/// plain_text - (I,REQ) - The message to encrypt
+/// nonce - (I,REQ) - A 12-byte nonce
+/// aad - (I,REQ) - Associated data
+///
+/// Returns:
+/// cipher text || tag
+#[allow(non_snake_case)]
+pub fn GCM_SIV_encrypt<const NK: usize>(cipher: &Aes<NK>, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    GcmSiv::new(cipher)?.encrypt(plain_text, nonce, aad)
+}
+
+/// Description:
+/// Verifies and decrypts AES-GCM-SIV.
+///
+/// Arguments:
+/// cipher - (I,REQ) - AES-128 or AES-256 keyed with the key-generating key
+/// message - (I,REQ) - cipher text || tag
+/// nonce - (I,REQ) - The nonce used for encryption
+/// aad - (I,REQ) - Associated data
+///
+/// Returns:
+/// The plain text, or AuthenticationFailed if the tag does not match
+#[allow(non_snake_case)]
+pub fn GCM_SIV_decrypt<const NK: usize>(cipher: &Aes<NK>, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    GcmSiv::new(cipher)?.decrypt(message, nonce, aad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes128,Aes192,Aes256};
+
+    const NONCE: [u8; 12] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_SIV_AES_128_test() {
+        // RFC 8452 appendix C.1
+        let cipher = Aes128::new([0x01000000, 0, 0, 0]);
+
+        let res = GCM_SIV_encrypt(&cipher, vec![], &NONCE, &[]).unwrap();
+        let actual: [u8; 16] = [0xdc, 0x20, 0xe2, 0xd8, 0x3f, 0x25, 0x70, 0x5b, 0xb4, 0x9e, 0x43, 0x9e, 0xca, 0x56, 0xde, 0x25];
+        assert_eq!(res, actual);
+
+        let res = GCM_SIV_decrypt(&cipher, res, &NONCE, &[]).unwrap();
+        assert!(res.is_empty());
+
+        let plain_text: [u8; 8] = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let res = GCM_SIV_encrypt(&cipher, plain_text.to_vec(), &NONCE, &[]).unwrap();
+        let actual: [u8; 24] = [0xb5, 0xd8, 0x39, 0x33, 0x0a, 0xc7, 0xb7, 0x86, 0x57, 0x87, 0x82, 0xff, 0xf6, 0x01, 0x3b, 0x81, 0x5b, 0x28, 0x7c, 0x22, 0x49, 0x3a, 0x36, 0x4c];
+        assert_eq!(res, actual);
+
+        let res = GCM_SIV_decrypt(&cipher, res, &NONCE, &[]).unwrap();
+        assert_eq!(res, plain_text);
+
+        let plain_text: [u8; 8] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let res = GCM_SIV_encrypt(&cipher, plain_text.to_vec(), &NONCE, &[0x01]).unwrap();
+        let actual: [u8; 24] = [0x1e, 0x6d, 0xab, 0xa3, 0x56, 0x69, 0xf4, 0x27, 0x3b, 0x0a, 0x1a, 0x25, 0x60, 0x96, 0x9c, 0xdf, 0x79, 0x0d, 0x99, 0x75, 0x9a, 0xbd, 0x15, 0x08];
+        assert_eq!(res, actual);
+
+        let res = GCM_SIV_decrypt(&cipher, res, &NONCE, &[0x01]).unwrap();
+        assert_eq!(res, plain_text);
+
+        let plain_text: [u8; 64] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let aad: [u8; 12] = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let res = GCM_SIV_encrypt(&cipher, plain_text.to_vec(), &NONCE, &aad).unwrap();
+        let actual: [u8; 80] = [0x6c, 0xf2, 0x10, 0x86, 0x90, 0x4f, 0xc7, 0x69, 0x4f, 0x72, 0xc6, 0x74, 0x67, 0x9c, 0xad, 0xf0, 0x8e, 0xea, 0xc5, 0x3b, 0xb7, 0x7c, 0xc5, 0x2e, 0x36, 0x01, 0x35, 0x68, 0x6b, 0xc3, 0xd1, 0x1c, 0x04, 0x4b, 0xe1, 0xca, 0xca, 0xd5, 0xda, 0x63, 0xca, 0x58, 0x98, 0x79, 0x1d, 0x8d, 0xee, 0xef, 0xad, 0x46, 0xf5, 0x62, 0x59, 0x5b, 0xed, 0x8b, 0xf6, 0x5b, 0x07, 0x18, 0xf5, 0x2c, 0xa7, 0x4f, 0x71, 0x46, 0xba, 0x24, 0xa1, 0x2a, 0x60, 0xbc, 0xac, 0xc7, 0xf3, 0xcb, 0x8a, 0x13, 0xb1, 0x07];
+        assert_eq!(res, actual);
+
+        let res = GCM_SIV_decrypt(&cipher, res, &NONCE, &aad).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn GCM_SIV_AES_256_test() {
+        // RFC 8452 appendix C.2
+        let cipher = Aes256::new([0x01000000, 0, 0, 0, 0, 0, 0, 0]);
+
+        let res = GCM_SIV_encrypt(&cipher, vec![], &NONCE, &[]).unwrap();
+        let actual: [u8; 16] = [0x07, 0xf5, 0xf4, 0x16, 0x9b, 0xbf, 0x55, 0xa8, 0x40, 0x0c, 0xd4, 0x7e, 0xa6, 0xfd, 0x40, 0x0f];
+        assert_eq!(res, actual);
+
+        let res = GCM_SIV_decrypt(&cipher, res, &NONCE, &[]).unwrap();
+        assert!(res.is_empty());
+
+        let plain_text: [u8; 32] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let res = GCM_SIV_encrypt(&cipher, plain_text.to_vec(), &NONCE, &[0x01]).unwrap();
+        let actual: [u8; 48] = [0x07, 0xda, 0xd3, 0x64, 0xbf, 0xc2, 0xb9, 0xda, 0x89, 0x11, 0x6d, 0x7b, 0xef, 0x6d, 0xaa, 0xaf, 0x6f, 0x25, 0x55, 0x10, 0xaa, 0x65, 0x4f, 0x92, 0x0a, 0xc8, 0x1b, 0x94, 0xe8, 0xba, 0xd3, 0x65, 0xae, 0xa1, 0xba, 0xd1, 0x27, 0x02, 0xe1, 0x96, 0x56, 0x04, 0x37, 0x4a, 0xab, 0x96, 0xdb, 0xbc];
+        assert_eq!(res, actual);
+
+        let res = GCM_SIV_decrypt(&cipher, res, &NONCE, &[0x01]).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    fn authentication_failure_test() {
+        let cipher = Aes128::new([0x01000000, 0, 0, 0]);
+        let message = GCM_SIV_encrypt(&cipher, vec![0; 20], &NONCE, b"header").unwrap();
+
+        let mut tampered = message.clone();
+        tampered[0] ^= 1;
+        let res = GCM_SIV_decrypt(&cipher, tampered, &NONCE, b"header");
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+
+        // A flipped tag bit
+        let mut tampered = message.clone();
+        tampered[20] ^= 1;
+        let res = GCM_SIV_decrypt(&cipher, tampered, &NONCE, b"header");
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(GCM_SIV_decrypt(&cipher, message.clone(), &NONCE, b""), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(GCM_SIV_decrypt(&cipher, message.clone(), &[0; 12], b"header"), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(GCM_SIV_decrypt(&cipher, message[..15].to_vec(), &NONCE, b"header"), Err(CryptoError::InvalidLength));
+        assert_eq!(GCM_SIV_encrypt(&cipher, vec![], &[0; 16], &[]), Err(CryptoError::InvalidLength));
+
+        let cipher = Aes192::new([0; 6]);
+        assert_eq!(GCM_SIV_encrypt(&cipher, vec![], &NONCE, &[]), Err(CryptoError::InvalidKey));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,576 @@
+use crate::util::zeroize;
+
+/// SHA-224 and SHA-256 round constants (FIPS 180-4 section 4.2.2)
+const K256: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-384, SHA-512 and SHA-512/t round constants (FIPS 180-4 section 4.2.3)
+const K512: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+/// SHA-224 initial hash value (FIPS 180-4 section 5.3.2)
+const H224: [u32; 8] = [0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4];
+
+/// SHA-256 initial hash value (FIPS 180-4 section 5.3.3)
+const H256: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+/// SHA-384 initial hash value (FIPS 180-4 section 5.3.4)
+const H384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+    0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+];
+
+/// SHA-512 initial hash value (FIPS 180-4 section 5.3.5)
+const H512: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+/// SHA-512/256 initial hash value (FIPS 180-4 section 5.3.6.2)
+const H512_256: [u64; 8] = [
+    0x22312194fc2bf72c, 0x9f555fa3c84c64c2, 0x2393b86b6f53b151, 0x963877195940eabd,
+    0x96283ee2a88effe3, 0xbe5e1e2553863992, 0x2b0199fc2c85b8aa, 0x0eb72ddc81c52ca2,
+];
+
+/// Description:
+/// A hash function that absorbs a message in pieces of any size.
+pub trait Digest: Sized {
+    /// Digest length in bytes
+    const OUTPUT_SIZE: usize;
+    /// Length in bytes of the blocks the compression function consumes
+    const BLOCK_SIZE: usize;
+    type Output: AsRef<[u8]>;
+
+    /// Description:
+    /// Starts from the initial hash value.
+    ///
+    /// Returns:
+    /// A hash with no input yet
+    fn new() -> Self;
+
+    /// Description:
+    /// Absorbs more of the message.
+    ///
+    /// Arguments:
+    /// data - (I,REQ) - The next bytes of the message
+    fn update(&mut self, data: &[u8]);
+
+    /// Description:
+    /// Pads the message and processes the last block.
+    ///
+    /// Returns:
+    /// The digest of everything absorbed
+    fn finalize(self) -> Self::Output;
+
+    /// Description:
+    /// Discards everything absorbed so the hash can be reused for another message.
+    fn reset(&mut self);
+
+    /// Description:
+    /// Hashes a whole message.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - The message to hash
+    ///
+    /// Returns:
+    /// The digest
+    fn digest(message: &[u8]) -> Self::Output {
+        let mut hash = Self::new();
+        hash.update(message);
+        hash.finalize()
+    }
+}
+
+/// Description:
+/// SHA-224 and SHA-256 (FIPS 180-4 section 6.2), which share the compression function and
+/// differ in the initial hash value and how much of the final state is output. N is the
+/// digest length in bytes.
+#[derive(Clone)]
+pub struct Sha256Core<const N: usize> {
+    state: [u32; 8],
+    buffer: [u8; 64],
+    buffer_length: usize,
+    length: u64,
+}
+
+pub type Sha224 = Sha256Core<28>;
+pub type Sha256 = Sha256Core<32>;
+
+impl<const N: usize> Sha256Core<N> {
+    fn initial_state() -> [u32; 8] {
+        const { assert!(N == 28 || N == 32, "SHA-256 digests are 28 or 32 bytes long") };
+
+        if N == 28 { H224 } else { H256 }
+    }
+}
+
+impl<const N: usize> Digest for Sha256Core<N> {
+    const OUTPUT_SIZE: usize = N;
+    const BLOCK_SIZE: usize = 64;
+    type Output = [u8; N];
+
+    fn new() -> Self {
+        Sha256Core { state: Self::initial_state(), buffer: [0; 64], buffer_length: 0, length: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+        let state = &mut self.state;
+        absorb(&mut self.buffer, &mut self.buffer_length, data, |block| compress256(state, block));
+    }
+
+    fn finalize(mut self) -> [u8; N] {
+        let state = &mut self.state;
+        pad(&mut self.buffer, self.buffer_length, &(self.length << 3).to_be_bytes(), |block| compress256(state, block));
+
+        let mut output = [0; N];
+        for (chunk, word) in output.chunks_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes()[..chunk.len()]);
+        }
+        output
+    }
+
+    fn reset(&mut self) {
+        self.state = Self::initial_state();
+        zeroize(&mut self.buffer);
+        self.buffer_length = 0;
+        self.length = 0;
+    }
+}
+
+impl<const N: usize> Drop for Sha256Core<N> {
+    fn drop(&mut self) {
+        zeroize(&mut self.state);
+        zeroize(&mut self.buffer);
+    }
+}
+
+/// Description:
+/// SHA-384, SHA-512 and SHA-512/256 (FIPS 180-4 sections 6.4 to 6.7), which share the
+/// compression function and differ in the initial hash value and how much of the final
+/// state is output. N is the digest length in bytes.
+#[derive(Clone)]
+pub struct Sha512Core<const N: usize> {
+    state: [u64; 8],
+    buffer: [u8; 128],
+    buffer_length: usize,
+    length: u128,
+}
+
+pub type Sha384 = Sha512Core<48>;
+pub type Sha512 = Sha512Core<64>;
+pub type Sha512_256 = Sha512Core<32>;
+
+impl<const N: usize> Sha512Core<N> {
+    fn initial_state() -> [u64; 8] {
+        const { assert!(N == 32 || N == 48 || N == 64, "SHA-512 digests are 32, 48 or 64 bytes long") };
+
+        match N {
+            32 => H512_256,
+            48 => H384,
+            _ => H512,
+        }
+    }
+}
+
+impl<const N: usize> Digest for Sha512Core<N> {
+    const OUTPUT_SIZE: usize = N;
+    const BLOCK_SIZE: usize = 128;
+    type Output = [u8; N];
+
+    fn new() -> Self {
+        Sha512Core { state: Self::initial_state(), buffer: [0; 128], buffer_length: 0, length: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u128);
+        let state = &mut self.state;
+        absorb(&mut self.buffer, &mut self.buffer_length, data, |block| compress512(state, block));
+    }
+
+    fn finalize(mut self) -> [u8; N] {
+        let state = &mut self.state;
+        pad(&mut self.buffer, self.buffer_length, &(self.length << 3).to_be_bytes(), |block| compress512(state, block));
+
+        let mut output = [0; N];
+        for (chunk, word) in output.chunks_mut(8).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes()[..chunk.len()]);
+        }
+        output
+    }
+
+    fn reset(&mut self) {
+        self.state = Self::initial_state();
+        zeroize(&mut self.buffer);
+        self.buffer_length = 0;
+        self.length = 0;
+    }
+}
+
+impl<const N: usize> Drop for Sha512Core<N> {
+    fn drop(&mut self) {
+        zeroize(&mut self.state);
+        zeroize(&mut self.buffer);
+    }
+}
+
+// Copies data into the block buffer, compressing each block as it fills. A full block is
+// compressed straight away since finalize always has room to append padding after it.
+fn absorb<const B: usize>(buffer: &mut [u8; B], buffer_length: &mut usize, data: &[u8], mut compress: impl FnMut(&[u8; B])) {
+    let mut data = data;
+    if *buffer_length > 0 {
+        let n = (B - *buffer_length).min(data.len());
+        buffer[*buffer_length..*buffer_length+n].copy_from_slice(&data[..n]);
+        *buffer_length += n;
+        data = &data[n..];
+        if *buffer_length < B {
+            return;
+        }
+        compress(buffer);
+        *buffer_length = 0;
+    }
+
+    let mut blocks = data.chunks_exact(B);
+    for block in &mut blocks {
+        compress(block.try_into().unwrap());
+    }
+    let rest = blocks.remainder();
+    buffer[..rest.len()].copy_from_slice(rest);
+    *buffer_length = rest.len();
+}
+
+// Appends the 0x80 byte, zeros and the big-endian bit length (FIPS 180-4 section 5.1),
+// spilling into one more block when the length does not fit after the message
+fn pad<const B: usize>(buffer: &mut [u8; B], buffer_length: usize, bit_length: &[u8], mut compress: impl FnMut(&[u8; B])) {
+    buffer[buffer_length] = 0x80;
+    buffer[buffer_length+1..].fill(0);
+    if buffer_length + 1 > B - bit_length.len() {
+        compress(buffer);
+        buffer.fill(0);
+    }
+    buffer[B - bit_length.len()..].copy_from_slice(bit_length);
+    compress(buffer);
+}
+
+// SHA-256 compression function (FIPS 180-4 section 6.2.2)
+fn compress256(state: &mut [u32; 8], block: &[u8; 64]) {
+    let mut w: [u32; 64] = [0; 64];
+    for t in 0..16 {
+        w[t] = u32::from_be_bytes(block[4*t..4*t+4].try_into().unwrap());
+    }
+    for t in 16..64 {
+        let s0 = w[t-15].rotate_right(7) ^ w[t-15].rotate_right(18) ^ (w[t-15] >> 3);
+        let s1 = w[t-2].rotate_right(17) ^ w[t-2].rotate_right(19) ^ (w[t-2] >> 10);
+        w[t] = w[t-16].wrapping_add(s0).wrapping_add(w[t-7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..64 {
+        let ch = (e & f) ^ (!e & g);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let big_s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let big_s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let t1 = h.wrapping_add(big_s1).wrapping_add(ch).wrapping_add(K256[t]).wrapping_add(w[t]);
+        let t2 = big_s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+// SHA-512 compression function (FIPS 180-4 section 6.4.2)
+fn compress512(state: &mut [u64; 8], block: &[u8; 128]) {
+    let mut w: [u64; 80] = [0; 80];
+    for t in 0..16 {
+        w[t] = u64::from_be_bytes(block[8*t..8*t+8].try_into().unwrap());
+    }
+    for t in 16..80 {
+        let s0 = w[t-15].rotate_right(1) ^ w[t-15].rotate_right(8) ^ (w[t-15] >> 7);
+        let s1 = w[t-2].rotate_right(19) ^ w[t-2].rotate_right(61) ^ (w[t-2] >> 6);
+        w[t] = w[t-16].wrapping_add(s0).wrapping_add(w[t-7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..80 {
+        let ch = (e & f) ^ (!e & g);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let big_s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let big_s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let t1 = h.wrapping_add(big_s1).wrapping_add(ch).wrapping_add(K512[t]).wrapping_add(w[t]);
+        let t2 = big_s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+/// Description:
+/// Computes the SHA-224 digest of a whole message.
+///
+/// Arguments:
+/// message - (I,REQ) - The message to hash
+///
+/// Returns:
+/// The 28-byte digest
+#[allow(non_snake_case)]
+pub fn SHA224(message: &[u8]) -> [u8; 28] {
+    Sha224::digest(message)
+}
+
+/// Description:
+/// Computes the SHA-256 digest of a whole message.
+///
+/// Arguments:
+/// message - (I,REQ) - The message to hash
+///
+/// Returns:
+/// The 32-byte digest
+#[allow(non_snake_case)]
+pub fn SHA256(message: &[u8]) -> [u8; 32] {
+    Sha256::digest(message)
+}
+
+/// Description:
+/// Computes the SHA-384 digest of a whole message.
+///
+/// Arguments:
+/// message - (I,REQ) - The message to hash
+///
+/// Returns:
+/// The 48-byte digest
+#[allow(non_snake_case)]
+pub fn SHA384(message: &[u8]) -> [u8; 48] {
+    Sha384::digest(message)
+}
+
+/// Description:
+/// Computes the SHA-512 digest of a whole message.
+///
+/// Arguments:
+/// message - (I,REQ) - The message to hash
+///
+/// Returns:
+/// The 64-byte digest
+#[allow(non_snake_case)]
+pub fn SHA512(message: &[u8]) -> [u8; 64] {
+    Sha512::digest(message)
+}
+
+/// Description:
+/// Computes the SHA-512/256 digest of a whole message.
+///
+/// Arguments:
+/// message - (I,REQ) - The message to hash
+///
+/// Returns:
+/// The 32-byte digest
+#[allow(non_snake_case)]
+pub fn SHA512_256(message: &[u8]) -> [u8; 32] {
+    Sha512_256::digest(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC: &[u8] = b"abc";
+    // FIPS 180-4 two-block examples for the 64-byte and 128-byte block sizes
+    const MESSAGE_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const MESSAGE_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    // The one-shot digest, and the same digest streamed in two pieces at every split point
+    // and one byte at a time
+    fn check<D: Digest + Clone>(message: &[u8], expected: &[u8]) {
+        assert_eq!(D::digest(message).as_ref(), expected);
+
+        for split in 0..=message.len() {
+            let mut hash = D::new();
+            hash.update(&message[..split]);
+            hash.update(&message[split..]);
+            assert_eq!(hash.finalize().as_ref(), expected);
+        }
+
+        let mut hash = D::new();
+        for byte in message {
+            hash.update(std::slice::from_ref(byte));
+        }
+        assert_eq!(hash.clone().finalize().as_ref(), expected);
+
+        hash.reset();
+        hash.update(message);
+        assert_eq!(hash.finalize().as_ref(), expected);
+    }
+
+    // SHAVS Monte Carlo test: each checkpoint chains 1000 digests of the previous three
+    fn monte_carlo<D: Digest>(seed: &[u8]) -> Vec<u8> {
+        let mut digests = [seed.to_vec(), seed.to_vec(), seed.to_vec()];
+        for _ in 0..1000 {
+            let mut hash = D::new();
+            for digest in &digests {
+                hash.update(digest);
+            }
+            digests.rotate_left(1);
+            digests[2] = hash.finalize().as_ref().to_vec();
+        }
+        digests[2].clone()
+    }
+
+    #[test]
+    fn sha224_test() {
+        let actual: [u8; 28] = [0x23, 0x09, 0x7d, 0x22, 0x34, 0x05, 0xd8, 0x22, 0x86, 0x42, 0xa4, 0x77, 0xbd, 0xa2, 0x55, 0xb3, 0x2a, 0xad, 0xbc, 0xe4, 0xbd, 0xa0, 0xb3, 0xf7, 0xe3, 0x6c, 0x9d, 0xa7];
+        check::<Sha224>(ABC, &actual);
+        assert_eq!(SHA224(ABC), actual);
+
+        let actual: [u8; 28] = [0x75, 0x38, 0x8b, 0x16, 0x51, 0x27, 0x76, 0xcc, 0x5d, 0xba, 0x5d, 0xa1, 0xfd, 0x89, 0x01, 0x50, 0xb0, 0xc6, 0x45, 0x5c, 0xb4, 0xf5, 0x8b, 0x19, 0x52, 0x52, 0x25, 0x25];
+        check::<Sha224>(MESSAGE_448, &actual);
+
+        // SHAVS SHA224ShortMsg, Len = 0 and Len = 8
+        let actual: [u8; 28] = [0xd1, 0x4a, 0x02, 0x8c, 0x2a, 0x3a, 0x2b, 0xc9, 0x47, 0x61, 0x02, 0xbb, 0x28, 0x82, 0x34, 0xc4, 0x15, 0xa2, 0xb0, 0x1f, 0x82, 0x8e, 0xa6, 0x2a, 0xc5, 0xb3, 0xe4, 0x2f];
+        check::<Sha224>(&[], &actual);
+        let actual: [u8; 28] = [0x3c, 0xd3, 0x69, 0x21, 0xdf, 0x5d, 0x69, 0x63, 0xe7, 0x37, 0x39, 0xcf, 0x4d, 0x20, 0x21, 0x1e, 0x2d, 0x88, 0x77, 0xc1, 0x9c, 0xff, 0x08, 0x7a, 0xde, 0x9d, 0x0e, 0x3a];
+        check::<Sha224>(&[0x84], &actual);
+    }
+
+    #[test]
+    fn sha256_test() {
+        let actual: [u8; 32] = [0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad];
+        check::<Sha256>(ABC, &actual);
+        assert_eq!(SHA256(ABC), actual);
+
+        let actual: [u8; 32] = [0x24, 0x8d, 0x6a, 0x61, 0xd2, 0x06, 0x38, 0xb8, 0xe5, 0xc0, 0x26, 0x93, 0x0c, 0x3e, 0x60, 0x39, 0xa3, 0x3c, 0xe4, 0x59, 0x64, 0xff, 0x21, 0x67, 0xf6, 0xec, 0xed, 0xd4, 0x19, 0xdb, 0x06, 0xc1];
+        check::<Sha256>(MESSAGE_448, &actual);
+
+        // SHAVS SHA256ShortMsg, Len = 0 and Len = 8
+        let actual: [u8; 32] = [0xe3, 0xb0, 0xc4, 0x42, 0x98, 0xfc, 0x1c, 0x14, 0x9a, 0xfb, 0xf4, 0xc8, 0x99, 0x6f, 0xb9, 0x24, 0x27, 0xae, 0x41, 0xe4, 0x64, 0x9b, 0x93, 0x4c, 0xa4, 0x95, 0x99, 0x1b, 0x78, 0x52, 0xb8, 0x55];
+        check::<Sha256>(&[], &actual);
+        let actual: [u8; 32] = [0x28, 0x96, 0x9c, 0xdf, 0xa7, 0x4a, 0x12, 0xc8, 0x2f, 0x3b, 0xad, 0x96, 0x0b, 0x0b, 0x00, 0x0a, 0xca, 0x2a, 0xc3, 0x29, 0xde, 0xea, 0x5c, 0x23, 0x28, 0xeb, 0xc6, 0xf2, 0xba, 0x98, 0x02, 0xc1];
+        check::<Sha256>(&[0xd3], &actual);
+    }
+
+    #[test]
+    fn sha384_test() {
+        let actual: [u8; 48] = [0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6, 0x50, 0x07, 0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a, 0x43, 0xff, 0x5b, 0xed, 0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba, 0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7];
+        check::<Sha384>(ABC, &actual);
+        assert_eq!(SHA384(ABC), actual);
+
+        let actual: [u8; 48] = [0x09, 0x33, 0x0c, 0x33, 0xf7, 0x11, 0x47, 0xe8, 0x3d, 0x19, 0x2f, 0xc7, 0x82, 0xcd, 0x1b, 0x47, 0x53, 0x11, 0x1b, 0x17, 0x3b, 0x3b, 0x05, 0xd2, 0x2f, 0xa0, 0x80, 0x86, 0xe3, 0xb0, 0xf7, 0x12, 0xfc, 0xc7, 0xc7, 0x1a, 0x55, 0x7e, 0x2d, 0xb9, 0x66, 0xc3, 0xe9, 0xfa, 0x91, 0x74, 0x60, 0x39];
+        check::<Sha384>(MESSAGE_896, &actual);
+
+        // SHAVS SHA384ShortMsg, Len = 0 and Len = 8
+        let actual: [u8; 48] = [0x38, 0xb0, 0x60, 0xa7, 0x51, 0xac, 0x96, 0x38, 0x4c, 0xd9, 0x32, 0x7e, 0xb1, 0xb1, 0xe3, 0x6a, 0x21, 0xfd, 0xb7, 0x11, 0x14, 0xbe, 0x07, 0x43, 0x4c, 0x0c, 0xc7, 0xbf, 0x63, 0xf6, 0xe1, 0xda, 0x27, 0x4e, 0xde, 0xbf, 0xe7, 0x6f, 0x65, 0xfb, 0xd5, 0x1a, 0xd2, 0xf1, 0x48, 0x98, 0xb9, 0x5b];
+        check::<Sha384>(&[], &actual);
+        let actual: [u8; 48] = [0xb5, 0x2b, 0x72, 0xda, 0x75, 0xd0, 0x66, 0x63, 0x79, 0xe2, 0x0f, 0x9b, 0x4a, 0x79, 0xc3, 0x3a, 0x32, 0x9a, 0x01, 0xf0, 0x6a, 0x2f, 0xb7, 0x86, 0x5c, 0x90, 0x62, 0xa2, 0x8c, 0x1d, 0xe8, 0x60, 0xba, 0x43, 0x2e, 0xdf, 0xd8, 0x6b, 0x4c, 0xb1, 0xcb, 0x8a, 0x75, 0xb4, 0x60, 0x76, 0xe3, 0xb1];
+        check::<Sha384>(&[0xc5], &actual);
+    }
+
+    #[test]
+    fn sha512_test() {
+        let actual: [u8; 64] = [0xdd, 0xaf, 0x35, 0xa1, 0x93, 0x61, 0x7a, 0xba, 0xcc, 0x41, 0x73, 0x49, 0xae, 0x20, 0x41, 0x31, 0x12, 0xe6, 0xfa, 0x4e, 0x89, 0xa9, 0x7e, 0xa2, 0x0a, 0x9e, 0xee, 0xe6, 0x4b, 0x55, 0xd3, 0x9a, 0x21, 0x92, 0x99, 0x2a, 0x27, 0x4f, 0xc1, 0xa8, 0x36, 0xba, 0x3c, 0x23, 0xa3, 0xfe, 0xeb, 0xbd, 0x45, 0x4d, 0x44, 0x23, 0x64, 0x3c, 0xe8, 0x0e, 0x2a, 0x9a, 0xc9, 0x4f, 0xa5, 0x4c, 0xa4, 0x9f];
+        check::<Sha512>(ABC, &actual);
+        assert_eq!(SHA512(ABC), actual);
+
+        let actual: [u8; 64] = [0x8e, 0x95, 0x9b, 0x75, 0xda, 0xe3, 0x13, 0xda, 0x8c, 0xf4, 0xf7, 0x28, 0x14, 0xfc, 0x14, 0x3f, 0x8f, 0x77, 0x79, 0xc6, 0xeb, 0x9f, 0x7f, 0xa1, 0x72, 0x99, 0xae, 0xad, 0xb6, 0x88, 0x90, 0x18, 0x50, 0x1d, 0x28, 0x9e, 0x49, 0x00, 0xf7, 0xe4, 0x33, 0x1b, 0x99, 0xde, 0xc4, 0xb5, 0x43, 0x3a, 0xc7, 0xd3, 0x29, 0xee, 0xb6, 0xdd, 0x26, 0x54, 0x5e, 0x96, 0xe5, 0x5b, 0x87, 0x4b, 0xe9, 0x09];
+        check::<Sha512>(MESSAGE_896, &actual);
+
+        // SHAVS SHA512ShortMsg, Len = 0 and Len = 8
+        let actual: [u8; 64] = [0xcf, 0x83, 0xe1, 0x35, 0x7e, 0xef, 0xb8, 0xbd, 0xf1, 0x54, 0x28, 0x50, 0xd6, 0x6d, 0x80, 0x07, 0xd6, 0x20, 0xe4, 0x05, 0x0b, 0x57, 0x15, 0xdc, 0x83, 0xf4, 0xa9, 0x21, 0xd3, 0x6c, 0xe9, 0xce, 0x47, 0xd0, 0xd1, 0x3c, 0x5d, 0x85, 0xf2, 0xb0, 0xff, 0x83, 0x18, 0xd2, 0x87, 0x7e, 0xec, 0x2f, 0x63, 0xb9, 0x31, 0xbd, 0x47, 0x41, 0x7a, 0x81, 0xa5, 0x38, 0x32, 0x7a, 0xf9, 0x27, 0xda, 0x3e];
+        check::<Sha512>(&[], &actual);
+        let actual: [u8; 64] = [0x38, 0x31, 0xa6, 0xa6, 0x15, 0x5e, 0x50, 0x9d, 0xee, 0x59, 0xa7, 0xf4, 0x51, 0xeb, 0x35, 0x32, 0x4d, 0x8f, 0x8f, 0x2d, 0xf6, 0xe3, 0x70, 0x88, 0x94, 0x74, 0x0f, 0x98, 0xfd, 0xee, 0x23, 0x88, 0x9f, 0x4d, 0xe5, 0xad, 0xb0, 0xc5, 0x01, 0x0d, 0xfb, 0x55, 0x5c, 0xda, 0x77, 0xc8, 0xab, 0x5d, 0xc9, 0x02, 0x09, 0x4c, 0x52, 0xde, 0x32, 0x78, 0xf3, 0x5a, 0x75, 0xeb, 0xc2, 0x5f, 0x09, 0x3a];
+        check::<Sha512>(&[0x21], &actual);
+    }
+
+    #[test]
+    fn sha512_256_test() {
+        let actual: [u8; 32] = [0x53, 0x04, 0x8e, 0x26, 0x81, 0x94, 0x1e, 0xf9, 0x9b, 0x2e, 0x29, 0xb7, 0x6b, 0x4c, 0x7d, 0xab, 0xe4, 0xc2, 0xd0, 0xc6, 0x34, 0xfc, 0x6d, 0x46, 0xe0, 0xe2, 0xf1, 0x31, 0x07, 0xe7, 0xaf, 0x23];
+        check::<Sha512_256>(ABC, &actual);
+        assert_eq!(SHA512_256(ABC), actual);
+
+        let actual: [u8; 32] = [0x39, 0x28, 0xe1, 0x84, 0xfb, 0x86, 0x90, 0xf8, 0x40, 0xda, 0x39, 0x88, 0x12, 0x1d, 0x31, 0xbe, 0x65, 0xcb, 0x9d, 0x3e, 0xf8, 0x3e, 0xe6, 0x14, 0x6f, 0xea, 0xc8, 0x61, 0xe1, 0x9b, 0x56, 0x3a];
+        check::<Sha512_256>(MESSAGE_896, &actual);
+
+        let actual: [u8; 32] = [0xc6, 0x72, 0xb8, 0xd1, 0xef, 0x56, 0xed, 0x28, 0xab, 0x87, 0xc3, 0x62, 0x2c, 0x51, 0x14, 0x06, 0x9b, 0xdd, 0x3a, 0xd7, 0xb8, 0xf9, 0x73, 0x74, 0x98, 0xd0, 0xc0, 0x1e, 0xce, 0xf0, 0x96, 0x7a];
+        check::<Sha512_256>(&[], &actual);
+    }
+
+    #[test]
+    fn million_a_test() {
+        // One million repetitions of "a", absorbed in pieces that straddle block boundaries
+        fn million_a<D: Digest>() -> D::Output {
+            let mut hash = D::new();
+            let piece = [b'a'; 1000];
+            for _ in 0..1000 {
+                hash.update(&piece);
+            }
+            hash.finalize()
+        }
+
+        let actual: [u8; 28] = [0x20, 0x79, 0x46, 0x55, 0x98, 0x0c, 0x91, 0xd8, 0xbb, 0xb4, 0xc1, 0xea, 0x97, 0x61, 0x8a, 0x4b, 0xf0, 0x3f, 0x42, 0x58, 0x19, 0x48, 0xb2, 0xee, 0x4e, 0xe7, 0xad, 0x67];
+        assert_eq!(million_a::<Sha224>(), actual);
+        let actual: [u8; 32] = [0xcd, 0xc7, 0x6e, 0x5c, 0x99, 0x14, 0xfb, 0x92, 0x81, 0xa1, 0xc7, 0xe2, 0x84, 0xd7, 0x3e, 0x67, 0xf1, 0x80, 0x9a, 0x48, 0xa4, 0x97, 0x20, 0x0e, 0x04, 0x6d, 0x39, 0xcc, 0xc7, 0x11, 0x2c, 0xd0];
+        assert_eq!(million_a::<Sha256>(), actual);
+        let actual: [u8; 48] = [0x9d, 0x0e, 0x18, 0x09, 0x71, 0x64, 0x74, 0xcb, 0x08, 0x6e, 0x83, 0x4e, 0x31, 0x0a, 0x4a, 0x1c, 0xed, 0x14, 0x9e, 0x9c, 0x00, 0xf2, 0x48, 0x52, 0x79, 0x72, 0xce, 0xc5, 0x70, 0x4c, 0x2a, 0x5b, 0x07, 0xb8, 0xb3, 0xdc, 0x38, 0xec, 0xc4, 0xeb, 0xae, 0x97, 0xdd, 0xd8, 0x7f, 0x3d, 0x89, 0x85];
+        assert_eq!(million_a::<Sha384>(), actual);
+        let actual: [u8; 64] = [0xe7, 0x18, 0x48, 0x3d, 0x0c, 0xe7, 0x69, 0x64, 0x4e, 0x2e, 0x42, 0xc7, 0xbc, 0x15, 0xb4, 0x63, 0x8e, 0x1f, 0x98, 0xb1, 0x3b, 0x20, 0x44, 0x28, 0x56, 0x32, 0xa8, 0x03, 0xaf, 0xa9, 0x73, 0xeb, 0xde, 0x0f, 0xf2, 0x44, 0x87, 0x7e, 0xa6, 0x0a, 0x4c, 0xb0, 0x43, 0x2c, 0xe5, 0x77, 0xc3, 0x1b, 0xeb, 0x00, 0x9c, 0x5c, 0x2c, 0x49, 0xaa, 0x2e, 0x4e, 0xad, 0xb2, 0x17, 0xad, 0x8c, 0xc0, 0x9b];
+        assert_eq!(million_a::<Sha512>(), actual);
+        let actual: [u8; 32] = [0x9a, 0x59, 0xa0, 0x52, 0x93, 0x01, 0x87, 0xa9, 0x70, 0x38, 0xca, 0xe6, 0x92, 0xf3, 0x07, 0x08, 0xaa, 0x64, 0x91, 0x92, 0x3e, 0xf5, 0x19, 0x43, 0x94, 0xdc, 0x68, 0xd5, 0x6c, 0x74, 0xfb, 0x21];
+        assert_eq!(million_a::<Sha512_256>(), actual);
+    }
+
+    #[test]
+    fn monte_carlo_test() {
+        // SHAVS SHA256Monte and SHA512Monte, COUNT = 0
+        let seed: [u8; 32] = [0x6d, 0x1e, 0x72, 0xad, 0x03, 0xdd, 0xeb, 0x5d, 0xe8, 0x91, 0xe5, 0x72, 0xe2, 0x39, 0x6f, 0x8d, 0xa0, 0x15, 0xd8, 0x99, 0xef, 0x0e, 0x79, 0x50, 0x31, 0x52, 0xd6, 0x01, 0x0a, 0x3f, 0xe6, 0x91];
+        let actual: [u8; 32] = [0xe9, 0x3c, 0x33, 0x0a, 0xe5, 0x44, 0x77, 0x38, 0xc8, 0xaa, 0x85, 0xd7, 0x1a, 0x6c, 0x80, 0xf2, 0xa5, 0x83, 0x81, 0xd0, 0x58, 0x72, 0xd2, 0x6b, 0xdd, 0x39, 0xf1, 0xfc, 0xd4, 0xf2, 0xb7, 0x88];
+        assert_eq!(monte_carlo::<Sha256>(&seed), actual);
+
+        let seed: [u8; 64] = [0x5c, 0x33, 0x7d, 0xe5, 0xca, 0xf3, 0x5d, 0x18, 0xed, 0x90, 0xb5, 0xcd, 0xdf, 0xce, 0x00, 0x1c, 0xa1, 0xb8, 0xee, 0x86, 0x02, 0xf3, 0x67, 0xe7, 0xc2, 0x4c, 0xcc, 0xa6, 0xf8, 0x93, 0x80, 0x2f, 0xb1, 0xac, 0xa7, 0xa3, 0xda, 0xe3, 0x2d, 0xcd, 0x60, 0x80, 0x0a, 0x59, 0x95, 0x9b, 0xc5, 0x40, 0xd6, 0x32, 0x37, 0x87, 0x6b, 0x79, 0x92, 0x29, 0xae, 0x71, 0xa2, 0x52, 0x6f, 0xbc, 0x52, 0xcd];
+        let actual: [u8; 64] = [0xad, 0xa6, 0x9a, 0xdd, 0x00, 0x71, 0xb7, 0x94, 0x46, 0x3c, 0x88, 0x06, 0xa1, 0x77, 0x32, 0x67, 0x35, 0xfa, 0x62, 0x4b, 0x68, 0xab, 0x7b, 0xca, 0xb2, 0x38, 0x8b, 0x92, 0x76, 0xc0, 0x36, 0xe4, 0xea, 0xaf, 0xf8, 0x73, 0x33, 0xe8, 0x3c, 0x81, 0xc0, 0xbc, 0xa0, 0x35, 0x9d, 0x4a, 0xee, 0xbc, 0xbc, 0xfd, 0x31, 0x4c, 0x06, 0x30, 0xe0, 0xc2, 0xaf, 0x68, 0xc1, 0xfb, 0x19, 0xcc, 0x47, 0x0e];
+        assert_eq!(monte_carlo::<Sha512>(&seed), actual);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,290 @@
+use std::fmt;
+
+use rand::prelude::*;
+
+use crate::error::CryptoError;
+use crate::util::zeroize;
+
+/// Longest key in bytes, AES-256
+const MAX_KEY_BYTES: usize = 32;
+
+/// Description:
+/// An AES key of NK big-endian words. The words are zeroed when the key is dropped, Debug
+/// never prints them, and comparing two keys takes the same time wherever they differ.
+pub struct SecretKey<const NK: usize> {
+    words: [u32; NK],
+}
+
+pub type SecretKey128 = SecretKey<4>;
+pub type SecretKey192 = SecretKey<6>;
+pub type SecretKey256 = SecretKey<8>;
+
+impl<const NK: usize> SecretKey<NK> {
+    /// Description:
+    /// Takes ownership of key words. The caller's copy is not cleared.
+    ///
+    /// Arguments:
+    /// words - (I,REQ) - The key as 4, 6 or 8 big-endian words
+    ///
+    /// Returns:
+    /// The key
+    pub fn new(words: [u32; NK]) -> Self {
+        const { assert!(NK == 4 || NK == 6 || NK == 8, "AES keys are 4, 6 or 8 words long") };
+
+        SecretKey { words }
+    }
+
+    /// Description:
+    /// Generates a key from the thread-local cryptographically secure generator.
+    ///
+    /// Returns:
+    /// A random key
+    pub fn random() -> Self {
+        let mut rng = rand::rng();
+
+        let mut key = SecretKey::new([0; NK]);
+        for word in key.words.iter_mut() {
+            *word = rng.random::<u32>();
+        }
+
+        key
+    }
+
+    /// Description:
+    /// Reads a key from its bytes, in the order test vectors and key files list them.
+    ///
+    /// Arguments:
+    /// bytes - (I,REQ) - Exactly 4 * NK bytes: 16, 24 or 32
+    ///
+    /// Returns:
+    /// The key, or InvalidKey for any other length
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != 4*NK {
+            return Err(CryptoError::InvalidKey);
+        }
+
+        Ok(SecretKey::new(std::array::from_fn(|i| u32::from_be_bytes(bytes[4*i..4*i+4].try_into().unwrap()))))
+    }
+
+    /// Description:
+    /// Reads a key from hexadecimal, in either case, ignoring surrounding whitespace.
+    ///
+    /// Arguments:
+    /// text - (I,REQ) - 8 * NK hex digits
+    ///
+    /// Returns:
+    /// The key, or InvalidKey for a wrong length or a character that is not a hex digit
+    pub fn from_hex(text: &str) -> Result<Self, CryptoError> {
+        let mut bytes: [u8; MAX_KEY_BYTES] = [0; MAX_KEY_BYTES];
+        let key = decode_hex(text.trim(), &mut bytes[..4*NK]).and_then(|_| SecretKey::from_bytes(&bytes[..4*NK]));
+        zeroize(&mut bytes);
+
+        key
+    }
+
+    /// Description:
+    /// Reads a key from standard base64 (RFC 4648 section 4), with or without padding,
+    /// ignoring surrounding whitespace.
+    ///
+    /// Arguments:
+    /// text - (I,REQ) - The base64 encoding of 4 * NK bytes
+    ///
+    /// Returns:
+    /// The key, or InvalidKey for a wrong length or malformed base64
+    pub fn from_base64(text: &str) -> Result<Self, CryptoError> {
+        let mut bytes: [u8; MAX_KEY_BYTES] = [0; MAX_KEY_BYTES];
+        let key = decode_base64(text.trim(), &mut bytes).and_then(|length| SecretKey::from_bytes(&bytes[..length]));
+        zeroize(&mut bytes);
+
+        key
+    }
+
+    /// Description:
+    /// Borrows the key words, for example to hand them to a key schedule.
+    ///
+    /// Returns:
+    /// The key as big-endian words
+    pub fn expose(&self) -> &[u32; NK] {
+        &self.words
+    }
+}
+
+impl<const NK: usize> Clone for SecretKey<NK> {
+    fn clone(&self) -> Self {
+        SecretKey { words: self.words }
+    }
+}
+
+impl<const NK: usize> fmt::Debug for SecretKey<NK> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey<{}>([REDACTED])", NK)
+    }
+}
+
+impl<const NK: usize> PartialEq for SecretKey<NK> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut diff: u32 = 0;
+        for i in 0..NK {
+            diff |= self.words[i] ^ other.words[i];
+        }
+
+        std::hint::black_box(diff) == 0
+    }
+}
+
+impl<const NK: usize> Eq for SecretKey<NK> {}
+
+impl<const NK: usize> Drop for SecretKey<NK> {
+    fn drop(&mut self) {
+        zeroize(&mut self.words);
+    }
+}
+
+// Fills output from exactly 2 * output.len() hex digits
+fn decode_hex(text: &str, output: &mut [u8]) -> Result<(), CryptoError> {
+    let text = text.as_bytes();
+    if text.len() != 2*output.len() {
+        return Err(CryptoError::InvalidKey);
+    }
+
+    for (byte, digits) in output.iter_mut().zip(text.chunks_exact(2)) {
+        *byte = hex_value(digits[0])? << 4 | hex_value(digits[1])?;
+    }
+
+    Ok(())
+}
+
+fn hex_value(digit: u8) -> Result<u8, CryptoError> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(CryptoError::InvalidKey),
+    }
+}
+
+// Decodes into output and returns the number of bytes written. Leftover bits must be zero
+// so that every key has exactly one accepted encoding apart from the optional padding.
+fn decode_base64(text: &str, output: &mut [u8; MAX_KEY_BYTES]) -> Result<usize, CryptoError> {
+    let unpadded = text.trim_end_matches('=');
+    let padding = text.len() - unpadded.len();
+    if padding > 2 || (padding > 0 && !text.len().is_multiple_of(4)) || unpadded.len() % 4 == 1 {
+        return Err(CryptoError::InvalidKey);
+    }
+
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut length = 0;
+    for character in unpadded.bytes() {
+        bits = bits << 6 | base64_value(character)?;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            if length == MAX_KEY_BYTES {
+                return Err(CryptoError::InvalidKey);
+            }
+            output[length] = (bits >> bit_count) as u8;
+            length += 1;
+        }
+        bits &= (1 << bit_count) - 1;
+    }
+    if bits != 0 {
+        return Err(CryptoError::InvalidKey);
+    }
+
+    Ok(length)
+}
+
+fn base64_value(character: u8) -> Result<u32, CryptoError> {
+    match character {
+        b'A'..=b'Z' => Ok((character - b'A') as u32),
+        b'a'..=b'z' => Ok((character - b'a') as u32 + 26),
+        b'0'..=b'9' => Ok((character - b'0') as u32 + 52),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(CryptoError::InvalidKey),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes,BlockCipher};
+    use crate::salt::get_salt_256;
+
+    const KEY: [u32; 8] = [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4];
+
+    #[test]
+    fn debug_test() {
+        let key = SecretKey256::new(KEY);
+        let res = format!("{:?}", key);
+        assert_eq!(res, "SecretKey<8>([REDACTED])");
+        assert!(!res.contains("603deb10") && !res.contains(&0x603deb10u32.to_string()));
+    }
+
+    #[test]
+    fn eq_test() {
+        let key = SecretKey256::new(KEY);
+        assert_eq!(key, key.clone());
+
+        let mut words = KEY;
+        words[7] ^= 1;
+        assert_ne!(key, SecretKey256::new(words));
+    }
+
+    #[test]
+    fn random_test() {
+        assert_ne!(SecretKey128::random(), SecretKey128::random());
+        assert_ne!(get_salt_256(), get_salt_256());
+    }
+
+    #[test]
+    fn with_key_test() {
+        let block: [u8; 16] = [0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a];
+        let actual: [u8; 16] = [0xf3, 0xee, 0xd1, 0xbd, 0xb5, 0xd2, 0xa0, 0x3c, 0x06, 0x4b, 0x5a, 0x7e, 0x3d, 0xb1, 0x81, 0xf8];
+        let key = SecretKey256::new(KEY);
+        assert_eq!(Aes::with_key(&key).encrypt_block(block), actual);
+        assert_eq!(Aes::new(*key.expose()).encrypt_block(block), actual);
+    }
+
+    #[test]
+    fn from_bytes_test() {
+        let bytes: [u8; 16] = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let key = SecretKey128::new([0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+        assert_eq!(SecretKey128::from_bytes(&bytes), Ok(key.clone()));
+        assert_eq!(SecretKey128::from_hex("2b7e151628aed2a6abf7158809cf4f3c"), Ok(key.clone()));
+        assert_eq!(SecretKey128::from_hex(" 2B7E151628AED2A6ABF7158809CF4F3C\n"), Ok(key.clone()));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWICc9PPA=="), Ok(key.clone()));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWICc9PPA"), Ok(key));
+
+        let key = SecretKey192::new([0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b]);
+        assert_eq!(SecretKey192::from_hex("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b"), Ok(key.clone()));
+        assert_eq!(SecretKey192::from_base64("jnOw99oOZFLIEPMrgJB55WL46tJSLGt7"), Ok(key));
+
+        assert_eq!(SecretKey256::from_hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"), Ok(SecretKey256::new(KEY)));
+    }
+
+    #[test]
+    fn from_bytes_error_test() {
+        assert_eq!(SecretKey128::from_bytes(&[0; 15]), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_bytes(&[0; 17]), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey192::from_bytes(&[0; 16]), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey256::from_bytes(&[0; 24]), Err(CryptoError::InvalidKey));
+
+        // Wrong length, odd length, non-hex digit, sign accepted by u8::from_str_radix
+        assert_eq!(SecretKey128::from_hex("2b7e151628aed2a6abf7158809cf4f"), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_hex("2b7e151628aed2a6abf7158809cf4f3"), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_hex("2b7e151628aed2a6abf7158809cf4f3g"), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_hex("+b7e151628aed2a6abf7158809cf4f3c"), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey256::from_hex("2b7e151628aed2a6abf7158809cf4f3c"), Err(CryptoError::InvalidKey));
+
+        // A 24-byte key, bad padding, a stray character, non-zero trailing bits, URL-safe alphabet
+        assert_eq!(SecretKey128::from_base64("jnOw99oOZFLIEPMrgJB55WL46tJSLGt7"), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWICc9PPA="), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWICc9PPA==="), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWI Cc9PPA=="), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWICc9PPB=="), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey128::from_base64("K34VFiiu0qar9xWICc9PPA_="), Err(CryptoError::InvalidKey));
+        assert_eq!(SecretKey256::from_base64(&"A".repeat(64)), Err(CryptoError::InvalidKey));
+    }
+}
\ No newline at end of file
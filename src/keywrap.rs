@@ -0,0 +1,319 @@
+use crate::cipher::BlockCipher;
+use crate::error::CryptoError;
+use crate::util::constant_time_eq;
+
+/// The default initial value of RFC 3394 section 2.2.3.1
+pub const DEFAULT_IV: [u8; 8] = [0xa6; 8];
+/// The constant half of the alternative initial value of RFC 5649 section 3
+pub const ALTERNATIVE_IV: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+/// Description:
+/// Wraps key data with AES Key Wrap (RFC 3394, SP 800-38F KW).
+///
+/// Arguments:
+/// cipher - (I,REQ) - The cipher keyed with the key-encryption key
+/// key_data - (I,REQ) - The key to wrap, a multiple of 8 bytes and at least 16 bytes long
+///
+/// Returns:
+/// The wrapped key, 8 bytes longer than key_data, or InvalidLength
+#[allow(non_snake_case)]
+pub fn KW_wrap<C: BlockCipher>(cipher: &C, key_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if key_data.len() < 16 || !key_data.len().is_multiple_of(8) {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    Ok(wrap(cipher, DEFAULT_IV, key_data))
+}
+
+/// Description:
+/// Unwraps key data wrapped with AES Key Wrap and checks the integrity check value.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The cipher keyed with the key-encryption key
+/// wrapped - (I,REQ) - The wrapped key, a multiple of 8 bytes and at least 24 bytes long
+///
+/// Returns:
+/// The key data, AuthenticationFailed if the integrity check value does not match, or InvalidLength
+#[allow(non_snake_case)]
+pub fn KW_unwrap<C: BlockCipher>(cipher: &C, wrapped: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let (a, mut key_data) = unwrap(cipher, wrapped);
+    if !constant_time_eq(&a, &DEFAULT_IV) {
+        key_data.fill(0);
+        return Err(CryptoError::AuthenticationFailed);
+    }
+
+    Ok(key_data)
+}
+
+/// Description:
+/// Wraps key data of any length with AES Key Wrap with Padding (RFC 5649, SP 800-38F KWP).
+/// The length goes in the initial value and the key data is zero padded to a multiple of 8 bytes.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The cipher keyed with the key-encryption key
+/// key_data - (I,REQ) - The key to wrap, 1 to 2^32 - 1 bytes
+///
+/// Returns:
+/// The wrapped key, or InvalidLength
+#[allow(non_snake_case)]
+pub fn KWP_wrap<C: BlockCipher>(cipher: &C, key_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if key_data.is_empty() || key_data.len() as u64 > u32::MAX as u64 {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let mut iv: [u8; 8] = [0; 8];
+    iv[..4].copy_from_slice(&ALTERNATIVE_IV);
+    iv[4..].copy_from_slice(&(key_data.len() as u32).to_be_bytes());
+
+    let mut padded = key_data.to_vec();
+    padded.resize(key_data.len().next_multiple_of(8), 0);
+
+    // A single padded block is encrypted together with the initial value as one AES block
+    let wrapped = if padded.len() == 8 {
+        let mut block: [u8; 16] = [0; 16];
+        block[..8].copy_from_slice(&iv);
+        block[8..].copy_from_slice(&padded);
+        cipher.encrypt_block(block).to_vec()
+    } else {
+        wrap(cipher, iv, &padded)
+    };
+    padded.fill(0);
+
+    Ok(wrapped)
+}
+
+/// Description:
+/// Unwraps key data wrapped with AES Key Wrap with Padding, checking the constant half of the
+/// initial value, the message length indicator and that the padding is zero.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The cipher keyed with the key-encryption key
+/// wrapped - (I,REQ) - The wrapped key, a multiple of 8 bytes and at least 16 bytes long
+///
+/// Returns:
+/// The key data, AuthenticationFailed if any check fails, or InvalidLength
+#[allow(non_snake_case)]
+pub fn KWP_unwrap<C: BlockCipher>(cipher: &C, wrapped: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if wrapped.len() < 16 || !wrapped.len().is_multiple_of(8) {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let (a, mut padded) = if wrapped.len() == 16 {
+        let block = cipher.decrypt_block(wrapped.try_into().unwrap());
+        (block[..8].try_into().unwrap(), block[8..].to_vec())
+    } else {
+        unwrap(cipher, wrapped)
+    };
+
+    // Every check is evaluated before deciding, so the failure does not reveal which one failed
+    let length = u32::from_be_bytes(a[4..].try_into().unwrap()) as usize;
+    let n = padded.len();
+    let length_valid = length <= n && n - length < 8;
+    let padding_zero = padded[length.min(n)..].iter().fold(0, |acc, byte| acc | byte) == 0;
+    if !(constant_time_eq(&a[..4], &ALTERNATIVE_IV) & length_valid & padding_zero) {
+        padded.fill(0);
+        return Err(CryptoError::AuthenticationFailed);
+    }
+    padded.truncate(length);
+
+    Ok(padded)
+}
+
+// The wrapping process W of RFC 3394 section 2.2.1 in its index-based form
+fn wrap<C: BlockCipher>(cipher: &C, iv: [u8; 8], key_data: &[u8]) -> Vec<u8> {
+    let n = key_data.len() / 8;
+    let mut a = iv;
+    let mut r: Vec<u8> = key_data.to_vec();
+
+    let mut block: [u8; 16] = [0; 16];
+    for j in 0..6 {
+        for i in 0..n {
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(&r[8*i..8*i+8]);
+            block = cipher.encrypt_block(block);
+
+            let t = (n*j + i + 1) as u64;
+            a = (u64::from_be_bytes(block[..8].try_into().unwrap()) ^ t).to_be_bytes();
+            r[8*i..8*i+8].copy_from_slice(&block[8..]);
+        }
+    }
+    block.fill(0);
+
+    let mut wrapped = a.to_vec();
+    wrapped.extend(&r);
+    r.fill(0);
+
+    wrapped
+}
+
+// The unwrapping process W^-1 of RFC 3394 section 2.2.2, returning A and the key data
+fn unwrap<C: BlockCipher>(cipher: &C, wrapped: &[u8]) -> ([u8; 8], Vec<u8>) {
+    let n = wrapped.len() / 8 - 1;
+    let mut a: [u8; 8] = wrapped[..8].try_into().unwrap();
+    let mut r: Vec<u8> = wrapped[8..].to_vec();
+
+    let mut block: [u8; 16] = [0; 16];
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = (n*j + i + 1) as u64;
+            block[..8].copy_from_slice(&(u64::from_be_bytes(a) ^ t).to_be_bytes());
+            block[8..].copy_from_slice(&r[8*i..8*i+8]);
+            block = cipher.decrypt_block(block);
+
+            a = block[..8].try_into().unwrap();
+            r[8*i..8*i+8].copy_from_slice(&block[8..]);
+        }
+    }
+    block.fill(0);
+
+    (a, r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes128,Aes192,Aes256};
+
+    const KEY_DATA: [u8; 32] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+    const KEK_128: [u32; 4] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f];
+    const KEK_192: [u32; 6] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f, 0x10111213, 0x14151617];
+    const KEK_256: [u32; 8] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f, 0x10111213, 0x14151617, 0x18191a1b, 0x1c1d1e1f];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn KW_test() {
+        // RFC 3394 section 4.1: 128 bits of key data with a 128-bit KEK
+        let cipher = Aes128::new(KEK_128);
+        let res = KW_wrap(&cipher, &KEY_DATA[..16]).unwrap();
+        let actual: [u8; 24] = [0x1f, 0xa6, 0x8b, 0x0a, 0x81, 0x12, 0xb4, 0x47, 0xae, 0xf3, 0x4b, 0xd8, 0xfb, 0x5a, 0x7b, 0x82, 0x9d, 0x3e, 0x86, 0x23, 0x71, 0xd2, 0xcf, 0xe5];
+        assert_eq!(res, actual);
+
+        let res = KW_unwrap(&cipher, &res).unwrap();
+        assert_eq!(res, &KEY_DATA[..16]);
+
+        // Section 4.2: 128 bits of key data with a 192-bit KEK
+        let cipher = Aes192::new(KEK_192);
+        let res = KW_wrap(&cipher, &KEY_DATA[..16]).unwrap();
+        let actual: [u8; 24] = [0x96, 0x77, 0x8b, 0x25, 0xae, 0x6c, 0xa4, 0x35, 0xf9, 0x2b, 0x5b, 0x97, 0xc0, 0x50, 0xae, 0xd2, 0x46, 0x8a, 0xb8, 0xa1, 0x7a, 0xd8, 0x4e, 0x5d];
+        assert_eq!(res, actual);
+
+        let res = KW_unwrap(&cipher, &res).unwrap();
+        assert_eq!(res, &KEY_DATA[..16]);
+
+        // Section 4.4: 192 bits of key data with a 192-bit KEK
+        let res = KW_wrap(&cipher, &KEY_DATA[..24]).unwrap();
+        let actual: [u8; 32] = [0x03, 0x1d, 0x33, 0x26, 0x4e, 0x15, 0xd3, 0x32, 0x68, 0xf2, 0x4e, 0xc2, 0x60, 0x74, 0x3e, 0xdc, 0xe1, 0xc6, 0xc7, 0xdd, 0xee, 0x72, 0x5a, 0x93, 0x6b, 0xa8, 0x14, 0x91, 0x5c, 0x67, 0x62, 0xd2];
+        assert_eq!(res, actual);
+
+        let res = KW_unwrap(&cipher, &res).unwrap();
+        assert_eq!(res, &KEY_DATA[..24]);
+
+        // Section 4.6: 256 bits of key data with a 256-bit KEK
+        let cipher = Aes256::new(KEK_256);
+        let res = KW_wrap(&cipher, &KEY_DATA).unwrap();
+        let actual: [u8; 40] = [0x28, 0xc9, 0xf4, 0x04, 0xc4, 0xb8, 0x10, 0xf4, 0xcb, 0xcc, 0xb3, 0x5c, 0xfb, 0x87, 0xf8, 0x26, 0x3f, 0x57, 0x86, 0xe2, 0xd8, 0x0e, 0xd3, 0x26, 0xcb, 0xc7, 0xf0, 0xe7, 0x1a, 0x99, 0xf4, 0x3b, 0xfb, 0x98, 0x8b, 0x9b, 0x7a, 0x02, 0xdd, 0x21];
+        assert_eq!(res, actual);
+
+        let res = KW_unwrap(&cipher, &res).unwrap();
+        assert_eq!(res, &KEY_DATA);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn KW_failure_test() {
+        let cipher = Aes128::new(KEK_128);
+        let wrapped = KW_wrap(&cipher, &KEY_DATA[..16]).unwrap();
+
+        let mut tampered = wrapped.clone();
+        tampered[0] ^= 1;
+        assert_eq!(KW_unwrap(&cipher, &tampered), Err(CryptoError::AuthenticationFailed));
+        let mut tampered = wrapped.clone();
+        tampered[23] ^= 1;
+        assert_eq!(KW_unwrap(&cipher, &tampered), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(KW_unwrap(&Aes128::new([0; 4]), &wrapped), Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(KW_wrap(&cipher, &KEY_DATA[..8]), Err(CryptoError::InvalidLength));
+        assert_eq!(KW_wrap(&cipher, &KEY_DATA[..20]), Err(CryptoError::InvalidLength));
+        assert_eq!(KW_unwrap(&cipher, &wrapped[..16]), Err(CryptoError::InvalidLength));
+        assert_eq!(KW_unwrap(&cipher, &wrapped[..23]), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn KWP_test() {
+        // RFC 5649 section 6
+        let cipher = Aes192::new([0x5840df6e, 0x29b02af1, 0xab493b70, 0x5bf16ea1, 0xae8338f4, 0xdcc176a8]);
+
+        let key_data: [u8; 20] = [0xc3, 0x7b, 0x7e, 0x64, 0x92, 0x58, 0x43, 0x40, 0xbe, 0xd1, 0x22, 0x07, 0x80, 0x89, 0x41, 0x15, 0x50, 0x68, 0xf7, 0x38];
+        let actual: [u8; 32] = [0x13, 0x8b, 0xde, 0xaa, 0x9b, 0x8f, 0xa7, 0xfc, 0x61, 0xf9, 0x77, 0x42, 0xe7, 0x22, 0x48, 0xee, 0x5a, 0xe6, 0xae, 0x53, 0x60, 0xd1, 0xae, 0x6a, 0x5f, 0x54, 0xf3, 0x73, 0xfa, 0x54, 0x3b, 0x6a];
+        let res = KWP_wrap(&cipher, &key_data).unwrap();
+        assert_eq!(res, actual);
+
+        let res = KWP_unwrap(&cipher, &res).unwrap();
+        assert_eq!(res, key_data);
+
+        let key_data: [u8; 7] = [0x46, 0x6f, 0x72, 0x50, 0x61, 0x73, 0x69];
+        let actual: [u8; 16] = [0xaf, 0xbe, 0xb0, 0xf0, 0x7d, 0xfb, 0xf5, 0x41, 0x92, 0x00, 0xf2, 0xcc, 0xb5, 0x0b, 0xb2, 0x4f];
+        let res = KWP_wrap(&cipher, &key_data).unwrap();
+        assert_eq!(res, actual);
+
+        let res = KWP_unwrap(&cipher, &res).unwrap();
+        assert_eq!(res, key_data);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn KWP_lengths_test() {
+        let cipher = Aes256::new(KEK_256);
+        for length in 1..=KEY_DATA.len() {
+            let wrapped = KWP_wrap(&cipher, &KEY_DATA[..length]).unwrap();
+            assert_eq!(wrapped.len(), length.next_multiple_of(8) + 8);
+            assert_eq!(KWP_unwrap(&cipher, &wrapped).unwrap(), &KEY_DATA[..length]);
+        }
+
+        assert_eq!(KWP_wrap(&cipher, &[]), Err(CryptoError::InvalidLength));
+        assert_eq!(KWP_unwrap(&cipher, &[0; 8]), Err(CryptoError::InvalidLength));
+        assert_eq!(KWP_unwrap(&cipher, &[0; 20]), Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn KWP_failure_test() {
+        let cipher = Aes128::new(KEK_128);
+
+        // A single block is decrypted directly rather than through the unwrap rounds
+        let mut wrapped = KWP_wrap(&cipher, &KEY_DATA[..7]).unwrap();
+        wrapped[15] ^= 1;
+        assert_eq!(KWP_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+
+        let mut wrapped = KWP_wrap(&cipher, &KEY_DATA[..20]).unwrap();
+        wrapped[0] ^= 1;
+        assert_eq!(KWP_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+
+        // KW output has the wrong initial value for KWP and the other way round
+        let wrapped = KW_wrap(&cipher, &KEY_DATA[..16]).unwrap();
+        assert_eq!(KWP_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+        let wrapped = KWP_wrap(&cipher, &KEY_DATA[..16]).unwrap();
+        assert_eq!(KW_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+
+        // A length indicator that disagrees with the padding is rejected
+        let mut iv: [u8; 8] = [0xa6, 0x59, 0x59, 0xa6, 0x00, 0x00, 0x00, 0x09];
+        let wrapped = wrap(&cipher, iv, &KEY_DATA[..24]);
+        assert_eq!(KWP_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+        iv[7] = 17;
+        let wrapped = wrap(&cipher, iv, &KEY_DATA[..24]);
+        assert_eq!(KWP_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+        let mut padded = KEY_DATA[..24].to_vec();
+        padded[23] = 0;
+        iv[7] = 23;
+        let wrapped = wrap(&cipher, iv, &padded);
+        assert_eq!(KWP_unwrap(&cipher, &wrapped).unwrap(), &KEY_DATA[..23]);
+        padded[23] = 1;
+        let wrapped = wrap(&cipher, iv, &padded);
+        assert_eq!(KWP_unwrap(&cipher, &wrapped), Err(CryptoError::AuthenticationFailed));
+    }
+}
\ No newline at end of file
@@ -1,5 +1,28 @@
+pub mod cbc;
+pub mod ccm;
 pub mod cipher;
+pub mod cmac;
+pub mod ctr;
+pub mod eax;
+pub mod error;
+pub mod gcm;
+pub mod gcm_siv;
+pub mod hash;
+pub mod key;
+pub mod keywrap;
+pub mod ocb;
 pub mod padding;
+pub mod pmac;
 pub mod salt;
+pub mod siv;
+pub mod stream;
+pub mod xts;
 mod util;
-mod math;
\ No newline at end of file
+#[cfg(target_arch = "x86_64")]
+mod aesni;
+mod math;
+#[cfg_attr(not(feature = "ttable"), allow(dead_code))]
+mod ttable;
+
+#[cfg(all(feature = "ttable", feature = "constant-time"))]
+compile_error!("the ttable feature indexes lookup tables with secret data and cannot be combined with constant-time");
\ No newline at end of file
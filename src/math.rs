@@ -1,7 +1,18 @@
+use crate::error::CryptoError;
+
+#[allow(clippy::unusual_byte_groupings)]
 pub const AES_IRREDUCIBLE_POLYNOMIAL: u16 = 0b_1_0001_1011;
+/// 1 + x + x^2 + x^7 in the reflected bit order used by GHASH
+pub const GCM_REDUCTION: u128 = 0xe1 << 120;
+/// x^128 = x^7 + x^2 + x + 1 with the most significant bit as the coefficient of x^127, as used by CMAC
+pub const CMAC_REDUCTION: u128 = 0x87;
+/// (x^128 + x^127 + x^126 + x^121 + 1) / x without its x^127 term, the POLYVAL reduction for one shift right
+pub const POLYVAL_REDUCTION: u128 = 1 << 127 | 1 << 126 | 1 << 125 | 1 << 120;
 
 /// Description:
 /// Multiplies two bytes assuming the bytes are elements of 2Z[x]/p(x) where p(x) is the irreducible polynomial for AES.
+/// Runs in constant time: the loop count is fixed and no branch depends on a or b.
+/// It is a const fn so lookup tables can be built from it at compile time.
 ///
 /// Arguments:
 /// a - (I,REQ) - byte
@@ -9,23 +20,42 @@ pub const AES_IRREDUCIBLE_POLYNOMIAL: u16 = 0b_1_0001_1011;
 ///
 /// Returns:
 /// a*b
-pub fn byte_multiplication(a: u8, b: u8) -> u8 {
-    let a = a as u16;
-    let b = b as u16;
-    let mut product = 0b_0;
-
-    // Do product in 2Z[x]
-    let mut digit1: u16 = 0b_1_0000_0000;
-    while digit1 != 0 {
-        let mut digit2: u16 = 0b_1_0000_0000;
-        while digit2 != 0 {
-            product ^= (a & digit1) * (b & digit2);
-            digit2 = digit2 >> 1;
-        }
-        digit1 = digit1 >> 1;
+pub const fn byte_multiplication(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut b = b;
+    let mut product: u8 = 0b_0;
+
+    // Shift and add, reducing by p(x) whenever a overflows x^7
+    let mut i = 0;
+    while i < 8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let overflow = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (overflow & AES_IRREDUCIBLE_POLYNOMIAL as u8);
+        b >>= 1;
+        i += 1;
     }
 
-    byte_remainder(product, AES_IRREDUCIBLE_POLYNOMIAL) as u8
+    product
+}
+
+/// Description:
+/// Finds the multiplicative inverse in constant time as a^254, mapping 0 to 0 like the AES S-box.
+///
+/// Arguments:
+/// a - (I,REQ) - The number to find the inverse of.
+///
+/// Returns:
+/// The inverse of a, or 0 if a is 0.
+#[allow(dead_code)]
+pub fn constant_time_byte_inverse(a: u8) -> u8 {
+    // 254 = 0b_1111_1110, one square and multiply per bit
+    let mut res = a;
+    for _ in 0..6 {
+        res = byte_multiplication(res, res);
+        res = byte_multiplication(res, a);
+    }
+
+    byte_multiplication(res, res)
 }
 
 /// Description:
@@ -37,27 +67,14 @@ pub fn byte_multiplication(a: u8, b: u8) -> u8 {
 /// b - (I,REQ) - divisor
 ///
 /// Returns:
-/// floor of a/b
+/// floor of a/b, or DivisionByZero if b is 0
 #[allow(dead_code)]
-pub fn byte_quotient(a: u16, b: u16) -> u16 {
-    assert!(b != 0, "Can't divide by 0");
-
-    let mut degree: u16 = 0;
-    while b >> degree+1 != 0 {
-        degree += 1;
-    }
-
-    let mut a = a;
-    let mut res = 0;
-    for i in (degree..16).rev() {
-        let digit = (a >> i) & 1; // Get ith bit
-        if digit == 1 {
-            res ^= digit << (i-degree);
-            a ^= b << (i-degree);
-        }
+pub fn byte_quotient(a: u16, b: u16) -> Result<u16, CryptoError> {
+    if b == 0 {
+        return Err(CryptoError::DivisionByZero);
     }
 
-    res
+    Ok(polynomial_division(a, b).0)
 }
 
 /// Description:
@@ -69,24 +86,45 @@ pub fn byte_quotient(a: u16, b: u16) -> u16 {
 /// b - (I,REQ) - divisor
 ///
 /// Returns:
-/// a modulo b
-pub fn byte_remainder(a: u16, b: u16) -> u16 {
-    assert!(b != 0, "Can't divide by 0");
+/// a modulo b, or DivisionByZero if b is 0
+#[allow(dead_code)]
+pub fn byte_remainder(a: u16, b: u16) -> Result<u16, CryptoError> {
+    if b == 0 {
+        return Err(CryptoError::DivisionByZero);
+    }
 
+    Ok(polynomial_division(a, b).1)
+}
+
+/// Description:
+/// Long division of polynomials over 2Z.
+///
+/// Arguments:
+/// a - (I,REQ) - dividend
+/// b - (I,REQ) - divisor
+///
+/// Constraints:
+/// b must be non-zero.
+///
+/// Returns:
+/// (q, r) with a = bq + r
+fn polynomial_division(a: u16, b: u16) -> (u16, u16) {
     let mut degree: u16 = 0;
-    while b >> degree+1 != 0 {
+    while b >> (degree+1) != 0 {
         degree += 1;
     }
 
     let mut a = a;
+    let mut q = 0;
     for i in (degree..16).rev() {
         let digit = (a >> i) & 1; // Get ith bit
         if digit == 1 {
+            q ^= digit << (i-degree);
             a ^= b << (i-degree);
         }
     }
 
-    a
+    (q, a)
 }
 
 /// Description:
@@ -119,14 +157,13 @@ pub fn byte_matrix_multiplication(A: &[[u8; 4]; 4], b: &[u8; 4]) -> [u8; 4] {
 /// Arguments:
 /// a - (I,REQ) - The number of find the inverse of.
 /// 
-/// Constraints:
-/// a must be non-zero.
-///
 /// Returns:
-/// The inverse of a.
+/// The inverse of a, or DivisionByZero if a is 0.
 #[allow(dead_code)]
-pub fn byte_inverse(a: u8) -> u8 {
-    assert!(a != 0, "0 does not have an inverse");
+pub fn byte_inverse(a: u8) -> Result<u8, CryptoError> {
+    if a == 0 {
+        return Err(CryptoError::DivisionByZero);
+    }
 
     let mut p = AES_IRREDUCIBLE_POLYNOMIAL;
     let mut a = a as u16;
@@ -134,11 +171,10 @@ pub fn byte_inverse(a: u8) -> u8 {
 
     // Extended euclidean algorithm
     while a != 0 {
-        let q = byte_quotient(p, a) as u8;
-        let r = byte_remainder(p, a) as u8;
+        let (q, r) = polynomial_division(p, a);
         p = a;
-        a = r as u16;
-        combinations.push(q);
+        a = r;
+        combinations.push(q as u8);
     }
 
     let mut u = 0;
@@ -150,9 +186,105 @@ pub fn byte_inverse(a: u8) -> u8 {
         u = temp;
     }
 
-    v
+    Ok(v)
+}
+
+
+/// Description:
+/// Multiplies two elements of GF(2^128) as defined for GHASH (SP 800-38D section 6.3).
+/// The bit order is reflected: the most significant bit of the u128 is the coefficient of x^0.
+/// Runs in constant time.
+///
+/// Arguments:
+/// x - (I,REQ) - block read as a big-endian u128
+/// y - (I,REQ) - block read as a big-endian u128
+///
+/// Returns:
+/// x*y
+pub fn gf128_multiplication(x: u128, y: u128) -> u128 {
+    let mut z: u128 = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        let bit = (x >> i) & 1;
+        z ^= v & 0u128.wrapping_sub(bit);
+        let carry = v & 1;
+        v = (v >> 1) ^ (GCM_REDUCTION & 0u128.wrapping_sub(carry));
+    }
+
+    z
+}
+
+/// Description:
+/// Multiplies by x in GF(2^128) with the bit order of CMAC (SP 800-38B), where the most
+/// significant bit of the u128 is the coefficient of x^127. Runs in constant time.
+///
+/// Arguments:
+/// x - (I,REQ) - block read as a big-endian u128
+///
+/// Returns:
+/// The input multiplied by x (dbl in RFC 5297)
+pub fn gf128_double(x: u128) -> u128 {
+    (x << 1) ^ (CMAC_REDUCTION & 0u128.wrapping_sub(x >> 127))
+}
+
+/// Description:
+/// The POLYVAL dot product of RFC 8452: x*y*x^-128 in GF(2^128) modulo x^128 + x^127 + x^126 + x^121 + 1.
+/// Each bit of x is added in and then the sum is divided by x, so after 128 bits the product
+/// carries the x^-128 factor. Runs in constant time.
+///
+/// Arguments:
+/// x - (I,REQ) - block read as a little-endian u128
+/// y - (I,REQ) - block read as a little-endian u128
+///
+/// Returns:
+/// dot(x, y)
+pub fn polyval_multiplication(x: u128, y: u128) -> u128 {
+    let mut z: u128 = 0;
+    for i in 0..128 {
+        let bit = (x >> i) & 1;
+        z ^= y & 0u128.wrapping_sub(bit);
+        let carry = z & 1;
+        z = (z >> 1) ^ (POLYVAL_REDUCTION & 0u128.wrapping_sub(carry));
+    }
+
+    z
 }
 
+/// Description:
+/// POLYVAL (RFC 8452 section 3) over several inputs, each zero padded to a whole block.
+///
+/// Arguments:
+/// h - (I,REQ) - The hash key read as a little-endian u128
+/// inputs - (I,REQ) - The byte strings to absorb, in order
+///
+/// Returns:
+/// The POLYVAL output as a little-endian u128
+pub fn polyval(h: u128, inputs: &[&[u8]]) -> u128 {
+    let mut s: u128 = 0;
+    for input in inputs {
+        for chunk in input.chunks(16) {
+            let mut block: [u8; 16] = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            s = polyval_multiplication(s ^ u128::from_le_bytes(block), h);
+        }
+    }
+
+    s
+}
+
+/// Description:
+/// Divides by x in GF(2^128) with the bit order of CMAC, the inverse of gf128_double.
+/// Runs in constant time.
+///
+/// Arguments:
+/// x - (I,REQ) - block read as a big-endian u128
+///
+/// Returns:
+/// The input divided by x (L(-1) in PMAC)
+pub fn gf128_halve(x: u128) -> u128 {
+    // Adding x^128 + x^7 + x^2 + x + 1 clears the constant term, then the shift divides by x
+    (x >> 1) ^ ((1 << 127 | CMAC_REDUCTION >> 1) & 0u128.wrapping_sub(x & 1))
+}
 
 #[cfg(test)]
 mod tests {
@@ -180,45 +312,135 @@ mod tests {
     }
 
     #[test]
+    fn constant_time_byte_inverse_test() {
+        assert_eq!(constant_time_byte_inverse(0), 0);
+        for a in 1..=255 {
+            let res = constant_time_byte_inverse(a);
+            assert_eq!(res, byte_inverse(a).unwrap());
+            assert_eq!(byte_multiplication(a, res), 1);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unusual_byte_groupings)]
     fn byte_quotient_test() {
         let a: u16 = 0b_1101_1111_0010;
         let b: u16 = 0b_1_0101;
-        let res = byte_quotient(a, b);
+        let res = byte_quotient(a, b).unwrap();
         let actual = 0b_1110_1011;
         assert_eq!(res, actual);
 
         let a: u16 = 0b_1_0101;
         let b: u16 = 0b_1101_1111_0010;
-        let res = byte_quotient(a, b);
+        let res = byte_quotient(a, b).unwrap();
         let actual = 0b_0;
         assert_eq!(res, actual);
+
+        let res = byte_quotient(a, 0);
+        assert_eq!(res, Err(CryptoError::DivisionByZero));
     }
 
     #[test]
+    #[allow(clippy::unusual_byte_groupings)]
     fn byte_remainder_test() {
         let a: u16 = 0b_1101_1111_0010;
         let b: u16 = 0b_1_0101;
-        let res = byte_remainder(a, b);
+        let res = byte_remainder(a, b).unwrap();
         let actual = 0b_101;
         assert_eq!(res, actual);
 
         let a: u16 = 0b_1_0101;
         let b: u16 = 0b_1101_1111_0010;
-        let res = byte_remainder(a, b);
+        let res = byte_remainder(a, b).unwrap();
         let actual = 0b_1_0101;
         assert_eq!(res, actual);
+
+        let res = byte_remainder(a, 0);
+        assert_eq!(res, Err(CryptoError::DivisionByZero));
     }
 
     #[test]
+    #[allow(clippy::unusual_byte_groupings)]
     fn byte_inverse_test() {
         let a: u8 = 1;
-        let res = byte_inverse(a);
+        let res = byte_inverse(a).unwrap();
         let actual = 1;
         assert_eq!(res, actual);
 
         let a: u8 = 0b_110_1100;
-        let res = byte_inverse(a);
+        let res = byte_inverse(a).unwrap();
         let actual = 0b_11_0011;
         assert_eq!(res, actual);
+
+        let res = byte_inverse(0);
+        assert_eq!(res, Err(CryptoError::DivisionByZero));
+    }
+
+    #[test]
+    fn gf128_multiplication_test() {
+        // H and X_1 from test case 2 of the GCM specification
+        let h: u128 = 0x66e94bd4ef8a2c3b884cfa59ca342b2e;
+        let x: u128 = 0x0388dace60b6a392f328c2b971b2fe78;
+        let res = gf128_multiplication(x, h);
+        let actual: u128 = 0x5e2ec746917062882c85b0685353deb7;
+        assert_eq!(res, actual);
+
+        let one: u128 = 1 << 127;
+        let res = gf128_multiplication(one, h);
+        assert_eq!(res, h);
+
+        let res = gf128_multiplication(h, 0);
+        assert_eq!(res, 0);
+    }
+
+    #[test]
+    fn gf128_double_test() {
+        // Subkeys of the RFC 4493 example key
+        let l: u128 = 0x7df76b0c1ab899b33e42f047b91b546f;
+        let k1 = gf128_double(l);
+        assert_eq!(k1, 0xfbeed618357133667c85e08f7236a8de);
+        assert_eq!(gf128_double(k1), 0xf7ddac306ae266ccf90bc11ee46d513b);
+
+        assert_eq!(gf128_double(1), 2);
+        assert_eq!(gf128_double(1 << 127), 0x87);
+    }
+
+    #[test]
+    fn gf128_halve_test() {
+        let l: u128 = 0x7df76b0c1ab899b33e42f047b91b546f;
+        assert_eq!(gf128_halve(gf128_double(l)), l);
+        assert_eq!(gf128_double(gf128_halve(l)), l);
+
+        assert_eq!(gf128_halve(2), 1);
+        assert_eq!(gf128_halve(0x87), 1 << 127);
+    }
+
+    #[test]
+    fn polyval_multiplication_test() {
+        // H and X_1 from RFC 8452 appendix A
+        let h: u128 = u128::from_le_bytes([0x25, 0x62, 0x93, 0x47, 0x58, 0x92, 0x42, 0x76, 0x1d, 0x31, 0xf8, 0x26, 0xba, 0x4b, 0x75, 0x7b]);
+        let x: u128 = u128::from_le_bytes([0x4f, 0x4f, 0x95, 0x66, 0x8c, 0x83, 0xdf, 0xb6, 0x40, 0x17, 0x62, 0xbb, 0x2d, 0x01, 0xa2, 0x62]);
+        assert_eq!(polyval_multiplication(h, x), polyval_multiplication(x, h));
+
+        // x^128 mod p is the identity of dot
+        let x_128: u128 = 1 << 127 | 1 << 126 | 1 << 121 | 1;
+        assert_eq!(polyval_multiplication(h, x_128), h);
+        assert_eq!(polyval_multiplication(x_128, x), x);
+
+        let res = polyval_multiplication(h, 0);
+        assert_eq!(res, 0);
+    }
+
+    #[test]
+    fn polyval_test() {
+        // RFC 8452 appendix A
+        let h: [u8; 16] = [0x25, 0x62, 0x93, 0x47, 0x58, 0x92, 0x42, 0x76, 0x1d, 0x31, 0xf8, 0x26, 0xba, 0x4b, 0x75, 0x7b];
+        let x: [u8; 32] = [0x4f, 0x4f, 0x95, 0x66, 0x8c, 0x83, 0xdf, 0xb6, 0x40, 0x17, 0x62, 0xbb, 0x2d, 0x01, 0xa2, 0x62, 0xd1, 0xa2, 0x4d, 0xdd, 0x27, 0x21, 0xd0, 0x06, 0xbb, 0xe4, 0x5f, 0x20, 0xd3, 0xc9, 0xf3, 0x62];
+        let actual: [u8; 16] = [0xf7, 0xa3, 0xb4, 0x7b, 0x84, 0x61, 0x19, 0xfa, 0xe5, 0xb7, 0x86, 0x6c, 0xf5, 0xe5, 0xb7, 0x7e];
+        let res = polyval(u128::from_le_bytes(h), &[&x]);
+        assert_eq!(res.to_le_bytes(), actual);
+
+        let res = polyval(u128::from_le_bytes(h), &[&x[..16], &x[16..]]);
+        assert_eq!(res.to_le_bytes(), actual);
     }
 }
\ No newline at end of file
@@ -0,0 +1,346 @@
+use crate::cipher::BlockCipher;
+use crate::error::CryptoError;
+use crate::math::gf128_double;
+use crate::util::{constant_time_eq,zeroize};
+
+/// Number of precomputed L_i; block index i uses L_ntz(i) and ntz of a u64 is below 64
+const L_TABLE_SIZE: usize = 64;
+/// Number of blocks masked and passed to the cipher together
+const BATCH: usize = 8;
+
+/// Description:
+/// OCB3 authenticated encryption (RFC 7253). Each block is masked with an offset before and
+/// after the block cipher, so a message costs one cipher call per block and the blocks are
+/// independent; the tag is the encrypted checksum of the plain text XORed with a PMAC-like
+/// hash of the associated data. The L values derived from the key are zeroed when the
+/// instance is dropped.
+pub struct Ocb<'a, C: BlockCipher> {
+    cipher: &'a C,
+    tag_length: usize,
+    l_star: u128,
+    l_dollar: u128,
+    l: [u128; L_TABLE_SIZE],
+}
+
+impl<'a, C: BlockCipher> Ocb<'a, C> {
+    /// Description:
+    /// Creates an OCB instance and precomputes L_* = E(0^128), L_$ = 2 L_* and L_i = 2^(i+1) L_$.
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// tag_length - (I,REQ) - Tag length in bytes, from 1 to 16
+    ///
+    /// Returns:
+    /// The OCB instance, or InvalidLength for an unsupported tag length
+    pub fn new(cipher: &'a C, tag_length: usize) -> Result<Self, CryptoError> {
+        if !(1..=16).contains(&tag_length) {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let l_star = u128::from_be_bytes(cipher.encrypt_block([0; 16]));
+        let l_dollar = gf128_double(l_star);
+        let mut l: [u128; L_TABLE_SIZE] = [gf128_double(l_dollar); L_TABLE_SIZE];
+        for i in 1..L_TABLE_SIZE {
+            l[i] = gf128_double(l[i-1]);
+        }
+
+        Ok(Ocb { cipher, tag_length, l_star, l_dollar, l })
+    }
+
+    /// Description:
+    /// Encrypts and authenticates plain text and authenticates the associated data.
+    ///
+    /// Arguments:
+    /// plain_text - (I,REQ) - The message to encrypt
+    /// nonce - (I,REQ) - A 1 to 15 byte nonce that is never reused with the same key; 12 bytes is recommended
+    /// aad - (I,REQ) - Associated data that is authenticated but not encrypted
+    ///
+    /// Returns:
+    /// cipher text || tag, or InvalidLength for a wrong nonce length
+    pub fn encrypt(&self, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let offset = self.initial_offset(nonce)?;
+
+        let mut message = plain_text;
+        let (offset, checksum) = self.process(&mut message, offset, true);
+        let tag = self.tag(offset, checksum, aad);
+        message.extend(&tag[..self.tag_length]);
+
+        Ok(message)
+    }
+
+    /// Description:
+    /// Decrypts and verifies the tag. The plain text is only returned if the tag matches.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - cipher text || tag as produced by encrypt
+    /// nonce - (I,REQ) - The nonce used for encryption
+    /// aad - (I,REQ) - The associated data used for encryption
+    ///
+    /// Returns:
+    /// The plain text, InvalidLength for a wrong nonce length or a message shorter than the tag,
+    /// or AuthenticationFailed if the tag does not match
+    pub fn decrypt(&self, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if message.len() < self.tag_length {
+            return Err(CryptoError::InvalidLength);
+        }
+        let offset = self.initial_offset(nonce)?;
+
+        let mut plain_text = message;
+        let received_tag = plain_text.split_off(plain_text.len() - self.tag_length);
+        let (offset, checksum) = self.process(&mut plain_text, offset, false);
+        let tag = self.tag(offset, checksum, aad);
+        if !constant_time_eq(&tag[..self.tag_length], &received_tag) {
+            plain_text.fill(0);
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        Ok(plain_text)
+    }
+
+    // Offset_0 from the nonce: Ktop encrypts the nonce block with its last 6 bits cleared and
+    // those bits pick where the 128-bit window starts in Stretch = Ktop || (Ktop[1..64] ^ Ktop[9..72])
+    fn initial_offset(&self, nonce: &[u8]) -> Result<u128, CryptoError> {
+        if nonce.is_empty() || nonce.len() > 15 {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let mut block: [u8; 16] = [0; 16];
+        block[16-nonce.len()..].copy_from_slice(nonce);
+        block[15-nonce.len()] |= 1;
+        block[0] |= (((self.tag_length * 8) % 128) << 1) as u8;
+        let bottom = (block[15] & 0x3f) as u32;
+        block[15] &= 0xc0;
+
+        let ktop = u128::from_be_bytes(self.cipher.encrypt_block(block));
+        if bottom == 0 {
+            return Ok(ktop);
+        }
+        let stretch = ((ktop >> 64) as u64) ^ ((ktop >> 56) as u64);
+
+        Ok((ktop << bottom) | (stretch >> (64 - bottom)) as u128)
+    }
+
+    // Encrypts or decrypts data in place and returns the final offset and the plain text checksum
+    fn process(&self, data: &mut [u8], offset: u128, encrypt: bool) -> (u128, u128) {
+        let mut offset = offset;
+        let mut checksum: u128 = 0;
+        let mut index: u64 = 0;
+
+        let (blocks, tail) = data.as_chunks_mut::<16>();
+        for batch in blocks.chunks_mut(BATCH) {
+            let mut offsets: [u128; BATCH] = [0; BATCH];
+            for (block, block_offset) in batch.iter_mut().zip(offsets.iter_mut()) {
+                index += 1;
+                offset ^= self.l[index.trailing_zeros() as usize];
+                *block_offset = offset;
+                if encrypt {
+                    checksum ^= u128::from_be_bytes(*block);
+                }
+                *block = (u128::from_be_bytes(*block) ^ offset).to_be_bytes();
+            }
+
+            if encrypt {
+                self.cipher.encrypt_blocks(batch);
+            } else {
+                self.cipher.decrypt_blocks(batch);
+            }
+
+            for (block, block_offset) in batch.iter_mut().zip(offsets) {
+                *block = (u128::from_be_bytes(*block) ^ block_offset).to_be_bytes();
+                if !encrypt {
+                    checksum ^= u128::from_be_bytes(*block);
+                }
+            }
+        }
+
+        if !tail.is_empty() {
+            offset ^= self.l_star;
+            let pad = self.cipher.encrypt_block(offset.to_be_bytes());
+            let mut block: [u8; 16] = [0; 16];
+            for (i, byte) in tail.iter_mut().enumerate() {
+                if encrypt {
+                    block[i] = *byte;
+                }
+                *byte ^= pad[i];
+                if !encrypt {
+                    block[i] = *byte;
+                }
+            }
+            block[tail.len()] = 0x80;
+            checksum ^= u128::from_be_bytes(block);
+        }
+
+        (offset, checksum)
+    }
+
+    fn tag(&self, offset: u128, checksum: u128, aad: &[u8]) -> [u8; 16] {
+        let tag = self.cipher.encrypt_block((checksum ^ offset ^ self.l_dollar).to_be_bytes());
+        (u128::from_be_bytes(tag) ^ self.hash(aad)).to_be_bytes()
+    }
+
+    // HASH(K, A): the same offset sequence as encryption, but starting from zero
+    fn hash(&self, aad: &[u8]) -> u128 {
+        let mut offset: u128 = 0;
+        let mut sum: u128 = 0;
+
+        let (blocks, tail) = aad.as_chunks::<16>();
+        for (i, block) in blocks.iter().enumerate() {
+            offset ^= self.l[(i as u64 + 1).trailing_zeros() as usize];
+            let block = u128::from_be_bytes(*block) ^ offset;
+            sum ^= u128::from_be_bytes(self.cipher.encrypt_block(block.to_be_bytes()));
+        }
+
+        if !tail.is_empty() {
+            let mut block: [u8; 16] = [0; 16];
+            block[..tail.len()].copy_from_slice(tail);
+            block[tail.len()] = 0x80;
+            let block = u128::from_be_bytes(block) ^ offset ^ self.l_star;
+            sum ^= u128::from_be_bytes(self.cipher.encrypt_block(block.to_be_bytes()));
+        }
+
+        sum
+    }
+}
+
+impl<'a, C: BlockCipher> Drop for Ocb<'a, C> {
+    fn drop(&mut self) {
+        zeroize(std::slice::from_mut(&mut self.l_star));
+        zeroize(std::slice::from_mut(&mut self.l_dollar));
+        zeroize(&mut self.l);
+    }
+}
+
+/// Description:
+/// Encrypts with OCB3 and a 16-byte tag.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// plain_text - (I,REQ) - The message to encrypt
+/// nonce - (I,REQ) - A 1 to 15 byte nonce that is never reused with the same key
+/// aad - (I,REQ) - Associated data that is authenticated but not encrypted
+///
+/// Returns:
+/// cipher text || tag, or an error as for Ocb::encrypt
+#[allow(non_snake_case)]
+pub fn OCB_encrypt<C: BlockCipher>(cipher: &C, plain_text: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ocb::new(cipher, 16)?.encrypt(plain_text, nonce, aad)
+}
+
+/// Description:
+/// Decrypts and verifies a message produced by OCB_encrypt.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - cipher text || tag
+/// nonce - (I,REQ) - The nonce used for encryption
+/// aad - (I,REQ) - The associated data used for encryption
+///
+/// Returns:
+/// The plain text, or an error as for Ocb::decrypt
+#[allow(non_snake_case)]
+pub fn OCB_decrypt<C: BlockCipher>(cipher: &C, message: Vec<u8>, nonce: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ocb::new(cipher, 16)?.decrypt(message, nonce, aad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes,Aes128};
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn OCB_sample_test() {
+        // RFC 7253 appendix A, key 000102...0f, nonce BBAA9988776655443322110N
+        let cipher = Aes128::new([0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f]);
+        let input: Vec<u8> = (0x00..0x28).collect();
+        let vectors: [(u8, usize, usize, &[u8]); 8] = [
+            (0x0, 0, 0, &[0x78, 0x54, 0x07, 0xbf, 0xff, 0xc8, 0xad, 0x9e, 0xdc, 0xc5, 0x52, 0x0a, 0xc9, 0x11, 0x1e, 0xe6]),
+            (0x1, 8, 8, &[0x68, 0x20, 0xb3, 0x65, 0x7b, 0x6f, 0x61, 0x5a, 0x57, 0x25, 0xbd, 0xa0, 0xd3, 0xb4, 0xeb, 0x3a, 0x25, 0x7c, 0x9a, 0xf1, 0xf8, 0xf0, 0x30, 0x09]),
+            (0x2, 8, 0, &[0x81, 0x01, 0x7f, 0x82, 0x03, 0xf0, 0x81, 0x27, 0x71, 0x52, 0xfa, 0xde, 0x69, 0x4a, 0x0a, 0x00]),
+            (0x3, 0, 8, &[0x45, 0xdd, 0x69, 0xf8, 0xf5, 0xaa, 0xe7, 0x24, 0x14, 0x05, 0x4c, 0xd1, 0xf3, 0x5d, 0x82, 0x76, 0x0b, 0x2c, 0xd0, 0x0d, 0x2f, 0x99, 0xbf, 0xa9]),
+            (0x4, 16, 16, &[0x57, 0x1d, 0x53, 0x5b, 0x60, 0xb2, 0x77, 0x18, 0x8b, 0xe5, 0x14, 0x71, 0x70, 0xa9, 0xa2, 0x2c, 0x3a, 0xd7, 0xa4, 0xff, 0x38, 0x35, 0xb8, 0xc5, 0x70, 0x1c, 0x1c, 0xce, 0xc8, 0xfc, 0x33, 0x58]),
+            (0x7, 24, 24, &[0x1c, 0xa2, 0x20, 0x73, 0x08, 0xc8, 0x7c, 0x01, 0x07, 0x56, 0x10, 0x4d, 0x88, 0x40, 0xce, 0x19, 0x52, 0xf0, 0x96, 0x73, 0xa4, 0x48, 0xa1, 0x22, 0xc9, 0x2c, 0x62, 0x24, 0x10, 0x51, 0xf5, 0x73, 0x56, 0xd7, 0xf3, 0xc9, 0x0b, 0xb0, 0xe0, 0x7f]),
+            (0xd, 40, 40, &[0xd5, 0xca, 0x91, 0x74, 0x84, 0x10, 0xc1, 0x75, 0x1f, 0xf8, 0xa2, 0xf6, 0x18, 0x25, 0x5b, 0x68, 0xa0, 0xa1, 0x2e, 0x09, 0x3f, 0xf4, 0x54, 0x60, 0x6e, 0x59, 0xf9, 0xc1, 0xd0, 0xdd, 0xc5, 0x4b, 0x65, 0xe8, 0x62, 0x8e, 0x56, 0x8b, 0xad, 0x7a, 0xed, 0x07, 0xba, 0x06, 0xa4, 0xa6, 0x94, 0x83, 0xa7, 0x03, 0x54, 0x90, 0xc5, 0x76, 0x9e, 0x60]),
+            (0xf, 0, 40, &[0x44, 0x12, 0x92, 0x34, 0x93, 0xc5, 0x7d, 0x5d, 0xe0, 0xd7, 0x00, 0xf7, 0x53, 0xcc, 0xe0, 0xd1, 0xd2, 0xd9, 0x50, 0x60, 0x12, 0x2e, 0x9f, 0x15, 0xa5, 0xdd, 0xbf, 0xc5, 0x78, 0x7e, 0x50, 0xb5, 0xcc, 0x55, 0xee, 0x50, 0x7b, 0xcb, 0x08, 0x4e, 0x47, 0x9a, 0xd3, 0x63, 0xac, 0x36, 0x6b, 0x95, 0xa9, 0x8c, 0xa5, 0xf3, 0x00, 0x0b, 0x14, 0x79]),
+        ];
+
+        for (n, aad_length, plain_text_length, actual) in vectors {
+            let nonce = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, n];
+            let aad = &input[..aad_length];
+            let res = OCB_encrypt(&cipher, input[..plain_text_length].to_vec(), &nonce, aad).unwrap();
+            assert_eq!(res, actual);
+            let res = OCB_decrypt(&cipher, res, &nonce, aad).unwrap();
+            assert_eq!(res, input[..plain_text_length]);
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn OCB_96_bit_tag_test() {
+        // RFC 7253 appendix A, the sample with TAGLEN = 96
+        let cipher = Aes128::new([0x0f0e0d0c, 0x0b0a0908, 0x07060504, 0x03020100]);
+        let nonce = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x0d];
+        let input: Vec<u8> = (0x00..0x28).collect();
+        let actual = [0x17, 0x92, 0xa4, 0xe3, 0x1e, 0x07, 0x55, 0xfb, 0x03, 0xe3, 0x1b, 0x22, 0x11, 0x6e, 0x6c, 0x2d, 0xdf, 0x9e, 0xfd, 0x6e, 0x33, 0xd5, 0x36, 0xf1, 0xa0, 0x12, 0x4b, 0x0a, 0x55, 0xba, 0xe8, 0x84, 0xed, 0x93, 0x48, 0x15, 0x29, 0xc7, 0x6b, 0x6a, 0xd0, 0xc5, 0x15, 0xf4, 0xd1, 0xcd, 0xd4, 0xfd, 0xac, 0x4f, 0x02, 0xaa];
+
+        let ocb = Ocb::new(&cipher, 12).unwrap();
+        let res = ocb.encrypt(input.clone(), &nonce, &input).unwrap();
+        assert_eq!(res, actual);
+        assert_eq!(ocb.decrypt(res, &nonce, &input).unwrap(), input);
+    }
+
+    // The iterated test from RFC 7253 appendix A: K = 0^(KEYLEN-8) || TAGLEN
+    fn iterated<const NK: usize>(tag_length: usize) -> Vec<u8> {
+        let mut key: [u32; NK] = [0; NK];
+        key[NK-1] = (tag_length * 8) as u32;
+        let cipher = Aes::<NK>::new(key);
+        let ocb = Ocb::new(&cipher, tag_length).unwrap();
+        let nonce = |n: u32| {
+            let mut nonce = [0; 12];
+            nonce[8..].copy_from_slice(&n.to_be_bytes());
+            nonce
+        };
+
+        let mut c = Vec::new();
+        for i in 0..128 {
+            let s = vec![0; i as usize];
+            c.extend(ocb.encrypt(s.clone(), &nonce(3*i+1), &s).unwrap());
+            c.extend(ocb.encrypt(s.clone(), &nonce(3*i+2), &[]).unwrap());
+            c.extend(ocb.encrypt(Vec::new(), &nonce(3*i+3), &s).unwrap());
+        }
+
+        ocb.encrypt(Vec::new(), &nonce(385), &c).unwrap()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn OCB_iterated_test() {
+        assert_eq!(iterated::<4>(16), [0x67, 0xe9, 0x44, 0xd2, 0x32, 0x56, 0xc5, 0xe0, 0xb6, 0xc6, 0x1f, 0xa2, 0x2f, 0xdf, 0x1e, 0xa2]);
+        assert_eq!(iterated::<6>(16), [0xf6, 0x73, 0xf2, 0xc3, 0xe7, 0x17, 0x4a, 0xae, 0x7b, 0xae, 0x98, 0x6c, 0xa9, 0xf2, 0x9e, 0x17]);
+        assert_eq!(iterated::<8>(16), [0xd9, 0x0e, 0xb8, 0xe9, 0xc9, 0x77, 0xc8, 0x8b, 0x79, 0xdd, 0x79, 0x3d, 0x7f, 0xfa, 0x16, 0x1c]);
+        assert_eq!(iterated::<4>(12), [0x77, 0xa3, 0xd8, 0xe7, 0x35, 0x89, 0x15, 0x8d, 0x25, 0xd0, 0x12, 0x09]);
+        assert_eq!(iterated::<6>(12), [0x05, 0xd5, 0x6e, 0xad, 0x27, 0x52, 0xc8, 0x6b, 0xe6, 0x93, 0x2c, 0x5e]);
+        assert_eq!(iterated::<8>(12), [0x54, 0x58, 0x35, 0x9a, 0xc2, 0x3b, 0x0c, 0xba, 0x9e, 0x63, 0x30, 0xdd]);
+        assert_eq!(iterated::<4>(8), [0x19, 0x2c, 0x9b, 0x7b, 0xd9, 0x0b, 0xa0, 0x6a]);
+        assert_eq!(iterated::<6>(8), [0x00, 0x66, 0xbc, 0x6e, 0x0e, 0xf3, 0x4e, 0x24]);
+        assert_eq!(iterated::<8>(8), [0x7d, 0x4e, 0xa5, 0xd4, 0x45, 0x50, 0x1c, 0xbe]);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn OCB_error_test() {
+        let cipher = Aes128::new([0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f]);
+        let nonce = [0; 12];
+        let mut message = OCB_encrypt(&cipher, (0..50).collect(), &nonce, b"header").unwrap();
+
+        message[3] ^= 1;
+        assert_eq!(OCB_decrypt(&cipher, message.clone(), &nonce, b"header"), Err(CryptoError::AuthenticationFailed));
+        message[3] ^= 1;
+        assert_eq!(OCB_decrypt(&cipher, message.clone(), &nonce, b"headers"), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(OCB_decrypt(&cipher, message[..15].to_vec(), &nonce, b""), Err(CryptoError::InvalidLength));
+
+        assert_eq!(OCB_encrypt(&cipher, Vec::new(), &[0; 16], b"").err(), Some(CryptoError::InvalidLength));
+        assert_eq!(OCB_encrypt(&cipher, Vec::new(), &[], b"").err(), Some(CryptoError::InvalidLength));
+        assert!(Ocb::new(&cipher, 17).is_err());
+    }
+}
\ No newline at end of file
@@ -1,3 +1,225 @@
+use rand::prelude::*;
+
+use crate::error::CryptoError;
+use crate::util::{ct_mask_lt,ct_mask_nonzero};
+
+/// Description:
+/// A scheme that extends a message to a whole number of blocks and removes the extension again.
+/// Block sizes from 1 to 255 bytes are supported, e.g. 8 for DES/3DES, 16 for AES and 32 for Rijndael-256.
+pub trait Padding {
+    /// Description:
+    /// Pads text in place to a multiple of block_size, reusing its allocation where possible.
+    ///
+    /// Arguments:
+    /// text - (I/O,REQ) - The message, padded on success and unchanged on error
+    /// block_size - (I,REQ) - The cipher block size, from 1 to 255
+    ///
+    /// Returns:
+    /// InvalidLength for an unsupported block size or a text the scheme cannot pad
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError>;
+
+    /// Description:
+    /// Removes the padding added by pad_in_place by truncating text.
+    ///
+    /// Arguments:
+    /// text - (I/O,REQ) - The padded message, unpadded on success and unchanged on error
+    /// block_size - (I,REQ) - The cipher block size, from 1 to 255
+    ///
+    /// Returns:
+    /// InvalidLength if text is not a multiple of block_size or InvalidPadding if the padding is malformed
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError>;
+
+    /// Description:
+    /// Pads text to a multiple of block_size.
+    ///
+    /// Arguments:
+    /// text - (I,REQ) - The message
+    /// block_size - (I,REQ) - The cipher block size, from 1 to 255
+    ///
+    /// Returns:
+    /// The padded text, or an error as for pad_in_place
+    fn pad(&self, text: Vec<u8>, block_size: usize) -> Result<Vec<u8>, CryptoError> {
+        let mut text = text;
+        self.pad_in_place(&mut text, block_size)?;
+
+        Ok(text)
+    }
+
+    /// Description:
+    /// Removes the padding added by pad.
+    ///
+    /// Arguments:
+    /// text - (I,REQ) - The padded message
+    /// block_size - (I,REQ) - The cipher block size, from 1 to 255
+    ///
+    /// Returns:
+    /// The unpadded text, or an error as for unpad_in_place
+    fn unpad(&self, text: Vec<u8>, block_size: usize) -> Result<Vec<u8>, CryptoError> {
+        let mut text = text;
+        self.unpad_in_place(&mut text, block_size)?;
+
+        Ok(text)
+    }
+}
+
+/// Description:
+/// PKCS#7: n bytes of value n.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Pkcs7;
+
+/// Description:
+/// ANSI X.923: zeros followed by a byte holding the padding length.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AnsiX923;
+
+/// Description:
+/// ISO 10126: random bytes followed by a byte holding the padding length.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Iso10126;
+
+/// Description:
+/// ISO/IEC 7816-4: a 0x80 byte followed by zeros.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Iso7816;
+
+/// Description:
+/// Zeros up to the next block boundary, nothing if already aligned.
+/// Unpadding strips every trailing zero, so it is only reversible for messages that do not end in 0x00.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZeroPadding;
+
+/// Description:
+/// No padding. The message must already be a multiple of the block size.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoPadding;
+
+/// What a length-byte scheme puts before its final byte
+#[derive(Clone, Copy)]
+enum Fill {
+    Length,
+    Zero,
+    Random,
+}
+
+impl Padding for Pkcs7 {
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        length_byte_padding(text, block_size, Fill::Length);
+
+        Ok(())
+    }
+
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        length_byte_unpadding(text, block_size, Fill::Length)
+    }
+}
+
+impl Padding for AnsiX923 {
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        length_byte_padding(text, block_size, Fill::Zero);
+
+        Ok(())
+    }
+
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        length_byte_unpadding(text, block_size, Fill::Zero)
+    }
+}
+
+impl Padding for Iso10126 {
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        length_byte_padding(text, block_size, Fill::Random);
+
+        Ok(())
+    }
+
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        length_byte_unpadding(text, block_size, Fill::Random)
+    }
+}
+
+impl Padding for Iso7816 {
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+
+        let n = text.len();
+        let padding = block_size - n % block_size;
+        text.reserve(padding);
+        text.push(0x80);
+        text.resize(n+padding, 0x00);
+
+        Ok(())
+    }
+
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        let n = text.len();
+        if n == 0 || !n.is_multiple_of(block_size) {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        // Scan the whole last block from the end: zeros until the first 0x80, anything before it
+        let mut found: u8 = 0x00;
+        let mut bad: u8 = 0x00;
+        let mut position: u8 = 0;
+        for i in 0..block_size {
+            let byte = text[n-1-i];
+            let is_marker = !found & !ct_mask_nonzero(byte ^ 0x80);
+            bad |= !found & ct_mask_nonzero(byte) & !is_marker;
+            position |= is_marker & i as u8;
+            found |= is_marker;
+        }
+        if std::hint::black_box(found & !bad) != 0xFF {
+            return Err(CryptoError::InvalidPadding);
+        }
+        text.truncate(n - 1 - position as usize);
+
+        Ok(())
+    }
+}
+
+impl Padding for ZeroPadding {
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        text.resize(text.len().next_multiple_of(block_size), 0x00);
+
+        Ok(())
+    }
+
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        if !text.len().is_multiple_of(block_size) {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        while text.last() == Some(&0x00) {
+            text.pop();
+        }
+
+        Ok(())
+    }
+}
+
+impl Padding for NoPadding {
+    fn pad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        check_block_size(block_size)?;
+        if !text.len().is_multiple_of(block_size) {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        Ok(())
+    }
+
+    fn unpad_in_place(&self, text: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        self.pad_in_place(text, block_size)
+    }
+}
+
 /// Description:
 /// Does PKCS7 padding to a multiple of 16.
 ///
@@ -5,34 +227,101 @@
 /// text - (I,REQ) - A string of hexadecimals where each digit is padded to length 2
 ///
 /// Returns:
-/// The padded text
+/// The padded text, in the same allocation as text when it has room
 #[allow(non_snake_case)]
 pub fn PKCS7_padding(text: Vec<u8>) -> Vec<u8> {
-    let n = text.len();
-    let padding = 16 - n % 16;
-    let mut padded_string: Vec<u8> = Vec::with_capacity(n+padding);
-    padded_string.extend(text);
-    for _ in 0..padding {
-        padded_string.push(padding as u8);
-    }
+    let mut text = text;
+    length_byte_padding(&mut text, 16, Fill::Length);
 
-    padded_string
+    text
 }
 
 /// Description:
 /// Unpads a string padded to a multiple of 16 with PKCS7.
+/// The padding is checked in constant time: the last 16 bytes are always all examined
+/// and every kind of malformed padding gives the same error, so the result cannot be
+/// used as a padding oracle.
 ///
 /// Arguments:
 /// text - (I,REQ) - A padded string of hexadecimals where each digit is padded to length 2
 ///
 /// Returns:
-/// The unpadded text
+/// The unpadded text, InvalidLength if text is not a non-empty multiple of 16
+/// or InvalidPadding if the padding is malformed
 #[allow(non_snake_case)]
-pub fn PKCS7_unpadding(text: Vec<u8>) -> Vec<u8> {
+pub fn PKCS7_unpadding(text: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    let mut text = text;
+    length_byte_unpadding(&mut text, 16, Fill::Length)?;
+
+    Ok(text)
+}
+
+fn check_block_size(block_size: usize) -> Result<(), CryptoError> {
+    match block_size {
+        1..=255 => Ok(()),
+        _ => Err(CryptoError::InvalidLength),
+    }
+}
+
+/// Description:
+/// Pads in place to a multiple of block_size with a final byte holding the padding length.
+///
+/// Arguments:
+/// text - (I/O,REQ) - The message
+/// block_size - (I,REQ) - The block size, from 1 to 255
+/// fill - (I,REQ) - What goes before the length byte
+fn length_byte_padding(text: &mut Vec<u8>, block_size: usize, fill: Fill) {
     let n = text.len();
-    let padding = text[n-1] as usize;
+    let padding = block_size - n % block_size;
+    text.reserve(padding);
+    match fill {
+        Fill::Length => text.resize(n+padding-1, padding as u8),
+        Fill::Zero => text.resize(n+padding-1, 0x00),
+        Fill::Random => {
+            let mut rng = rand::rng();
+            for _ in 1..padding {
+                text.push(rng.random::<u8>());
+            }
+        }
+    }
+    text.push(padding as u8);
+}
 
-    text[0..(n-padding)].to_vec()
+/// Description:
+/// Removes padding ending in a length byte by truncating text. The last block_size bytes
+/// are always all examined and every kind of malformed padding gives the same error, so
+/// the result cannot be used as a padding oracle.
+///
+/// Arguments:
+/// text - (I/O,REQ) - The padded message, left unchanged on error
+/// block_size - (I,REQ) - The block size, from 1 to 255
+/// fill - (I,REQ) - What is expected before the length byte
+///
+/// Returns:
+/// InvalidLength if text is not a non-empty multiple of block_size or InvalidPadding if the padding is malformed
+fn length_byte_unpadding(text: &mut Vec<u8>, block_size: usize, fill: Fill) -> Result<(), CryptoError> {
+    let n = text.len();
+    if n == 0 || !n.is_multiple_of(block_size) {
+        return Err(CryptoError::InvalidLength);
+    }
+
+    let padding = text[n-1];
+    let mut good = ct_mask_lt(padding.wrapping_sub(1), block_size as u8); // 1 <= padding <= block_size
+    for i in 1..block_size {
+        let in_padding = ct_mask_lt(i as u8, padding);
+        let expected = match fill {
+            Fill::Length => padding,
+            Fill::Zero => 0x00,
+            Fill::Random => continue,
+        };
+        good &= !(in_padding & ct_mask_nonzero(text[n-1-i] ^ expected));
+    }
+    if std::hint::black_box(good) != 0xFF {
+        return Err(CryptoError::InvalidPadding);
+    }
+    text.truncate(n - padding as usize);
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -57,13 +346,191 @@ mod tests {
     #[allow(non_snake_case)]
     fn PKCS7_unpadding_test() {
         let s = vec![0x00, 0x3F, 0x24, 0xF6, 0xD2, 0x57, 0x05, 0x92, 0xA4, 0x5E, 0xEA, 0x05, 0x05, 0x05, 0x05, 0x05];
-        let res = PKCS7_unpadding(s);
+        let res = PKCS7_unpadding(s).unwrap();
         let actual = vec![0x00, 0x3F, 0x24, 0xF6, 0xD2, 0x57, 0x05, 0x92, 0xA4, 0x5E, 0xEA];
         assert_eq!(res, actual);
 
         let s = vec![0x00, 0x3F, 0x24, 0xF6, 0xD2, 0x57, 0x05, 0x92, 0xA4, 0x5E, 0xEA, 0x05, 0x05, 0x05, 0x05, 0x05, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10];
-        let res = PKCS7_unpadding(s);
+        let res = PKCS7_unpadding(s).unwrap();
         let actual = vec![0x00, 0x3F, 0x24, 0xF6, 0xD2, 0x57, 0x05, 0x92, 0xA4, 0x5E, 0xEA, 0x05, 0x05, 0x05, 0x05, 0x05];
         assert_eq!(res, actual);
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn PKCS7_unpadding_error_test() {
+        let res = PKCS7_unpadding(vec![]);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+
+        let res = PKCS7_unpadding(vec![0x01; 15]);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+
+        let res = PKCS7_unpadding(vec![0x01; 17]);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+
+        // Padding byte out of range
+        for padding in [0x00, 0x11, 0x20, 0x80, 0xFF] {
+            let res = PKCS7_unpadding(vec![padding; 32]);
+            assert_eq!(res, Err(CryptoError::InvalidPadding));
+        }
+
+        // One wrong byte anywhere inside the padding
+        for padding in 2..=16 {
+            for wrong in 1..padding {
+                let mut s = PKCS7_padding(vec![0xAB; 16 - padding]);
+                s[15 - wrong] ^= 0x01;
+                let res = PKCS7_unpadding(s);
+                assert_eq!(res, Err(CryptoError::InvalidPadding));
+            }
+        }
+
+        // Padding claims more bytes than were padded
+        let mut s = PKCS7_padding(vec![0x00; 12]);
+        s[15] = 0x05;
+        let res = PKCS7_unpadding(s);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn PKCS7_round_trip_test() {
+        for n in 0..=48 {
+            let s: Vec<u8> = (0..n as u8).collect();
+            let res = PKCS7_unpadding(PKCS7_padding(s.clone())).unwrap();
+            assert_eq!(res, s);
+        }
+    }
+
+    #[test]
+    fn ansi_x923_test() {
+        let s = vec![0xDD, 0xDD, 0xDD, 0xDD];
+        let res = AnsiX923.pad(s.clone(), 8).unwrap();
+        let actual = vec![0xDD, 0xDD, 0xDD, 0xDD, 0x00, 0x00, 0x00, 0x04];
+        assert_eq!(res, actual);
+        assert_eq!(AnsiX923.unpad(res, 8).unwrap(), s);
+
+        let res = AnsiX923.unpad(vec![0xDD, 0xDD, 0xDD, 0xDD, 0x00, 0x01, 0x00, 0x04], 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+        let res = AnsiX923.unpad(vec![0x00; 8], 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+        let res = AnsiX923.unpad(vec![0x09; 8], 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+    }
+
+    #[test]
+    fn iso_10126_test() {
+        let s = vec![0xDD, 0xDD, 0xDD, 0xDD, 0xDD];
+        let res = Iso10126.pad(s.clone(), 16).unwrap();
+        assert_eq!(res.len(), 16);
+        assert_eq!(res[15], 0x0B);
+        assert_eq!(Iso10126.unpad(res, 16).unwrap(), s);
+
+        let res = Iso10126.unpad(vec![0xDD, 0x81, 0x32, 0x4F, 0xAA, 0x00, 0xDD, 0x03], 8).unwrap();
+        assert_eq!(res, vec![0xDD, 0x81, 0x32, 0x4F, 0xAA]);
+        let res = Iso10126.unpad(vec![0xDD; 8], 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+    }
+
+    #[test]
+    fn iso_7816_test() {
+        let s = vec![0xDD, 0xDD, 0xDD, 0xDD];
+        let res = Iso7816.pad(s.clone(), 8).unwrap();
+        let actual = vec![0xDD, 0xDD, 0xDD, 0xDD, 0x80, 0x00, 0x00, 0x00];
+        assert_eq!(res, actual);
+        assert_eq!(Iso7816.unpad(res, 8).unwrap(), s);
+
+        let s = vec![0x80; 8];
+        let res = Iso7816.pad(s.clone(), 8).unwrap();
+        let actual = vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(res, actual);
+        assert_eq!(Iso7816.unpad(res, 8).unwrap(), s);
+
+        let res = Iso7816.unpad(vec![0xDD, 0xDD, 0xDD, 0xDD, 0x80, 0x00, 0x01, 0x00], 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+        let res = Iso7816.unpad(vec![0x00; 16], 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+    }
+
+    #[test]
+    fn zero_padding_test() {
+        let s = vec![0xDD, 0xDD, 0xDD, 0xDD];
+        let res = ZeroPadding.pad(s.clone(), 8).unwrap();
+        let actual = vec![0xDD, 0xDD, 0xDD, 0xDD, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(res, actual);
+        assert_eq!(ZeroPadding.unpad(res, 8).unwrap(), s);
+
+        let s = vec![0xDD; 16];
+        let res = ZeroPadding.pad(s.clone(), 16).unwrap();
+        assert_eq!(res, s);
+    }
+
+    #[test]
+    fn no_padding_test() {
+        let s = vec![0xDD; 16];
+        let res = NoPadding.pad(s.clone(), 8).unwrap();
+        assert_eq!(res, s);
+        assert_eq!(NoPadding.unpad(res, 8).unwrap(), s);
+
+        let res = NoPadding.pad(vec![0xDD; 15], 16);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    fn padding_block_size_test() {
+        let res = Pkcs7.pad(vec![0xDD; 3], 8).unwrap();
+        let actual = vec![0xDD, 0xDD, 0xDD, 0x05, 0x05, 0x05, 0x05, 0x05];
+        assert_eq!(res, actual);
+        assert_eq!(Pkcs7.unpad(res, 8).unwrap(), vec![0xDD; 3]);
+
+        let res = Pkcs7.pad(vec![0xDD; 3], 0);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+        let res = Pkcs7.pad(vec![0xDD; 3], 256);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+        let res = Iso7816.unpad(vec![0xDD; 24], 256);
+        assert_eq!(res, Err(CryptoError::InvalidLength));
+    }
+
+    #[test]
+    fn pkcs7_block_size_test() {
+        for block_size in [1, 2, 8, 15, 16, 32, 200, 255] {
+            for n in [0, 1, block_size - 1, block_size, block_size + 1, 3 * block_size + 2] {
+                let s: Vec<u8> = (0..n).map(|i| i as u8).collect();
+                let res = Pkcs7.pad(s.clone(), block_size).unwrap();
+                let padding = block_size - n % block_size;
+                assert_eq!(res.len(), n + padding);
+                assert!(res[n..].iter().all(|&byte| byte as usize == padding));
+                assert_eq!(Pkcs7.unpad(res, block_size).unwrap(), s);
+            }
+        }
+
+        // A full block of 255 is the largest padding there is
+        let res = Pkcs7.pad(vec![], 255).unwrap();
+        assert_eq!(res, vec![0xFF; 255]);
+
+        let mut s = vec![0x20; 64];
+        s[40] = 0x21;
+        let res = Pkcs7.unpad(s, 32);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+        let res = Pkcs7.unpad(vec![0x21; 32], 32);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+    }
+
+    #[test]
+    fn pad_in_place_test() {
+        let mut s = Vec::with_capacity(16);
+        s.extend([0xDD, 0xDD, 0xDD]);
+        let pointer = s.as_ptr();
+        Pkcs7.pad_in_place(&mut s, 8).unwrap();
+        assert_eq!(s, vec![0xDD, 0xDD, 0xDD, 0x05, 0x05, 0x05, 0x05, 0x05]);
+        assert_eq!(s.as_ptr(), pointer);
+
+        Pkcs7.unpad_in_place(&mut s, 8).unwrap();
+        assert_eq!(s, vec![0xDD, 0xDD, 0xDD]);
+        assert_eq!(s.as_ptr(), pointer);
+
+        let mut s = vec![0xDD, 0xDD, 0xDD, 0xDD, 0xDD, 0xDD, 0xDD, 0x09];
+        let res = Pkcs7.unpad_in_place(&mut s, 8);
+        assert_eq!(res, Err(CryptoError::InvalidPadding));
+        assert_eq!(s.len(), 8);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use crate::cipher::BlockCipher;
+use crate::error::CryptoError;
+use crate::math::{gf128_double,gf128_halve};
+use crate::util::{constant_time_eq,zeroize};
+
+/// Number of precomputed L(i); block index i uses L(ntz(i)) and ntz of a u64 is below 64
+const L_TABLE_SIZE: usize = 64;
+
+/// Description:
+/// Streaming PMAC1 (Black and Rogaway). Every block but the last is masked with an offset and
+/// encrypted independently, so the blocks could be processed in parallel; the last block is
+/// held back until finalize because its treatment depends on whether it is complete. The L
+/// values, offset and running sum are zeroed when the instance is dropped.
+pub struct Pmac<'a, C: BlockCipher> {
+    cipher: &'a C,
+    l: [u128; L_TABLE_SIZE],
+    l_inverse: u128,
+    offset: u128,
+    sum: u128,
+    blocks: u64,
+    buffer: [u8; 16],
+    buffer_length: usize,
+}
+
+impl<'a, C: BlockCipher> Pmac<'a, C> {
+    /// Description:
+    /// Precomputes L(i) = L * x^i and L(-1) = L / x from L = E(0^128).
+    ///
+    /// Arguments:
+    /// cipher - (I,REQ) - The keyed block cipher
+    ///
+    /// Returns:
+    /// A PMAC with no input yet
+    pub fn new(cipher: &'a C) -> Self {
+        let l0 = u128::from_be_bytes(cipher.encrypt_block([0; 16]));
+        let mut l: [u128; L_TABLE_SIZE] = [l0; L_TABLE_SIZE];
+        for i in 1..L_TABLE_SIZE {
+            l[i] = gf128_double(l[i-1]);
+        }
+
+        Pmac { cipher, l, l_inverse: gf128_halve(l0), offset: 0, sum: 0, blocks: 0, buffer: [0; 16], buffer_length: 0 }
+    }
+
+    /// Description:
+    /// Absorbs more of the message.
+    ///
+    /// Arguments:
+    /// data - (I,REQ) - The next bytes of the message
+    pub fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+        while !data.is_empty() {
+            if self.buffer_length == 16 {
+                self.blocks += 1;
+                self.offset ^= self.l[self.blocks.trailing_zeros() as usize];
+                let block = u128::from_be_bytes(self.buffer) ^ self.offset;
+                self.sum ^= u128::from_be_bytes(self.cipher.encrypt_block(block.to_be_bytes()));
+                self.buffer_length = 0;
+            }
+
+            let n = (16 - self.buffer_length).min(data.len());
+            self.buffer[self.buffer_length..self.buffer_length+n].copy_from_slice(&data[..n]);
+            self.buffer_length += n;
+            data = &data[n..];
+        }
+    }
+
+    /// Description:
+    /// Adds the last block to the sum and encrypts it.
+    ///
+    /// Returns:
+    /// The 16-byte tag
+    pub fn finalize(self) -> [u8; 16] {
+        let mut block = self.buffer;
+        let sum = if self.buffer_length == 16 {
+            self.sum ^ u128::from_be_bytes(block) ^ self.l_inverse
+        } else {
+            block[self.buffer_length] = 0x80;
+            block[self.buffer_length+1..].fill(0);
+            self.sum ^ u128::from_be_bytes(block)
+        };
+
+        self.cipher.encrypt_block(sum.to_be_bytes())
+    }
+
+    /// Description:
+    /// Compares the tag of the absorbed message with an expected tag in constant time.
+    ///
+    /// Arguments:
+    /// tag - (I,REQ) - The expected tag, possibly truncated to its first 1 to 16 bytes
+    ///
+    /// Returns:
+    /// Ok if the tags match, AuthenticationFailed if not, or InvalidLength for an empty or overlong tag
+    pub fn verify(self, tag: &[u8]) -> Result<(), CryptoError> {
+        if tag.is_empty() || tag.len() > 16 {
+            return Err(CryptoError::InvalidLength);
+        }
+        if !constant_time_eq(&self.finalize()[..tag.len()], tag) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, C: BlockCipher> Drop for Pmac<'a, C> {
+    fn drop(&mut self) {
+        zeroize(&mut self.l);
+        zeroize(std::slice::from_mut(&mut self.l_inverse));
+        zeroize(std::slice::from_mut(&mut self.offset));
+        zeroize(std::slice::from_mut(&mut self.sum));
+        zeroize(&mut self.buffer);
+    }
+}
+
+/// Description:
+/// Computes the PMAC of a whole message.
+///
+/// Arguments:
+/// cipher - (I,REQ) - The keyed block cipher
+/// message - (I,REQ) - The message to authenticate
+///
+/// Returns:
+/// The 16-byte tag
+#[allow(non_snake_case)]
+pub fn AES_PMAC<C: BlockCipher>(cipher: &C, message: &[u8]) -> [u8; 16] {
+    let mut mac = Pmac::new(cipher);
+    mac.update(message);
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::Aes128;
+
+    const KEY: [u32; 4] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f];
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn PMAC_AES_128_test() {
+        // Published PMAC1 vectors for AES-128 over prefixes of 00 01 02 ...
+        let cipher = Aes128::new(KEY);
+        let message: Vec<u8> = (0x00..0x20).collect();
+        let vectors: [(usize, [u8; 16]); 5] = [
+            (0, [0x43, 0x99, 0x57, 0x2c, 0xd6, 0xea, 0x53, 0x41, 0xb8, 0xd3, 0x58, 0x76, 0xa7, 0x09, 0x8a, 0xf7]),
+            (3, [0x25, 0x6b, 0xa5, 0x19, 0x3c, 0x1b, 0x99, 0x1b, 0x4d, 0xf0, 0xc5, 0x1f, 0x38, 0x8a, 0x9e, 0x27]),
+            (16, [0xeb, 0xbd, 0x82, 0x2f, 0xa4, 0x58, 0xda, 0xf6, 0xdf, 0xda, 0xd7, 0xc2, 0x7d, 0xa7, 0x63, 0x38]),
+            (20, [0x04, 0x12, 0xca, 0x15, 0x0b, 0xbf, 0x79, 0x05, 0x8d, 0x8c, 0x75, 0xa5, 0x8c, 0x99, 0x3f, 0x55]),
+            (32, [0xe9, 0x7a, 0xc0, 0x4e, 0x9e, 0x5e, 0x33, 0x99, 0xce, 0x53, 0x55, 0xcd, 0x74, 0x07, 0xbc, 0x75]),
+        ];
+
+        for (length, tag) in vectors {
+            let message = &message[..length];
+            assert_eq!(AES_PMAC(&cipher, message), tag);
+
+            for split in 0..=length {
+                let mut mac = Pmac::new(&cipher);
+                mac.update(&message[..split]);
+                mac.update(&message[split..]);
+                assert_eq!(mac.finalize(), tag);
+            }
+        }
+    }
+
+    #[test]
+    fn verify_test() {
+        let cipher = Aes128::new(KEY);
+        let message: Vec<u8> = (0..100).collect();
+        let tag = AES_PMAC(&cipher, &message);
+
+        let mut mac = Pmac::new(&cipher);
+        mac.update(&message);
+        assert_eq!(mac.verify(&tag[..12]), Ok(()));
+
+        let mut mac = Pmac::new(&cipher);
+        mac.update(&message[1..]);
+        assert_eq!(mac.verify(&tag), Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(Pmac::new(&cipher).verify(&[]), Err(CryptoError::InvalidLength));
+    }
+}
\ No newline at end of file
@@ -1,13 +1,16 @@
 use rand::prelude::*;
 
-#[allow(dead_code)]
-pub fn get_salt_256() -> [u32; 8] {
+use crate::key::{SecretKey,SecretKey256};
+
+pub fn get_salt_256() -> SecretKey256 {
+    SecretKey::random()
+}
+
+pub fn get_iv_128() -> [u8; 16] {
     let mut rng = rand::rng();
 
-    let mut salt: [u32; 8] = [0; 8];
-    for i in 0..8 {
-        salt[i] = rng.random::<u32>();
-    }
+    let mut iv: [u8; 16] = [0; 16];
+    rng.fill_bytes(&mut iv);
 
-    salt
+    iv
 }
\ No newline at end of file
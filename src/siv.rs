@@ -0,0 +1,219 @@
+use crate::cipher::BlockCipher;
+use crate::cmac::AES_CMAC;
+use crate::ctr::{CounterLayout,Ctr};
+use crate::error::CryptoError;
+use crate::math::gf128_double;
+use crate::util::constant_time_eq;
+
+/// The most associated data components S2V can take alongside the plain text (RFC 5297 section 7)
+pub const MAX_AAD_COMPONENTS: usize = 126;
+
+/// Description:
+/// AES-SIV deterministic authenticated encryption (RFC 5297). The synthetic IV is the S2V
+/// CMAC over every associated data component and the plain text, so reusing a nonce, or
+/// using none at all, only reveals whether the same message was encrypted twice.
+/// The RFC's double-length key is split into one cipher for S2V and one for CTR mode.
+pub struct Siv<'a, C: BlockCipher> {
+    mac_cipher: &'a C,
+    ctr_cipher: &'a C,
+}
+
+impl<'a, C: BlockCipher> Siv<'a, C> {
+    /// Description:
+    /// Creates an SIV instance from the two halves of the key.
+    ///
+    /// Arguments:
+    /// mac_cipher - (I,REQ) - The cipher keyed with K1, the first half of the key
+    /// ctr_cipher - (I,REQ) - The cipher keyed with K2, the second half of the key
+    ///
+    /// Returns:
+    /// The SIV instance
+    pub fn new(mac_cipher: &'a C, ctr_cipher: &'a C) -> Self {
+        Siv { mac_cipher, ctr_cipher }
+    }
+
+    /// Description:
+    /// Encrypts and authenticates plain text and authenticates each associated data component.
+    /// A nonce, if used, is passed as the last component.
+    ///
+    /// Arguments:
+    /// plain_text - (I,REQ) - The message to encrypt
+    /// aad - (I,REQ) - Up to 126 associated data components, kept distinct from one another
+    ///
+    /// Returns:
+    /// V || cipher text, or InvalidLength for too many components
+    pub fn encrypt(&self, plain_text: Vec<u8>, aad: &[&[u8]]) -> Result<Vec<u8>, CryptoError> {
+        if aad.len() > MAX_AAD_COMPONENTS {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let v = self.s2v(aad, &plain_text);
+        let mut message = plain_text;
+        self.ctr(v, &mut message)?;
+        message.splice(0..0, v);
+
+        Ok(message)
+    }
+
+    /// Description:
+    /// Decrypts and verifies the synthetic IV. The plain text is only returned if it matches.
+    ///
+    /// Arguments:
+    /// message - (I,REQ) - V || cipher text as produced by encrypt
+    /// aad - (I,REQ) - The associated data components used for encryption
+    ///
+    /// Returns:
+    /// The plain text, or AuthenticationFailed if V does not match
+    pub fn decrypt(&self, message: Vec<u8>, aad: &[&[u8]]) -> Result<Vec<u8>, CryptoError> {
+        if message.len() < 16 || aad.len() > MAX_AAD_COMPONENTS {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let mut plain_text = message;
+        let v: [u8; 16] = plain_text[..16].try_into().unwrap();
+        plain_text.drain(..16);
+        self.ctr(v, &mut plain_text)?;
+
+        if !constant_time_eq(&self.s2v(aad, &plain_text), &v) {
+            plain_text.fill(0);
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        Ok(plain_text)
+    }
+
+    // S2V of RFC 5297 section 2.4 with the plain text as the final string
+    fn s2v(&self, aad: &[&[u8]], plain_text: &[u8]) -> [u8; 16] {
+        let mut d = u128::from_be_bytes(AES_CMAC(self.mac_cipher, &[0; 16]));
+        for component in aad {
+            d = gf128_double(d) ^ u128::from_be_bytes(AES_CMAC(self.mac_cipher, component));
+        }
+
+        let t = if plain_text.len() >= 16 {
+            // xorend: D is XORed into the last 16 bytes
+            let mut t = plain_text.to_vec();
+            let n = t.len();
+            for (byte, d) in t[n-16..].iter_mut().zip(d.to_be_bytes()) {
+                *byte ^= d;
+            }
+            t
+        } else {
+            let mut padded: [u8; 16] = [0; 16];
+            padded[..plain_text.len()].copy_from_slice(plain_text);
+            padded[plain_text.len()] = 0x80;
+            (gf128_double(d) ^ u128::from_be_bytes(padded)).to_be_bytes().to_vec()
+        };
+
+        AES_CMAC(self.mac_cipher, &t)
+    }
+
+    // The counter is V with the top bit of its last two 32-bit words cleared, so that
+    // implementations with 64-bit counters agree on where it wraps
+    fn ctr(&self, v: [u8; 16], data: &mut [u8]) -> Result<(), CryptoError> {
+        let q = u128::from_be_bytes(v) & !(1 << 63 | 1 << 31);
+        Ctr::new(self.ctr_cipher, q.to_be_bytes(), CounterLayout::Counter128).apply_keystream(data)
+    }
+}
+
+/// Description:
+/// Encrypts with AES-SIV.
+///
+/// Arguments:
+/// mac_cipher - (I,REQ) - The cipher keyed with the first half of the key
+/// ctr_cipher - (I,REQ) - The cipher keyed with the second half of the key
+/// plain_text - (I,REQ) - The message to encrypt
+/// aad - (I,REQ) - Associated data components, with any nonce last
+///
+/// Returns:
+/// V || cipher text
+#[allow(non_snake_case)]
+pub fn SIV_encrypt<C: BlockCipher>(mac_cipher: &C, ctr_cipher: &C, plain_text: Vec<u8>, aad: &[&[u8]]) -> Result<Vec<u8>, CryptoError> {
+    Siv::new(mac_cipher, ctr_cipher).encrypt(plain_text, aad)
+}
+
+/// Description:
+/// Verifies and decrypts AES-SIV.
+///
+/// Arguments:
+/// mac_cipher - (I,REQ) - The cipher keyed with the first half of the key
+/// ctr_cipher - (I,REQ) - The cipher keyed with the second half of the key
+/// message - (I,REQ) - V || cipher text
+/// aad - (I,REQ) - Associated data components, with any nonce last
+///
+/// Returns:
+/// The plain text, or AuthenticationFailed if V does not match
+#[allow(non_snake_case)]
+pub fn SIV_decrypt<C: BlockCipher>(mac_cipher: &C, ctr_cipher: &C, message: Vec<u8>, aad: &[&[u8]]) -> Result<Vec<u8>, CryptoError> {
+    Siv::new(mac_cipher, ctr_cipher).decrypt(message, aad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::Aes128;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn SIV_deterministic_test() {
+        // RFC 5297 appendix A.1
+        let mac_cipher = Aes128::new([0xfffefdfc, 0xfbfaf9f8, 0xf7f6f5f4, 0xf3f2f1f0]);
+        let ctr_cipher = Aes128::new([0xf0f1f2f3, 0xf4f5f6f7, 0xf8f9fafb, 0xfcfdfeff]);
+        let aad: [u8; 24] = [0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27];
+        let plain_text: [u8; 14] = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee];
+        let actual: [u8; 30] = [0x85, 0x63, 0x2d, 0x07, 0xc6, 0xe8, 0xf3, 0x7f, 0x95, 0x0a, 0xcd, 0x32, 0x0a, 0x2e, 0xcc, 0x93, 0x40, 0xc0, 0x2b, 0x96, 0x90, 0xc4, 0xdc, 0x04, 0xda, 0xef, 0x7f, 0x6a, 0xfe, 0x5c];
+
+        let res = SIV_encrypt(&mac_cipher, &ctr_cipher, plain_text.to_vec(), &[&aad]).unwrap();
+        assert_eq!(res, actual);
+
+        let res = SIV_decrypt(&mac_cipher, &ctr_cipher, res, &[&aad]).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn SIV_nonce_test() {
+        // RFC 5297 appendix A.2: two associated data components and a nonce
+        let mac_cipher = Aes128::new([0x7f7e7d7c, 0x7b7a7978, 0x77767574, 0x73727170]);
+        let ctr_cipher = Aes128::new([0x40414243, 0x44454647, 0x48494a4b, 0x4c4d4e4f]);
+        let aad_1: [u8; 40] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0xde, 0xad, 0xda, 0xda, 0xde, 0xad, 0xda, 0xda, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00];
+        let aad_2: [u8; 10] = [0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80, 0x90, 0xa0];
+        let nonce: [u8; 16] = [0x09, 0xf9, 0x11, 0x02, 0x9d, 0x74, 0xe3, 0x5b, 0xd8, 0x41, 0x56, 0xc5, 0x63, 0x56, 0x88, 0xc0];
+        let plain_text: [u8; 47] = [0x74, 0x68, 0x69, 0x73, 0x20, 0x69, 0x73, 0x20, 0x73, 0x6f, 0x6d, 0x65, 0x20, 0x70, 0x6c, 0x61, 0x69, 0x6e, 0x74, 0x65, 0x78, 0x74, 0x20, 0x74, 0x6f, 0x20, 0x65, 0x6e, 0x63, 0x72, 0x79, 0x70, 0x74, 0x20, 0x75, 0x73, 0x69, 0x6e, 0x67, 0x20, 0x53, 0x49, 0x56, 0x2d, 0x41, 0x45, 0x53];
+        let actual: [u8; 63] = [0x7b, 0xdb, 0x6e, 0x3b, 0x43, 0x26, 0x67, 0xeb, 0x06, 0xf4, 0xd1, 0x4b, 0xff, 0x2f, 0xbd, 0x0f, 0xcb, 0x90, 0x0f, 0x2f, 0xdd, 0xbe, 0x40, 0x43, 0x26, 0x60, 0x19, 0x65, 0xc8, 0x89, 0xbf, 0x17, 0xdb, 0xa7, 0x7c, 0xeb, 0x09, 0x4f, 0xa6, 0x63, 0xb7, 0xa3, 0xf7, 0x48, 0xba, 0x8a, 0xf8, 0x29, 0xea, 0x64, 0xad, 0x54, 0x4a, 0x27, 0x2e, 0x9c, 0x48, 0x5b, 0x62, 0xa3, 0xfd, 0x5c, 0x0d];
+
+        let res = SIV_encrypt(&mac_cipher, &ctr_cipher, plain_text.to_vec(), &[&aad_1, &aad_2, &nonce]).unwrap();
+        assert_eq!(res, actual);
+
+        let res = SIV_decrypt(&mac_cipher, &ctr_cipher, res, &[&aad_1, &aad_2, &nonce]).unwrap();
+        assert_eq!(res, plain_text);
+
+        // The components are not simply concatenated
+        let mut joined = aad_1.to_vec();
+        joined.extend(aad_2);
+        let res = SIV_decrypt(&mac_cipher, &ctr_cipher, actual.to_vec(), &[&joined, &nonce]);
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+    }
+
+    #[test]
+    fn authentication_failure_test() {
+        let cipher = Aes128::new([0; 4]);
+        let message = SIV_encrypt(&cipher, &cipher, vec![0; 20], &[b"header"]).unwrap();
+
+        // A flipped bit in the synthetic IV
+        let mut tampered = message.clone();
+        tampered[0] ^= 1;
+        let res = SIV_decrypt(&cipher, &cipher, tampered, &[b"header"]);
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+
+        let mut tampered = message.clone();
+        tampered[16] ^= 1;
+        let res = SIV_decrypt(&cipher, &cipher, tampered, &[b"header"]);
+        assert_eq!(res, Err(CryptoError::AuthenticationFailed));
+
+        assert_eq!(SIV_decrypt(&cipher, &cipher, message.clone(), &[]), Err(CryptoError::AuthenticationFailed));
+        assert_eq!(SIV_decrypt(&cipher, &cipher, message[..15].to_vec(), &[]), Err(CryptoError::InvalidLength));
+
+        let aad: Vec<&[u8]> = vec![b""; MAX_AAD_COMPONENTS + 1];
+        assert_eq!(SIV_encrypt(&cipher, &cipher, vec![], &aad), Err(CryptoError::InvalidLength));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,441 @@
+use std::io::{self,Read,Write};
+
+use crate::cbc::{CBC_decrypt_blocks,CBC_encrypt_blocks};
+use crate::cipher::{BlockCipher,Cfb,Cfb8,FeedbackMode,Ofb};
+use crate::ctr::{CounterLayout,Ctr};
+use crate::error::CryptoError;
+use crate::padding::Padding;
+
+/// Largest number of bytes taken from one write or read, which bounds the buffering
+const CHUNK_SIZE: usize = 8192;
+
+/// Description:
+/// The mode a streaming adapter runs and its starting value. ECB and CBC are padded at
+/// finalization; the other modes are length preserving and ignore the padding scheme.
+/// Authenticated modes are not offered because they cannot release plain text before
+/// the whole message has been verified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamMode {
+    /// ECB.
+    Ecb,
+    /// CBC with the given IV.
+    Cbc([u8; 16]),
+    /// CTR from the given initial counter block.
+    Ctr([u8; 16], CounterLayout),
+    /// CFB with a 128-bit segment and the given IV.
+    Cfb([u8; 16]),
+    /// CFB with an 8-bit segment and the given IV.
+    Cfb8([u8; 16]),
+    /// OFB with the given IV.
+    Ofb([u8; 16]),
+}
+
+// The running state of a mode. Block modes are only ever given whole blocks.
+enum Engine<'a, C: BlockCipher> {
+    Ecb(&'a C),
+    Cbc(&'a C, [u8; 16]),
+    Ctr(Ctr<'a, C>),
+    Cfb(Cfb<'a, C>),
+    Cfb8(Cfb8<'a, C>),
+    Ofb(Ofb<'a, C>),
+}
+
+impl<'a, C: BlockCipher> Engine<'a, C> {
+    fn new(cipher: &'a C, mode: StreamMode) -> Self {
+        match mode {
+            StreamMode::Ecb => Engine::Ecb(cipher),
+            StreamMode::Cbc(iv) => Engine::Cbc(cipher, iv),
+            StreamMode::Ctr(counter_block, layout) => Engine::Ctr(Ctr::new(cipher, counter_block, layout)),
+            StreamMode::Cfb(iv) => Engine::Cfb(Cfb::new(cipher, iv)),
+            StreamMode::Cfb8(iv) => Engine::Cfb8(Cfb8::new(cipher, iv)),
+            StreamMode::Ofb(iv) => Engine::Ofb(Ofb::new(cipher, iv)),
+        }
+    }
+
+    fn is_block_mode(&self) -> bool {
+        matches!(self, Engine::Ecb(_) | Engine::Cbc(..))
+    }
+
+    // Only CTR can fail, once its counter runs out
+    fn encrypt(&mut self, data: &mut [u8]) -> Result<(), CryptoError> {
+        match self {
+            Engine::Ecb(cipher) => cipher.encrypt_blocks(data.as_chunks_mut::<16>().0),
+            Engine::Cbc(cipher, previous) => {
+                if data.is_empty() {
+                    return Ok(());
+                }
+                CBC_encrypt_blocks(*cipher, data, *previous)?;
+                *previous = data[data.len()-16..].try_into().unwrap();
+            }
+            Engine::Ctr(ctr) => ctr.apply_keystream(data)?,
+            Engine::Cfb(mode) => mode.encrypt(data),
+            Engine::Cfb8(mode) => mode.encrypt(data),
+            Engine::Ofb(mode) => mode.encrypt(data),
+        }
+
+        Ok(())
+    }
+
+    fn decrypt(&mut self, data: &mut [u8]) -> Result<(), CryptoError> {
+        match self {
+            Engine::Ecb(cipher) => cipher.decrypt_blocks(data.as_chunks_mut::<16>().0),
+            Engine::Cbc(cipher, previous) => {
+                if data.is_empty() {
+                    return Ok(());
+                }
+                let last: [u8; 16] = data[data.len()-16..].try_into().unwrap();
+                CBC_decrypt_blocks(*cipher, data, *previous)?;
+                *previous = last;
+            }
+            Engine::Ctr(ctr) => ctr.apply_keystream(data)?,
+            Engine::Cfb(mode) => mode.decrypt(data),
+            Engine::Cfb8(mode) => mode.decrypt(data),
+            Engine::Ofb(mode) => mode.decrypt(data),
+        }
+
+        Ok(())
+    }
+}
+
+fn to_io_error(error: CryptoError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Description:
+/// Encrypts everything written to it and passes the cipher text on to an inner writer.
+/// Block modes hold back a partial block until finish, which pads and writes it, so
+/// finish must be called; dropping the writer loses the end of the message. Data is
+/// encrypted once it is accepted, and cipher text the inner writer did not take is
+/// written again before anything else.
+pub struct EncryptingWriter<'a, W: Write, C: BlockCipher, P: Padding> {
+    inner: W,
+    engine: Engine<'a, C>,
+    padding: &'a P,
+    buffer: Vec<u8>,
+    output: Vec<u8>,
+}
+
+impl<'a, W: Write, C: BlockCipher, P: Padding> EncryptingWriter<'a, W, C, P> {
+    /// Description:
+    /// Wraps a writer.
+    ///
+    /// Arguments:
+    /// inner - (I,REQ) - Where the cipher text is written
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// mode - (I,REQ) - The mode and its IV or counter block
+    /// padding - (I,REQ) - The padding scheme applied at finish for ECB and CBC
+    ///
+    /// Returns:
+    /// The encrypting writer
+    pub fn new(inner: W, cipher: &'a C, mode: StreamMode, padding: &'a P) -> Self {
+        EncryptingWriter { inner, engine: Engine::new(cipher, mode), padding, buffer: Vec::with_capacity(CHUNK_SIZE + 16), output: Vec::with_capacity(CHUNK_SIZE + 16) }
+    }
+
+    // Writes out the cipher text, dropping each part as soon as the inner writer has taken it
+    fn write_output(&mut self) -> io::Result<()> {
+        while !self.output.is_empty() {
+            match self.inner.write(&self.output) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => { self.output.drain(..n); }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Description:
+    /// Pads and encrypts the buffered end of the message, writes it and flushes the inner writer.
+    ///
+    /// Returns:
+    /// The inner writer, an error from it, or InvalidData wrapping the CryptoError if the
+    /// padding scheme cannot pad the message
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_output()?;
+        if self.engine.is_block_mode() {
+            self.padding.pad_in_place(&mut self.buffer, 16).map_err(to_io_error)?;
+            self.engine.encrypt(&mut self.buffer).map_err(to_io_error)?;
+            self.inner.write_all(&self.buffer)?;
+        }
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+impl<'a, W: Write, C: BlockCipher, P: Padding> Write for EncryptingWriter<'a, W, C, P> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_output()?;
+
+        let data = &data[..data.len().min(CHUNK_SIZE)];
+        let mut ready = self.buffer.len() + data.len();
+        if self.engine.is_block_mode() {
+            ready -= ready % 16;
+        }
+        self.buffer.extend_from_slice(data);
+        self.output.extend_from_slice(&self.buffer[..ready]);
+        if let Err(error) = self.engine.encrypt(&mut self.output) {
+            self.output.clear();
+            self.buffer.truncate(self.buffer.len() - data.len());
+            return Err(to_io_error(error));
+        }
+        self.buffer.drain(..ready);
+
+        // The data has been taken, so a failed write is reported by the next call instead
+        let _ = self.write_output();
+
+        Ok(data.len())
+    }
+
+    // A partial block cannot be encrypted before finish, so only the cipher text is flushed
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_output()?;
+        self.inner.flush()
+    }
+}
+
+/// Description:
+/// Reads cipher text from an inner reader and returns the plain text. Block modes hold back
+/// the last whole block until the end of the input, where it is unpadded; cipher text that
+/// is not a whole number of blocks or has malformed padding is reported as an InvalidData
+/// error wrapping the CryptoError, and every later read returns the same error. Truncation
+/// cannot be detected in the length preserving modes.
+pub struct DecryptingReader<'a, R: Read, C: BlockCipher, P: Padding> {
+    inner: R,
+    engine: Engine<'a, C>,
+    padding: &'a P,
+    chunk: Vec<u8>,
+    pending: Vec<u8>,
+    output: Vec<u8>,
+    position: usize,
+    finished: bool,
+    failed: Option<CryptoError>,
+}
+
+impl<'a, R: Read, C: BlockCipher, P: Padding> DecryptingReader<'a, R, C, P> {
+    /// Description:
+    /// Wraps a reader.
+    ///
+    /// Arguments:
+    /// inner - (I,REQ) - Where the cipher text is read from
+    /// cipher - (I,REQ) - The keyed block cipher
+    /// mode - (I,REQ) - The mode and its IV or counter block, as used for encryption
+    /// padding - (I,REQ) - The padding scheme used for encryption with ECB and CBC
+    ///
+    /// Returns:
+    /// The decrypting reader
+    pub fn new(inner: R, cipher: &'a C, mode: StreamMode, padding: &'a P) -> Self {
+        DecryptingReader { inner, engine: Engine::new(cipher, mode), padding, chunk: vec![0; CHUNK_SIZE], pending: Vec::new(), output: Vec::new(), position: 0, finished: false, failed: None }
+    }
+
+    /// Description:
+    /// Unwraps the inner reader.
+    ///
+    /// Returns:
+    /// The inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // Reads the next chunk of cipher text and decrypts as much of it as can be released.
+    // The cipher text is consumed either way, so a CryptoError ends the stream.
+    fn fill(&mut self) -> io::Result<()> {
+        let n = match self.inner.read(&mut self.chunk) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        self.output.clear();
+        self.position = 0;
+        self.finished = n == 0;
+
+        if let Err(error) = self.release(n) {
+            self.output.clear();
+            self.failed = Some(error);
+            return Err(to_io_error(error));
+        }
+
+        Ok(())
+    }
+
+    // Decrypts the first n bytes of the chunk into the output, less any held back blocks
+    fn release(&mut self, n: usize) -> Result<(), CryptoError> {
+        if !self.engine.is_block_mode() {
+            self.output.extend_from_slice(&self.chunk[..n]);
+            return self.engine.decrypt(&mut self.output);
+        }
+
+        self.pending.extend_from_slice(&self.chunk[..n]);
+        if self.finished {
+            if !self.pending.len().is_multiple_of(16) {
+                return Err(CryptoError::InvalidLength);
+            }
+            self.engine.decrypt(&mut self.pending)?;
+            self.padding.unpad_in_place(&mut self.pending, 16)?;
+            std::mem::swap(&mut self.output, &mut self.pending);
+            return Ok(());
+        }
+
+        // A trailing partial block means more cipher text follows, so every whole block
+        // before it can be released; otherwise the last block may be the padded one
+        let held = match self.pending.len() % 16 {
+            0 => self.pending.len().min(16),
+            partial => partial,
+        };
+        let ready = self.pending.len() - held;
+        self.engine.decrypt(&mut self.pending[..ready])?;
+        self.output.extend(self.pending.drain(..ready));
+
+        Ok(())
+    }
+}
+
+impl<'a, R: Read, C: BlockCipher, P: Padding> Read for DecryptingReader<'a, R, C, P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.output.len() {
+            if let Some(error) = self.failed {
+                return Err(to_io_error(error));
+            }
+            if self.finished {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+
+        let n = buf.len().min(self.output.len() - self.position);
+        buf[..n].copy_from_slice(&self.output[self.position..self.position+n]);
+        self.position += n;
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbc::CBC_encrypt;
+    use crate::cipher::{Aes128,CFB8_encrypt,CFB_encrypt,ECB_encrypt,OFB_encrypt};
+    use crate::ctr::CTR_encrypt;
+    use crate::padding::{Iso7816,NoPadding,Pkcs7};
+
+    const KEY: [u32; 4] = [0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c];
+    const IV: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+
+    // Writes in uneven pieces, then reads back through small reads
+    fn round_trip<P: Padding>(mode: StreamMode, padding: &P, plain_text: &[u8]) -> Vec<u8> {
+        let cipher = Aes128::new(KEY);
+        let mut writer = EncryptingWriter::new(Vec::new(), &cipher, mode, padding);
+        for piece in plain_text.chunks(1000) {
+            let (first, second) = piece.split_at(piece.len() / 3);
+            writer.write_all(first).unwrap();
+            writer.write_all(second).unwrap();
+        }
+        let cipher_text = writer.finish().unwrap();
+
+        let mut reader = DecryptingReader::new(cipher_text.as_slice(), &cipher, mode, padding);
+        let mut res = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            res.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(res, plain_text);
+
+        cipher_text
+    }
+
+    #[test]
+    fn stream_modes_test() {
+        let cipher = Aes128::new(KEY);
+        let counter_block = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff];
+
+        for length in [0, 1, 15, 16, 17, 32, 20000] {
+            let plain_text: Vec<u8> = (0..length).map(|i| (i * 7) as u8).collect();
+
+            let res = round_trip(StreamMode::Ecb, &Pkcs7, &plain_text);
+            assert_eq!(res, ECB_encrypt(&cipher, plain_text.clone(), &Pkcs7).unwrap());
+            let res = round_trip(StreamMode::Cbc(IV), &Iso7816, &plain_text);
+            assert_eq!(res, CBC_encrypt(&cipher, plain_text.clone(), IV, &Iso7816).unwrap());
+            let res = round_trip(StreamMode::Ctr(counter_block, CounterLayout::Counter128), &Pkcs7, &plain_text);
+            assert_eq!(res, CTR_encrypt(&cipher, plain_text.clone(), counter_block, CounterLayout::Counter128).unwrap());
+            let res = round_trip(StreamMode::Cfb(IV), &Pkcs7, &plain_text);
+            assert_eq!(res, CFB_encrypt(&cipher, plain_text.clone(), IV));
+            let res = round_trip(StreamMode::Cfb8(IV), &Pkcs7, &plain_text);
+            assert_eq!(res, CFB8_encrypt(&cipher, plain_text.clone(), IV));
+            let res = round_trip(StreamMode::Ofb(IV), &Pkcs7, &plain_text);
+            assert_eq!(res, OFB_encrypt(&cipher, plain_text.clone(), IV));
+        }
+
+        let plain_text = vec![0x5a; 64];
+        let res = round_trip(StreamMode::Cbc(IV), &NoPadding, &plain_text);
+        assert_eq!(res, CBC_encrypt(&cipher, plain_text, IV, &NoPadding).unwrap());
+    }
+
+    #[test]
+    fn stream_error_test() {
+        let cipher = Aes128::new(KEY);
+        let crypto_error = |e: io::Error| *e.get_ref().unwrap().downcast_ref::<CryptoError>().unwrap();
+
+        let mut writer = EncryptingWriter::new(Vec::new(), &cipher, StreamMode::Cbc(IV), &NoPadding);
+        writer.write_all(&[0; 20]).unwrap();
+        assert_eq!(writer.finish().map_err(crypto_error).err(), Some(CryptoError::InvalidLength));
+
+        let cipher_text = CBC_encrypt(&cipher, (0..40).collect(), IV, &Pkcs7).unwrap();
+        let mut res = Vec::new();
+        let mut reader = DecryptingReader::new(&cipher_text[..47], &cipher, StreamMode::Cbc(IV), &Pkcs7);
+        assert_eq!(reader.read_to_end(&mut res).map_err(crypto_error), Err(CryptoError::InvalidLength));
+        assert_eq!(reader.read(&mut [0; 16]).map_err(crypto_error), Err(CryptoError::InvalidLength));
+
+        let mut reader = DecryptingReader::new(&cipher_text[..0], &cipher, StreamMode::Ecb, &Pkcs7);
+        assert!(reader.read_to_end(&mut res).is_err());
+
+        let mut tampered = cipher_text.clone();
+        // Flips the last padding byte through the previous cipher text block
+        tampered[31] ^= 0xff;
+        let mut reader = DecryptingReader::new(tampered.as_slice(), &cipher, StreamMode::Cbc(IV), &Pkcs7);
+        assert_eq!(reader.read_to_end(&mut res).map_err(crypto_error), Err(CryptoError::InvalidPadding));
+        assert_eq!(reader.read(&mut [0; 16]).map_err(crypto_error), Err(CryptoError::InvalidPadding));
+    }
+
+    // Fails the given number of writes, then takes everything
+    struct FailingWriter {
+        failures: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            self.written.extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_retry_test() {
+        let cipher = Aes128::new(KEY);
+        let plain_text: Vec<u8> = (0..100).collect();
+        let inner = FailingWriter { failures: 2, written: Vec::new() };
+        let mut writer = EncryptingWriter::new(inner, &cipher, StreamMode::Cbc(IV), &Pkcs7);
+
+        // The first failure comes after the data was taken, the second before any is
+        assert_eq!(writer.write(&plain_text[..40]).unwrap(), 40);
+        assert!(writer.write(&plain_text[40..]).is_err());
+        writer.write_all(&plain_text[40..]).unwrap();
+
+        let res = writer.finish().unwrap().written;
+        let actual = CBC_encrypt(&cipher, plain_text, IV, &Pkcs7).unwrap();
+        assert_eq!(res, actual);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,223 @@
+use crate::math::byte_multiplication;
+use crate::util::{INV_SBOX,SBOX};
+
+/// Encryption tables: TE[k][x] is the MixColumns column for S-box output S[x] in row k, rotated into place
+static TE: [[u32; 256]; 4] = generate_te();
+/// Decryption tables: TD[k][x] is the InvMixColumns column for inverse S-box output S^-1[x] in row k
+static TD: [[u32; 256]; 4] = generate_td();
+
+/// Description:
+/// Builds the encryption T-tables from SBOX, fusing SubBytes and MixColumns.
+///
+/// Returns:
+/// TE[0][x] = (2*S[x], S[x], S[x], 3*S[x]) and TE[k] = TE[0] rotated right by 8k bits
+const fn generate_te() -> [[u32; 256]; 4] {
+    let mut table = [[0; 256]; 4];
+    let mut i = 0;
+    while i < 256 {
+        let s = SBOX[i];
+        let word = (byte_multiplication(s, 2) as u32) << 24 | (s as u32) << 16 | (s as u32) << 8 | byte_multiplication(s, 3) as u32;
+        table[0][i] = word;
+        table[1][i] = word.rotate_right(8);
+        table[2][i] = word.rotate_right(16);
+        table[3][i] = word.rotate_right(24);
+        i += 1;
+    }
+
+    table
+}
+
+/// Description:
+/// Builds the decryption T-tables from INV_SBOX, fusing InvSubBytes and InvMixColumns.
+///
+/// Returns:
+/// TD[0][x] = (14*S'[x], 9*S'[x], 13*S'[x], 11*S'[x]) and TD[k] = TD[0] rotated right by 8k bits
+const fn generate_td() -> [[u32; 256]; 4] {
+    let mut table = [[0; 256]; 4];
+    let mut i = 0;
+    while i < 256 {
+        let s = INV_SBOX[i];
+        let word = (byte_multiplication(s, 14) as u32) << 24 | (byte_multiplication(s, 9) as u32) << 16 | (byte_multiplication(s, 13) as u32) << 8 | byte_multiplication(s, 11) as u32;
+        table[0][i] = word;
+        table[1][i] = word.rotate_right(8);
+        table[2][i] = word.rotate_right(16);
+        table[3][i] = word.rotate_right(24);
+        i += 1;
+    }
+
+    table
+}
+
+/// Description:
+/// Converts round keys from the block layout into one big-endian word per column.
+///
+/// Arguments:
+/// key_schedule - (I,REQ) - Round keys as kept by Aes, one block per round
+///
+/// Returns:
+/// The same round keys as columns
+pub fn round_key_words(key_schedule: &[[u8; 16]]) -> Vec<[u32; 4]> {
+    let mut words = Vec::with_capacity(key_schedule.len());
+    for round_key in key_schedule {
+        let mut word: [u32; 4] = [0; 4];
+        for j in 0..4 {
+            word[j] = u32::from_be_bytes(round_key[4*j..4*j+4].try_into().unwrap());
+        }
+        words.push(word);
+    }
+
+    words
+}
+
+/// Description:
+/// Encrypts a block with four table lookups per column and round.
+///
+/// Arguments:
+/// round_keys - (I,REQ) - Encryption round keys from round_key_words
+/// block - (I,REQ) - The plain text block
+///
+/// Returns:
+/// The cipher text block
+pub fn encrypt_block(round_keys: &[[u32; 4]], block: [u8; 16]) -> [u8; 16] {
+    let nr = round_keys.len() - 1;
+    let mut s = load(block, round_keys[0]);
+
+    for round_key in &round_keys[1..nr] {
+        let mut t: [u32; 4] = [0; 4];
+        for j in 0..4 {
+            t[j] = TE[0][(s[j] >> 24) as usize]
+                ^ TE[1][(s[(j+1)%4] >> 16 & 0xFF) as usize]
+                ^ TE[2][(s[(j+2)%4] >> 8 & 0xFF) as usize]
+                ^ TE[3][(s[(j+3)%4] & 0xFF) as usize]
+                ^ round_key[j];
+        }
+        s = t;
+    }
+
+    let mut t: [u32; 4] = [0; 4];
+    for j in 0..4 {
+        t[j] = u32::from_be_bytes([
+            SBOX[(s[j] >> 24) as usize],
+            SBOX[(s[(j+1)%4] >> 16 & 0xFF) as usize],
+            SBOX[(s[(j+2)%4] >> 8 & 0xFF) as usize],
+            SBOX[(s[(j+3)%4] & 0xFF) as usize],
+        ]) ^ round_keys[nr][j];
+    }
+
+    store(t)
+}
+
+/// Description:
+/// Decrypts a block with the equivalent inverse cipher and four table lookups per column and round.
+///
+/// Arguments:
+/// round_keys - (I,REQ) - Decryption round keys in the order they are applied, InvMixColumns already applied to the middle ones
+/// block - (I,REQ) - The cipher text block
+///
+/// Returns:
+/// The plain text block
+pub fn decrypt_block(round_keys: &[[u32; 4]], block: [u8; 16]) -> [u8; 16] {
+    let nr = round_keys.len() - 1;
+    let mut s = load(block, round_keys[0]);
+
+    for round_key in &round_keys[1..nr] {
+        let mut t: [u32; 4] = [0; 4];
+        for j in 0..4 {
+            t[j] = TD[0][(s[j] >> 24) as usize]
+                ^ TD[1][(s[(j+3)%4] >> 16 & 0xFF) as usize]
+                ^ TD[2][(s[(j+2)%4] >> 8 & 0xFF) as usize]
+                ^ TD[3][(s[(j+1)%4] & 0xFF) as usize]
+                ^ round_key[j];
+        }
+        s = t;
+    }
+
+    let mut t: [u32; 4] = [0; 4];
+    for j in 0..4 {
+        t[j] = u32::from_be_bytes([
+            INV_SBOX[(s[j] >> 24) as usize],
+            INV_SBOX[(s[(j+3)%4] >> 16 & 0xFF) as usize],
+            INV_SBOX[(s[(j+2)%4] >> 8 & 0xFF) as usize],
+            INV_SBOX[(s[(j+1)%4] & 0xFF) as usize],
+        ]) ^ round_keys[nr][j];
+    }
+
+    store(t)
+}
+
+fn load(block: [u8; 16], round_key: [u32; 4]) -> [u32; 4] {
+    let mut s: [u32; 4] = [0; 4];
+    for j in 0..4 {
+        s[j] = u32::from_be_bytes(block[4*j..4*(j+1)].try_into().unwrap()) ^ round_key[j];
+    }
+
+    s
+}
+
+fn store(s: [u32; 4]) -> [u8; 16] {
+    let mut block: [u8; 16] = [0; 16];
+    for j in 0..4 {
+        block[4*j..4*(j+1)].copy_from_slice(&s[j].to_be_bytes());
+    }
+
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes,Aes128};
+    use rand::prelude::*;
+
+    fn cross_check<const NK: usize>() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let mut key: [u32; NK] = [0; NK];
+            for word in key.iter_mut() {
+                *word = rng.random::<u32>();
+            }
+            let mut block: [u8; 16] = [0; 16];
+            rng.fill_bytes(&mut block);
+
+            let cipher = Aes::new(key);
+            let mut expected = block;
+            cipher.encrypt_state(&mut expected);
+
+            let round_keys = round_key_words(cipher.key_schedule());
+            let res = encrypt_block(&round_keys, block);
+            assert_eq!(res, expected);
+
+            let inv_round_keys = round_key_words(cipher.inv_key_schedule());
+            let res = decrypt_block(&inv_round_keys, res);
+            assert_eq!(res, block);
+        }
+    }
+
+    #[test]
+    fn generate_te_test() {
+        // First MixColumns column of the FIPS-197 example: S[0x19] = 0xd4
+        assert_eq!(TE[0][0x19], 0xb3d4d467);
+        assert_eq!(TE[1][0x19], 0x67b3d4d4);
+        for i in 0..256 {
+            assert_eq!(TE[0][i] >> 16 & 0xFF, SBOX[i] as u32);
+            assert_eq!(TD[0][i] >> 24, byte_multiplication(INV_SBOX[i], 14) as u32);
+        }
+    }
+
+    #[test]
+    fn encrypt_block_test() {
+        let key: [u32; 4] = [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f];
+        let round_keys = round_key_words(Aes128::new(key).key_schedule());
+        let block: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let res = encrypt_block(&round_keys, block);
+        let actual: [u8; 16] = [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a];
+        assert_eq!(res, actual);
+    }
+
+    #[test]
+    fn cross_check_test() {
+        cross_check::<4>();
+        cross_check::<6>();
+        cross_check::<8>();
+    }
+}
\ No newline at end of file
@@ -1,9 +1,11 @@
-use crate::math::{byte_inverse,byte_remainder,AES_IRREDUCIBLE_POLYNOMIAL};
+use crate::math::{byte_inverse,byte_remainder,constant_time_byte_inverse,AES_IRREDUCIBLE_POLYNOMIAL};
 
 use std::io::{self, Write};
 
 pub const RCON: [u32; 10] = [0x1000000, 0x2000000, 0x4000000, 0x8000000, 0x10000000, 0x20000000, 0x40000000, 0x80000000, 0x1b000000, 0x36000000];
+#[cfg_attr(feature = "constant-time", allow(dead_code))]
 pub const SBOX: [u8; 256] = [0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x1, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76, 0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0, 0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15, 0x4, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x5, 0x9a, 0x7, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75, 0x9, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84, 0x53, 0xd1, 0x0, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf, 0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x2, 0x7f, 0x50, 0x3c, 0x9f, 0xa8, 0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2, 0xcd, 0xc, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73, 0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0xb, 0xdb, 0xe0, 0x32, 0x3a, 0xa, 0x49, 0x6, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79, 0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x8, 0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a, 0x70, 0x3e, 0xb5, 0x66, 0x48, 0x3, 0xf6, 0xe, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e, 0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf, 0x8c, 0xa1, 0x89, 0xd, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0xf, 0xb0, 0x54, 0xbb, 0x16];
+#[cfg_attr(feature = "constant-time", allow(dead_code))]
 pub const INV_SBOX: [u8; 256] = [0x52, 0x9, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb, 0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb, 0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0xb, 0x42, 0xfa, 0xc3, 0x4e, 0x8, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25, 0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92, 0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84, 0x90, 0xd8, 0xab, 0x0, 0x8c, 0xbc, 0xd3, 0xa, 0xf7, 0xe4, 0x58, 0x5, 0xb8, 0xb3, 0x45, 0x6, 0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0xf, 0x2, 0xc1, 0xaf, 0xbd, 0x3, 0x1, 0x13, 0x8a, 0x6b, 0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73, 0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e, 0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0xe, 0xaa, 0x18, 0xbe, 0x1b, 0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4, 0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x7, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f, 0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0xd, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef, 0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61, 0x17, 0x2b, 0x4, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0xc, 0x7d];
 
 /// Description:
@@ -11,9 +13,8 @@ pub const INV_SBOX: [u8; 256] = [0x52, 0x9, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38,
 #[allow(dead_code)]
 pub fn generate_s_box() -> [u8; 256] {
     let mut sbox: [u8; 256] = [0; 256];
-    sbox[0] = 0x63;
-    for i in 1..=255 {
-        let inverse = byte_inverse(i);
+    for i in 0..=255 {
+        let inverse = byte_inverse(i).unwrap_or(0); // AES treats 0 as its own inverse
         sbox[i as usize] = inverse ^ circular_left_bit_shift(inverse, 1) ^ circular_left_bit_shift(inverse, 2) ^ circular_left_bit_shift(inverse, 3) ^ circular_left_bit_shift(inverse, 4) ^ 0x63;
     }
 
@@ -39,7 +40,7 @@ pub fn generate_inv_s_box() -> [u8; 256] {
 pub fn generate_r_con() {
     print!("0x{:x}000000, ", 1);
     for i in 1..10 {
-        print!("0x{:x}000000, ", byte_remainder(1 << i, AES_IRREDUCIBLE_POLYNOMIAL));
+        print!("0x{:x}000000, ", byte_remainder(1 << i, AES_IRREDUCIBLE_POLYNOMIAL).unwrap());
     }
     io::stdout().flush().unwrap();
 }
@@ -82,28 +83,144 @@ pub fn rot_word(word: u32) -> u32 {
 pub fn sub_word(word: u32) -> u32 {
     let mut res: u32 = 0;
     for i in 0..4 {
-        res ^= (SBOX[(word >> (8*i) & 0xFF) as usize] as u32) << (8*i);
+        res ^= (sub_byte((word >> (8*i) & 0xFF) as u8) as u32) << (8*i);
     }
 
     res
 }
 
-pub fn message_to_block(message: &[u8]) -> [[u8; 4]; 4] {
-    let mut block: [[u8; 4]; 4] = [[0; 4]; 4];
-    for j in 0..4 {
-        for i in 0..4 {
-            block[i][j] = message[4*j+i]
-        }
+/// Description:
+/// The AES S-box. With the constant-time feature it is computed rather than looked up,
+/// so no memory access depends on the (secret) input.
+///
+/// Arguments:
+/// byte - (I,REQ) - The byte to substitute
+///
+/// Returns:
+/// SBOX[byte]
+#[cfg(not(feature = "constant-time"))]
+pub fn sub_byte(byte: u8) -> u8 {
+    SBOX[byte as usize]
+}
+
+#[cfg(feature = "constant-time")]
+pub fn sub_byte(byte: u8) -> u8 {
+    constant_time_sub_byte(byte)
+}
+
+/// Description:
+/// The inverse AES S-box, computed rather than looked up with the constant-time feature.
+///
+/// Arguments:
+/// byte - (I,REQ) - The byte to substitute
+///
+/// Returns:
+/// INV_SBOX[byte]
+#[cfg(not(feature = "constant-time"))]
+pub fn inv_sub_byte(byte: u8) -> u8 {
+    INV_SBOX[byte as usize]
+}
+
+#[cfg(feature = "constant-time")]
+pub fn inv_sub_byte(byte: u8) -> u8 {
+    constant_time_inv_sub_byte(byte)
+}
+
+/// Description:
+/// Table-free S-box: the inverse in GF(2^8) followed by the affine transform (FIPS-197 section 5.1.1).
+///
+/// Arguments:
+/// byte - (I,REQ) - The byte to substitute
+///
+/// Returns:
+/// SBOX[byte]
+#[allow(dead_code)]
+pub fn constant_time_sub_byte(byte: u8) -> u8 {
+    let inverse = constant_time_byte_inverse(byte);
+    inverse ^ circular_left_bit_shift(inverse, 1) ^ circular_left_bit_shift(inverse, 2) ^ circular_left_bit_shift(inverse, 3) ^ circular_left_bit_shift(inverse, 4) ^ 0x63
+}
+
+/// Description:
+/// Table-free inverse S-box: the inverse affine transform followed by the inverse in GF(2^8).
+///
+/// Arguments:
+/// byte - (I,REQ) - The byte to substitute
+///
+/// Returns:
+/// INV_SBOX[byte]
+#[allow(dead_code)]
+pub fn constant_time_inv_sub_byte(byte: u8) -> u8 {
+    let affine = circular_left_bit_shift(byte, 1) ^ circular_left_bit_shift(byte, 3) ^ circular_left_bit_shift(byte, 6) ^ 0x05;
+    constant_time_byte_inverse(affine)
+}
+
+/// Description:
+/// Compares two byte strings without branching on their contents.
+/// The running time only depends on the lengths.
+///
+/// Arguments:
+/// a - (I,REQ) - bytes
+/// b - (I,REQ) - bytes
+///
+/// Returns:
+/// true if a and b are equal
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
     }
 
-    block
+    let mut diff: u8 = 0;
+    for i in 0..a.len() {
+        diff |= a[i] ^ b[i];
+    }
+
+    std::hint::black_box(diff) == 0
+}
+
+/// Description:
+/// Overwrites secrets with zeros through volatile writes, which the compiler cannot
+/// remove as dead stores even when the memory is freed right afterwards.
+///
+/// Arguments:
+/// data - (I/O,REQ) - The values to clear
+pub fn zeroize<T: Copy + Default>(data: &mut [T]) {
+    for value in data.iter_mut() {
+        // SAFETY: value is a valid, aligned and exclusive reference.
+        unsafe { std::ptr::write_volatile(value, T::default()) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Description:
+/// Constant-time a < b.
+///
+/// Arguments:
+/// a - (I,REQ) - byte
+/// b - (I,REQ) - byte
+///
+/// Returns:
+/// 0xFF if a < b, otherwise 0x00
+pub fn ct_mask_lt(a: u8, b: u8) -> u8 {
+    ((a as u16).wrapping_sub(b as u16) >> 8) as u8
+}
+
+/// Description:
+/// Constant-time a != 0.
+///
+/// Arguments:
+/// a - (I,REQ) - byte
+///
+/// Returns:
+/// 0xFF if a is non-zero, otherwise 0x00
+pub fn ct_mask_nonzero(a: u8) -> u8 {
+    (0u16.wrapping_sub(a as u16) >> 8) as u8
 }
 
-pub fn block_to_message(block: [[u8; 4]; 4]) -> Vec<u8> {
-    let mut message = Vec::with_capacity(16);
+pub fn block_to_message(block: [[u8; 4]; 4]) -> [u8; 16] {
+    let mut message: [u8; 16] = [0; 16];
     for j in 0..4 {
         for i in 0..4 {
-            message.push(block[i][j]);
+            message[4*j+i] = block[i][j];
         }
     }
 
@@ -114,6 +231,22 @@ pub fn block_to_message(block: [[u8; 4]; 4]) -> Vec<u8> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn generate_s_box_test() {
+        assert_eq!(generate_s_box(), SBOX);
+        assert_eq!(generate_inv_s_box(), INV_SBOX);
+    }
+
+    #[test]
+    fn constant_time_sub_byte_test() {
+        for i in 0..=255 {
+            assert_eq!(constant_time_sub_byte(i), SBOX[i as usize]);
+            assert_eq!(constant_time_inv_sub_byte(i), INV_SBOX[i as usize]);
+            assert_eq!(sub_byte(i), SBOX[i as usize]);
+            assert_eq!(inv_sub_byte(i), INV_SBOX[i as usize]);
+        }
+    }
+
     #[test]
     fn circular_left_bit_shift_test() {
         let a: u8 = 0b_0011_1011;
@@ -146,16 +279,33 @@ mod tests {
     }
 
     #[test]
-    fn message_to_block_test() {
-        let s = vec![0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F];
-        let res = message_to_block(&s);
-        let actual: [[u8; 4]; 4] = [
-            [0x00,0x04,0x08,0xC],
-            [0x01,0x05,0x09,0xD],
-            [0x02,0x06,0x0A,0xE],
-            [0x03,0x07,0x0B,0xF]
-        ];
-        assert_eq!(res, actual)
+    fn constant_time_eq_test() {
+        assert!(constant_time_eq(&[0x01, 0x02, 0x03], &[0x01, 0x02, 0x03]));
+        assert!(!constant_time_eq(&[0x01, 0x02, 0x03], &[0x01, 0x02, 0x04]));
+        assert!(!constant_time_eq(&[0x01, 0x02, 0x03], &[0x01, 0x02]));
+        assert!(constant_time_eq(&[], &[]));
+    }
+
+    #[test]
+    fn ct_mask_test() {
+        assert_eq!(ct_mask_lt(3, 4), 0xFF);
+        assert_eq!(ct_mask_lt(4, 4), 0x00);
+        assert_eq!(ct_mask_lt(0xFF, 0x00), 0x00);
+        assert_eq!(ct_mask_lt(0x00, 0xFF), 0xFF);
+        assert_eq!(ct_mask_nonzero(0x00), 0x00);
+        assert_eq!(ct_mask_nonzero(0x01), 0xFF);
+        assert_eq!(ct_mask_nonzero(0x80), 0xFF);
+    }
+
+    #[test]
+    fn zeroize_test() {
+        let mut words: [u32; 4] = [1, 2, 3, 4];
+        zeroize(&mut words);
+        assert_eq!(words, [0; 4]);
+
+        let mut blocks: Vec<[u8; 16]> = vec![[0xff; 16]; 3];
+        zeroize(&mut blocks);
+        assert_eq!(blocks, vec![[0; 16]; 3]);
     }
 
     #[test]
@@ -167,7 +317,7 @@ mod tests {
             [0x03,0x07,0x0B,0xF]
         ];
         let res = block_to_message(block);
-        let actual = vec![0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F];
+        let actual: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F];
         assert_eq!(res, actual)       
     }
 }
\ No newline at end of file
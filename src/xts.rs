@@ -0,0 +1,282 @@
+use crate::cipher::BlockCipher;
+use crate::error::CryptoError;
+use crate::math::gf128_double;
+
+/// The longest data unit IEEE 1619 allows, 2^20 blocks
+pub const MAX_SECTOR_LENGTH: usize = 16 << 20;
+
+/// Blocks masked and encrypted together, so a backend can work on several at once
+const BATCH: usize = 8;
+
+/// Description:
+/// XTS-AES (IEEE 1619, SP 800-38E) for encrypting storage sectors in place.
+/// Each sector is a data unit whose tweak is its sector number encrypted under the second key;
+/// sectors that are not a multiple of 16 bytes use ciphertext stealing, so the cipher text is
+/// exactly as long as the plain text.
+pub struct Xts<'a, C: BlockCipher> {
+    data_cipher: &'a C,
+    tweak_cipher: &'a C,
+}
+
+impl<'a, C: BlockCipher> Xts<'a, C> {
+    /// Description:
+    /// Creates an XTS instance from the two halves of the key.
+    ///
+    /// Arguments:
+    /// data_cipher - (I,REQ) - The cipher keyed with Key1, which encrypts the data
+    /// tweak_cipher - (I,REQ) - The cipher keyed with Key2, which encrypts the sector number
+    ///
+    /// Returns:
+    /// The XTS instance
+    pub fn new(data_cipher: &'a C, tweak_cipher: &'a C) -> Self {
+        Xts { data_cipher, tweak_cipher }
+    }
+
+    /// Description:
+    /// Encrypts a sector in place.
+    ///
+    /// Arguments:
+    /// sector_no - (I,REQ) - The data unit sequence number
+    /// data - (I/O,REQ) - The sector, from 16 bytes up to 2^20 blocks
+    ///
+    /// Returns:
+    /// Ok, or InvalidLength for a sector that is too short or too long
+    pub fn encrypt_sector(&self, sector_no: u128, data: &mut [u8]) -> Result<(), CryptoError> {
+        self.process(sector_no, data, |blocks| self.data_cipher.encrypt_blocks(blocks), false)
+    }
+
+    /// Description:
+    /// Decrypts a sector in place.
+    ///
+    /// Arguments:
+    /// sector_no - (I,REQ) - The data unit sequence number used for encryption
+    /// data - (I/O,REQ) - The encrypted sector
+    ///
+    /// Returns:
+    /// Ok, or InvalidLength for a sector that is too short or too long
+    pub fn decrypt_sector(&self, sector_no: u128, data: &mut [u8]) -> Result<(), CryptoError> {
+        self.process(sector_no, data, |blocks| self.data_cipher.decrypt_blocks(blocks), true)
+    }
+
+    // Decryption steals with the last two tweaks swapped, since it has to undo the
+    // final full block before the partial block can be rebuilt
+    fn process(&self, sector_no: u128, data: &mut [u8], cipher: impl Fn(&mut [[u8; 16]]), decrypt: bool) -> Result<(), CryptoError> {
+        if data.len() < 16 || data.len() > MAX_SECTOR_LENGTH {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let partial = data.len() % 16;
+        let full_blocks = data.len() / 16 - if partial == 0 { 0 } else { 1 };
+        let (head, tail) = data.split_at_mut(16*full_blocks);
+
+        let mut tweak = u128::from_le_bytes(self.tweak_cipher.encrypt_block(sector_no.to_le_bytes()));
+        let (blocks, _) = head.as_chunks_mut::<16>();
+        for batch in blocks.chunks_mut(BATCH) {
+            tweak = xex(batch, tweak, &cipher);
+        }
+
+        if partial != 0 {
+            let (first_tweak, second_tweak) = if decrypt {
+                (gf128_double(tweak), tweak)
+            } else {
+                (tweak, gf128_double(tweak))
+            };
+
+            let mut block: [[u8; 16]; 1] = [tail[..16].try_into().unwrap()];
+            xex(&mut block, first_tweak, &cipher);
+            let [mut stolen] = block;
+            tail[..16].copy_from_slice(&stolen);
+
+            stolen[..partial].copy_from_slice(&tail[16..]);
+            tail.copy_within(..partial, 16);
+            let mut block: [[u8; 16]; 1] = [stolen];
+            xex(&mut block, second_tweak, &cipher);
+            tail[..16].copy_from_slice(&block[0]);
+        }
+
+        Ok(())
+    }
+}
+
+// Masks each block with its tweak before and after the cipher and returns the tweak for the next block
+fn xex(blocks: &mut [[u8; 16]], tweak: u128, cipher: &impl Fn(&mut [[u8; 16]])) -> u128 {
+    let mut tweaks: [u128; BATCH] = [0; BATCH];
+    let mut tweak = tweak;
+    for (block, t) in blocks.iter_mut().zip(&mut tweaks) {
+        *t = tweak;
+        *block = (u128::from_le_bytes(*block) ^ tweak).to_le_bytes();
+        tweak = gf128_double(tweak);
+    }
+
+    cipher(blocks);
+    for (block, t) in blocks.iter_mut().zip(tweaks) {
+        *block = (u128::from_le_bytes(*block) ^ t).to_le_bytes();
+    }
+
+    tweak
+}
+
+/// Description:
+/// Encrypts one sector with XTS-AES.
+///
+/// Arguments:
+/// data_cipher - (I,REQ) - The cipher keyed with Key1
+/// tweak_cipher - (I,REQ) - The cipher keyed with Key2
+/// sector_no - (I,REQ) - The data unit sequence number
+/// plain_text - (I,REQ) - The sector, at least 16 bytes
+///
+/// Returns:
+/// The encrypted sector
+#[allow(non_snake_case)]
+pub fn XTS_encrypt<C: BlockCipher>(data_cipher: &C, tweak_cipher: &C, sector_no: u128, plain_text: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    let mut cipher_text = plain_text;
+    Xts::new(data_cipher, tweak_cipher).encrypt_sector(sector_no, &mut cipher_text)?;
+
+    Ok(cipher_text)
+}
+
+/// Description:
+/// Decrypts one sector with XTS-AES.
+///
+/// Arguments:
+/// data_cipher - (I,REQ) - The cipher keyed with Key1
+/// tweak_cipher - (I,REQ) - The cipher keyed with Key2
+/// sector_no - (I,REQ) - The data unit sequence number
+/// cipher_text - (I,REQ) - The encrypted sector
+///
+/// Returns:
+/// The decrypted sector
+#[allow(non_snake_case)]
+pub fn XTS_decrypt<C: BlockCipher>(data_cipher: &C, tweak_cipher: &C, sector_no: u128, cipher_text: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    let mut plain_text = cipher_text;
+    Xts::new(data_cipher, tweak_cipher).decrypt_sector(sector_no, &mut plain_text)?;
+
+    Ok(plain_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::{Aes128,Aes256};
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn XTS_AES_128_test() {
+        // IEEE 1619 vector 1
+        let zero = Aes128::new([0; 4]);
+        let xts = Xts::new(&zero, &zero);
+        let mut res = [0; 32];
+        xts.encrypt_sector(0, &mut res).unwrap();
+        let actual: [u8; 32] = [0x91, 0x7c, 0xf6, 0x9e, 0xbd, 0x68, 0xb2, 0xec, 0x9b, 0x9f, 0xe9, 0xa3, 0xea, 0xdd, 0xa6, 0x92, 0xcd, 0x43, 0xd2, 0xf5, 0x95, 0x98, 0xed, 0x85, 0x8c, 0x02, 0xc2, 0x65, 0x2f, 0xbf, 0x92, 0x2e];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0, &mut res).unwrap();
+        assert_eq!(res, [0; 32]);
+
+        // Vector 2
+        let data_cipher = Aes128::new([0x11111111; 4]);
+        let tweak_cipher = Aes128::new([0x22222222; 4]);
+        let xts = Xts::new(&data_cipher, &tweak_cipher);
+        let mut res = [0x44; 32];
+        xts.encrypt_sector(0x3333333333, &mut res).unwrap();
+        let actual: [u8; 32] = [0xc4, 0x54, 0x18, 0x5e, 0x6a, 0x16, 0x93, 0x6e, 0x39, 0x33, 0x40, 0x38, 0xac, 0xef, 0x83, 0x8b, 0xfb, 0x18, 0x6f, 0xff, 0x74, 0x80, 0xad, 0xc4, 0x28, 0x93, 0x82, 0xec, 0xd6, 0xd3, 0x94, 0xf0];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0x3333333333, &mut res).unwrap();
+        assert_eq!(res, [0x44; 32]);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn XTS_AES_512_byte_sector_test() {
+        let plain_text: Vec<u8> = (0..512).map(|i| i as u8).collect();
+
+        // IEEE 1619 vector 4
+        let data_cipher = Aes128::new([0x27182818, 0x28459045, 0x23536028, 0x74713526]);
+        let tweak_cipher = Aes128::new([0x31415926, 0x53589793, 0x23846264, 0x33832795]);
+        let xts = Xts::new(&data_cipher, &tweak_cipher);
+        let mut res = plain_text.clone();
+        xts.encrypt_sector(0, &mut res).unwrap();
+        let actual: [u8; 512] = [0x27, 0xa7, 0x47, 0x9b, 0xef, 0xa1, 0xd4, 0x76, 0x48, 0x9f, 0x30, 0x8c, 0xd4, 0xcf, 0xa6, 0xe2, 0xa9, 0x6e, 0x4b, 0xbe, 0x32, 0x08, 0xff, 0x25, 0x28, 0x7d, 0xd3, 0x81, 0x96, 0x16, 0xe8, 0x9c, 0xc7, 0x8c, 0xf7, 0xf5, 0xe5, 0x43, 0x44, 0x5f, 0x83, 0x33, 0xd8, 0xfa, 0x7f, 0x56, 0x00, 0x00, 0x05, 0x27, 0x9f, 0xa5, 0xd8, 0xb5, 0xe4, 0xad, 0x40, 0xe7, 0x36, 0xdd, 0xb4, 0xd3, 0x54, 0x12, 0x32, 0x80, 0x63, 0xfd, 0x2a, 0xab, 0x53, 0xe5, 0xea, 0x1e, 0x0a, 0x9f, 0x33, 0x25, 0x00, 0xa5, 0xdf, 0x94, 0x87, 0xd0, 0x7a, 0x5c, 0x92, 0xcc, 0x51, 0x2c, 0x88, 0x66, 0xc7, 0xe8, 0x60, 0xce, 0x93, 0xfd, 0xf1, 0x66, 0xa2, 0x49, 0x12, 0xb4, 0x22, 0x97, 0x61, 0x46, 0xae, 0x20, 0xce, 0x84, 0x6b, 0xb7, 0xdc, 0x9b, 0xa9, 0x4a, 0x76, 0x7a, 0xae, 0xf2, 0x0c, 0x0d, 0x61, 0xad, 0x02, 0x65, 0x5e, 0xa9, 0x2d, 0xc4, 0xc4, 0xe4, 0x1a, 0x89, 0x52, 0xc6, 0x51, 0xd3, 0x31, 0x74, 0xbe, 0x51, 0xa1, 0x0c, 0x42, 0x11, 0x10, 0xe6, 0xd8, 0x15, 0x88, 0xed, 0xe8, 0x21, 0x03, 0xa2, 0x52, 0xd8, 0xa7, 0x50, 0xe8, 0x76, 0x8d, 0xef, 0xff, 0xed, 0x91, 0x22, 0x81, 0x0a, 0xae, 0xb9, 0x9f, 0x91, 0x72, 0xaf, 0x82, 0xb6, 0x04, 0xdc, 0x4b, 0x8e, 0x51, 0xbc, 0xb0, 0x82, 0x35, 0xa6, 0xf4, 0x34, 0x13, 0x32, 0xe4, 0xca, 0x60, 0x48, 0x2a, 0x4b, 0xa1, 0xa0, 0x3b, 0x3e, 0x65, 0x00, 0x8f, 0xc5, 0xda, 0x76, 0xb7, 0x0b, 0xf1, 0x69, 0x0d, 0xb4, 0xea, 0xe2, 0x9c, 0x5f, 0x1b, 0xad, 0xd0, 0x3c, 0x5c, 0xcf, 0x2a, 0x55, 0xd7, 0x05, 0xdd, 0xcd, 0x86, 0xd4, 0x49, 0x51, 0x1c, 0xeb, 0x7e, 0xc3, 0x0b, 0xf1, 0x2b, 0x1f, 0xa3, 0x5b, 0x91, 0x3f, 0x9f, 0x74, 0x7a, 0x8a, 0xfd, 0x1b, 0x13, 0x0e, 0x94, 0xbf, 0xf9, 0x4e, 0xff, 0xd0, 0x1a, 0x91, 0x73, 0x5c, 0xa1, 0x72, 0x6a, 0xcd, 0x0b, 0x19, 0x7c, 0x4e, 0x5b, 0x03, 0x39, 0x36, 0x97, 0xe1, 0x26, 0x82, 0x6f, 0xb6, 0xbb, 0xde, 0x8e, 0xcc, 0x1e, 0x08, 0x29, 0x85, 0x16, 0xe2, 0xc9, 0xed, 0x03, 0xff, 0x3c, 0x1b, 0x78, 0x60, 0xf6, 0xde, 0x76, 0xd4, 0xce, 0xcd, 0x94, 0xc8, 0x11, 0x98, 0x55, 0xef, 0x52, 0x97, 0xca, 0x67, 0xe9, 0xf3, 0xe7, 0xff, 0x72, 0xb1, 0xe9, 0x97, 0x85, 0xca, 0x0a, 0x7e, 0x77, 0x20, 0xc5, 0xb3, 0x6d, 0xc6, 0xd7, 0x2c, 0xac, 0x95, 0x74, 0xc8, 0xcb, 0xbc, 0x2f, 0x80, 0x1e, 0x23, 0xe5, 0x6f, 0xd3, 0x44, 0xb0, 0x7f, 0x22, 0x15, 0x4b, 0xeb, 0xa0, 0xf0, 0x8c, 0xe8, 0x89, 0x1e, 0x64, 0x3e, 0xd9, 0x95, 0xc9, 0x4d, 0x9a, 0x69, 0xc9, 0xf1, 0xb5, 0xf4, 0x99, 0x02, 0x7a, 0x78, 0x57, 0x2a, 0xee, 0xbd, 0x74, 0xd2, 0x0c, 0xc3, 0x98, 0x81, 0xc2, 0x13, 0xee, 0x77, 0x0b, 0x10, 0x10, 0xe4, 0xbe, 0xa7, 0x18, 0x84, 0x69, 0x77, 0xae, 0x11, 0x9f, 0x7a, 0x02, 0x3a, 0xb5, 0x8c, 0xca, 0x0a, 0xd7, 0x52, 0xaf, 0xe6, 0x56, 0xbb, 0x3c, 0x17, 0x25, 0x6a, 0x9f, 0x6e, 0x9b, 0xf1, 0x9f, 0xdd, 0x5a, 0x38, 0xfc, 0x82, 0xbb, 0xe8, 0x72, 0xc5, 0x53, 0x9e, 0xdb, 0x60, 0x9e, 0xf4, 0xf7, 0x9c, 0x20, 0x3e, 0xbb, 0x14, 0x0f, 0x2e, 0x58, 0x3c, 0xb2, 0xad, 0x15, 0xb4, 0xaa, 0x5b, 0x65, 0x50, 0x16, 0xa8, 0x44, 0x92, 0x77, 0xdb, 0xd4, 0x77, 0xef, 0x2c, 0x8d, 0x6c, 0x01, 0x7d, 0xb7, 0x38, 0xb1, 0x8d, 0xeb, 0x4a, 0x42, 0x7d, 0x19, 0x23, 0xce, 0x3f, 0xf2, 0x62, 0x73, 0x57, 0x79, 0xa4, 0x18, 0xf2, 0x0a, 0x28, 0x2d, 0xf9, 0x20, 0x14, 0x7b, 0xea, 0xbe, 0x42, 0x1e, 0xe5, 0x31, 0x9d, 0x05, 0x68];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0, &mut res).unwrap();
+        assert_eq!(res, plain_text);
+
+        // IEEE 1619 vector 10, XTS-AES-256
+        let data_cipher = Aes256::new([0x27182818, 0x28459045, 0x23536028, 0x74713526, 0x62497757, 0x24709369, 0x99595749, 0x66967627]);
+        let tweak_cipher = Aes256::new([0x31415926, 0x53589793, 0x23846264, 0x33832795, 0x02884197, 0x16939937, 0x51058209, 0x74944592]);
+        let xts = Xts::new(&data_cipher, &tweak_cipher);
+        let mut res = plain_text.clone();
+        xts.encrypt_sector(0xff, &mut res).unwrap();
+        let actual: [u8; 512] = [0x1c, 0x3b, 0x3a, 0x10, 0x2f, 0x77, 0x03, 0x86, 0xe4, 0x83, 0x6c, 0x99, 0xe3, 0x70, 0xcf, 0x9b, 0xea, 0x00, 0x80, 0x3f, 0x5e, 0x48, 0x23, 0x57, 0xa4, 0xae, 0x12, 0xd4, 0x14, 0xa3, 0xe6, 0x3b, 0x5d, 0x31, 0xe2, 0x76, 0xf8, 0xfe, 0x4a, 0x8d, 0x66, 0xb3, 0x17, 0xf9, 0xac, 0x68, 0x3f, 0x44, 0x68, 0x0a, 0x86, 0xac, 0x35, 0xad, 0xfc, 0x33, 0x45, 0xbe, 0xfe, 0xcb, 0x4b, 0xb1, 0x88, 0xfd, 0x57, 0x76, 0x92, 0x6c, 0x49, 0xa3, 0x09, 0x5e, 0xb1, 0x08, 0xfd, 0x10, 0x98, 0xba, 0xec, 0x70, 0xaa, 0xa6, 0x69, 0x99, 0xa7, 0x2a, 0x82, 0xf2, 0x7d, 0x84, 0x8b, 0x21, 0xd4, 0xa7, 0x41, 0xb0, 0xc5, 0xcd, 0x4d, 0x5f, 0xff, 0x9d, 0xac, 0x89, 0xae, 0xba, 0x12, 0x29, 0x61, 0xd0, 0x3a, 0x75, 0x71, 0x23, 0xe9, 0x87, 0x0f, 0x8a, 0xcf, 0x10, 0x00, 0x02, 0x08, 0x87, 0x89, 0x14, 0x29, 0xca, 0x2a, 0x3e, 0x7a, 0x7d, 0x7d, 0xf7, 0xb1, 0x03, 0x55, 0x16, 0x5c, 0x8b, 0x9a, 0x6d, 0x0a, 0x7d, 0xe8, 0xb0, 0x62, 0xc4, 0x50, 0x0d, 0xc4, 0xcd, 0x12, 0x0c, 0x0f, 0x74, 0x18, 0xda, 0xe3, 0xd0, 0xb5, 0x78, 0x1c, 0x34, 0x80, 0x3f, 0xa7, 0x54, 0x21, 0xc7, 0x90, 0xdf, 0xe1, 0xde, 0x18, 0x34, 0xf2, 0x80, 0xd7, 0x66, 0x7b, 0x32, 0x7f, 0x6c, 0x8c, 0xd7, 0x55, 0x7e, 0x12, 0xac, 0x3a, 0x0f, 0x93, 0xec, 0x05, 0xc5, 0x2e, 0x04, 0x93, 0xef, 0x31, 0xa1, 0x2d, 0x3d, 0x92, 0x60, 0xf7, 0x9a, 0x28, 0x9d, 0x6a, 0x37, 0x9b, 0xc7, 0x0c, 0x50, 0x84, 0x14, 0x73, 0xd1, 0xa8, 0xcc, 0x81, 0xec, 0x58, 0x3e, 0x96, 0x45, 0xe0, 0x7b, 0x8d, 0x96, 0x70, 0x65, 0x5b, 0xa5, 0xbb, 0xcf, 0xec, 0xc6, 0xdc, 0x39, 0x66, 0x38, 0x0a, 0xd8, 0xfe, 0xcb, 0x17, 0xb6, 0xba, 0x02, 0x46, 0x9a, 0x02, 0x0a, 0x84, 0xe1, 0x8e, 0x8f, 0x84, 0x25, 0x20, 0x70, 0xc1, 0x3e, 0x9f, 0x1f, 0x28, 0x9b, 0xe5, 0x4f, 0xbc, 0x48, 0x14, 0x57, 0x77, 0x8f, 0x61, 0x60, 0x15, 0xe1, 0x32, 0x7a, 0x02, 0xb1, 0x40, 0xf1, 0x50, 0x5e, 0xb3, 0x09, 0x32, 0x6d, 0x68, 0x37, 0x8f, 0x83, 0x74, 0x59, 0x5c, 0x84, 0x9d, 0x84, 0xf4, 0xc3, 0x33, 0xec, 0x44, 0x23, 0x88, 0x51, 0x43, 0xcb, 0x47, 0xbd, 0x71, 0xc5, 0xed, 0xae, 0x9b, 0xe6, 0x9a, 0x2f, 0xfe, 0xce, 0xb1, 0xbe, 0xc9, 0xde, 0x24, 0x4f, 0xbe, 0x15, 0x99, 0x2b, 0x11, 0xb7, 0x7c, 0x04, 0x0f, 0x12, 0xbd, 0x8f, 0x6a, 0x97, 0x5a, 0x44, 0xa0, 0xf9, 0x0c, 0x29, 0xa9, 0xab, 0xc3, 0xd4, 0xd8, 0x93, 0x92, 0x72, 0x84, 0xc5, 0x87, 0x54, 0xcc, 0xe2, 0x94, 0x52, 0x9f, 0x86, 0x14, 0xdc, 0xd2, 0xab, 0xa9, 0x91, 0x92, 0x5f, 0xed, 0xc4, 0xae, 0x74, 0xff, 0xac, 0x6e, 0x33, 0x3b, 0x93, 0xeb, 0x4a, 0xff, 0x04, 0x79, 0xda, 0x9a, 0x41, 0x0e, 0x44, 0x50, 0xe0, 0xdd, 0x7a, 0xe4, 0xc6, 0xe2, 0x91, 0x09, 0x00, 0x57, 0x5d, 0xa4, 0x01, 0xfc, 0x07, 0x05, 0x9f, 0x64, 0x5e, 0x8b, 0x7e, 0x9b, 0xfd, 0xef, 0x33, 0x94, 0x30, 0x54, 0xff, 0x84, 0x01, 0x14, 0x93, 0xc2, 0x7b, 0x34, 0x29, 0xea, 0xed, 0xb4, 0xed, 0x53, 0x76, 0x44, 0x1a, 0x77, 0xed, 0x43, 0x85, 0x1a, 0xd7, 0x7f, 0x16, 0xf5, 0x41, 0xdf, 0xd2, 0x69, 0xd5, 0x0d, 0x6a, 0x5f, 0x14, 0xfb, 0x0a, 0xab, 0x1c, 0xbb, 0x4c, 0x15, 0x50, 0xbe, 0x97, 0xf7, 0xab, 0x40, 0x66, 0x19, 0x3c, 0x4c, 0xaa, 0x77, 0x3d, 0xad, 0x38, 0x01, 0x4b, 0xd2, 0x09, 0x2f, 0xa7, 0x55, 0xc8, 0x24, 0xbb, 0x5e, 0x54, 0xc4, 0xf3, 0x6f, 0xfd, 0xa9, 0xfc, 0xea, 0x70, 0xb9, 0xc6, 0xe6, 0x93, 0xe1, 0x48, 0xc1, 0x51];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0xff, &mut res).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    fn ciphertext_stealing_test() {
+        // IEEE 1619 vectors 15 to 18
+        let data_cipher = Aes128::new([0xfffefdfc, 0xfbfaf9f8, 0xf7f6f5f4, 0xf3f2f1f0]);
+        let tweak_cipher = Aes128::new([0xbfbebdbc, 0xbbbab9b8, 0xb7b6b5b4, 0xb3b2b1b0]);
+        let xts = Xts::new(&data_cipher, &tweak_cipher);
+        let plain_text: Vec<u8> = (0x00..0x14).collect();
+
+        let mut res = plain_text[..17].to_vec();
+        xts.encrypt_sector(0x123456789a, &mut res).unwrap();
+        let actual: [u8; 17] = [0x6c, 0x16, 0x25, 0xdb, 0x46, 0x71, 0x52, 0x2d, 0x3d, 0x75, 0x99, 0x60, 0x1d, 0xe7, 0xca, 0x09, 0xed];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0x123456789a, &mut res).unwrap();
+        assert_eq!(res, &plain_text[..17]);
+
+        let mut res = plain_text[..18].to_vec();
+        xts.encrypt_sector(0x123456789a, &mut res).unwrap();
+        let actual: [u8; 18] = [0xd0, 0x69, 0x44, 0x4b, 0x7a, 0x7e, 0x0c, 0xab, 0x09, 0xe2, 0x44, 0x47, 0xd2, 0x4d, 0xeb, 0x1f, 0xed, 0xbf];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0x123456789a, &mut res).unwrap();
+        assert_eq!(res, &plain_text[..18]);
+
+        let mut res = plain_text[..19].to_vec();
+        xts.encrypt_sector(0x123456789a, &mut res).unwrap();
+        let actual: [u8; 19] = [0xe5, 0xdf, 0x13, 0x51, 0xc0, 0x54, 0x4b, 0xa1, 0x35, 0x0b, 0x33, 0x63, 0xcd, 0x8e, 0xf4, 0xbe, 0xed, 0xbf, 0x9d];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0x123456789a, &mut res).unwrap();
+        assert_eq!(res, &plain_text[..19]);
+
+        let mut res = plain_text.clone();
+        xts.encrypt_sector(0x123456789a, &mut res).unwrap();
+        let actual: [u8; 20] = [0x9d, 0x84, 0xc8, 0x13, 0xf7, 0x19, 0xaa, 0x2c, 0x7b, 0xe3, 0xf6, 0x61, 0x71, 0xc7, 0xc5, 0xc2, 0xed, 0xbf, 0x9d, 0xac];
+        assert_eq!(res, actual);
+
+        xts.decrypt_sector(0x123456789a, &mut res).unwrap();
+        assert_eq!(res, plain_text);
+    }
+
+    #[test]
+    fn sector_length_test() {
+        let data_cipher = Aes128::new([0x11111111; 4]);
+        let tweak_cipher = Aes128::new([0x22222222; 4]);
+        let xts = Xts::new(&data_cipher, &tweak_cipher);
+
+        // Every length round trips, and a stolen tail agrees with the unstolen prefix before it
+        let plain_text: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        for length in 16..=plain_text.len() {
+            let mut data = plain_text[..length].to_vec();
+            xts.encrypt_sector(7, &mut data).unwrap();
+            let whole = 16 * (length/16 - if length % 16 == 0 { 0 } else { 1 });
+            let prefix = XTS_encrypt(&data_cipher, &tweak_cipher, 7, plain_text[..whole].to_vec()).unwrap_or_default();
+            assert_eq!(data[..prefix.len()], prefix);
+
+            xts.decrypt_sector(7, &mut data).unwrap();
+            assert_eq!(data, &plain_text[..length]);
+        }
+
+        assert_eq!(xts.encrypt_sector(0, &mut [0; 15]), Err(CryptoError::InvalidLength));
+        assert_eq!(XTS_decrypt(&data_cipher, &tweak_cipher, 0, vec![]), Err(CryptoError::InvalidLength));
+    }
+}
\ No newline at end of file